@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+
+use crate::subcommand::CheckSummary;
+
+/// 配置中的已知问题知识库：含有某个关键字的命中行附上对应的说明，如排查建议、缺陷单号
+pub struct Annotations {
+    notes: BTreeMap<String, String>,
+}
+
+impl Annotations {
+    pub fn new(notes: BTreeMap<String, String>) -> Self {
+        Annotations { notes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// `text` 命中的所有说明，按关键字排序
+    fn notes_for(&self, text: &str) -> Vec<String> {
+        self.notes
+            .iter()
+            .filter(|(pattern, _)| text.contains(pattern.as_str()))
+            .map(|(_, note)| note.clone())
+            .collect()
+    }
+
+    /// 为记录的命中行 (不含上下文行) 附上说明
+    pub fn annotate(&self, summary: &mut CheckSummary) {
+        for matched in summary.matched_lines.iter_mut().filter(|m| !m.context) {
+            matched.annotations = self.notes_for(&matched.text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notes_for() {
+        let annotations = Annotations::new(BTreeMap::from([
+            (
+                "ERRCODE_MSOPTIMEOUT".to_string(),
+                "known issue JIRA-1234, fixed in 2.3".to_string(),
+            ),
+            ("disk full".to_string(), "clean /data".to_string()),
+        ]));
+        assert_eq!(
+            annotations.notes_for("[error] request failed: ERRCODE_MSOPTIMEOUT, disk full"),
+            ["known issue JIRA-1234, fixed in 2.3", "clean /data"]
+        );
+        assert!(annotations.notes_for("[info] ok").is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+
+use crate::{context::AppContext, extractor::metric_extractor, record::parse_line};
+
+/// MAD 换算为正态分布标准差的系数
+const MAD_SCALE: f64 = 1.4826;
+
+/// MAD 为 0 时 (指标长时间不变) 使用的最小偏差，单位与指标相同 (cpu/mem 为百分点)
+const MIN_DEVIATION: f64 = 0.5;
+
+#[derive(Parser)]
+pub struct AnomaliesArgs {
+    /// 文件路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 需要检测的指标：内置的 cpu、mem 或配置中的自定义指标
+    #[arg(short, long, default_value = "cpu")]
+    pub metric: String,
+
+    /// 滚动窗口大小 (之前的采样点个数)
+    #[arg(short, long, default_value_t = 20)]
+    pub window: usize,
+
+    /// 偏离中位数超过多少倍偏差视为异常
+    #[arg(short, long, default_value_t = 3.0)]
+    pub threshold: f64,
+}
+
+pub fn process_anomalies(ctx: &AppContext, args: AnomaliesArgs) -> Result<()> {
+    if args.window < 3 {
+        bail!("❌ window should be at least 3");
+    }
+
+    let extractor = metric_extractor(ctx, &args.metric)?;
+    let path = ctx.resolve_path(args.path)?;
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    let samples = content
+        .lines()
+        .filter_map(parse_line)
+        .filter_map(|r| Some((r.time, extractor.extract(r.message)?)))
+        .collect::<Vec<_>>();
+
+    let values = samples.iter().map(|&(_, v)| v).collect::<Vec<_>>();
+    let anomalies = detect_anomalies(&values, args.window, args.threshold);
+
+    let unit = extractor.unit();
+    for anomaly in &anomalies {
+        let (time, value) = samples[anomaly.index];
+        println!(
+            "{time}  {}: {value:.2}{unit}  (median {:.2}{unit}, deviation {:.2})",
+            extractor.name(),
+            anomaly.median,
+            anomaly.deviation
+        );
+    }
+
+    println!(
+        "file: {}, samples: {}, anomalies: {}",
+        path.display(),
+        samples.len(),
+        anomalies.len()
+    );
+
+    Ok(())
+}
+
+struct Anomaly {
+    index: usize,
+    median: f64,
+    deviation: f64,
+}
+
+/// 以之前 `window` 个采样点的中位数和 MAD 为基准，偏离超过 `threshold` 倍的点视为异常
+fn detect_anomalies(values: &[f64], window: usize, threshold: f64) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+
+    for (index, &value) in values.iter().enumerate().skip(window) {
+        let mut history = values[index - window..index].to_vec();
+        let median = median(&mut history);
+        let mut deviations = history
+            .iter()
+            .map(|v| (v - median).abs())
+            .collect::<Vec<_>>();
+        let deviation = (MAD_SCALE * self::median(&mut deviations)).max(MIN_DEVIATION);
+
+        if (value - median).abs() > threshold * deviation {
+            anomalies.push(Anomaly {
+                index,
+                median,
+                deviation,
+            });
+        }
+    }
+
+    anomalies
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_unstable_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_anomalies() {
+        let mut values = vec![5.0, 5.5, 6.0, 5.2, 5.8, 6.1, 5.4, 5.9, 5.6, 5.3];
+        values.extend([48.0, 5.7, 5.5, 0.0, 5.6]);
+
+        let anomalies = detect_anomalies(&values, 8, 3.0);
+        let indexes = anomalies.iter().map(|a| a.index).collect::<Vec<_>>();
+        assert_eq!(indexes, vec![10, 13]);
+
+        let flat = vec![1.0; 30];
+        assert!(detect_anomalies(&flat, 10, 3.0).is_empty());
+    }
+}
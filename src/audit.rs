@@ -0,0 +1,206 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Ok, Result, anyhow};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::{
+    context::AppContext,
+    time::{format_timestamp, parse_timestamp},
+    units::format_size,
+};
+
+const AUDIT_FILE: &str = "audit.jsonl";
+
+#[derive(Parser)]
+pub struct AuditArgs {
+    /// 只显示路径包含该内容的记录
+    #[arg(short, long)]
+    pub path: Option<String>,
+
+    /// 只显示该命令的记录，如 rf、clean
+    #[arg(short, long)]
+    pub command: Option<String>,
+
+    /// 只显示该时间 (UTC) 之后的记录，如 "2026-01-06 10:00"
+    #[arg(short, long)]
+    pub since: Option<String>,
+
+    /// 最多显示最近多少条
+    #[arg(short = 'n', long, default_value_t = 50)]
+    pub limit: usize,
+}
+
+/// 一条破坏性操作记录，在操作前采集文件大小与哈希
+#[derive(Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: i64,
+    pub user: String,
+    pub command: String,
+    pub action: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// 采集 `path` (文件或目录下所有文件) 的审计记录，需在删除/改写之前调用
+pub fn audit_entries(command: &str, action: &str, path: &Path) -> Result<Vec<AuditEntry>> {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    let user = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| {
+            let path = e.path();
+            Ok(AuditEntry {
+                time,
+                user: user.clone(),
+                command: command.to_string(),
+                action: action.to_string(),
+                path: path.to_path_buf(),
+                size: e.metadata()?.len(),
+                sha256: sha256_file(path)?,
+            })
+        })
+        .collect()
+}
+
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// 追加写入审计日志，加锁避免并发进程交错写入
+pub fn append_audit(ctx: &AppContext, entries: &[AuditEntry]) -> Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(ctx.config_file(AUDIT_FILE)?)?;
+    file.lock()?;
+
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry)?);
+        content.push('\n');
+    }
+    file.write_all(content.as_bytes())?;
+
+    Ok(())
+}
+
+pub fn process_audit(ctx: &AppContext, args: AuditArgs) -> Result<()> {
+    let path = ctx.config_file(AUDIT_FILE)?;
+    if !path.exists() {
+        println!("no audit records");
+        return Ok(());
+    }
+
+    let since = match args.since.as_deref() {
+        Some(s) => Some(parse_timestamp(s).ok_or_else(|| anyhow!("❌ invalid time: {s}"))?),
+        None => None,
+    };
+
+    let mut entries = Vec::new();
+    for line in BufReader::new(File::open(&path)?).lines() {
+        let entry: AuditEntry = serde_json::from_str(&line?)?;
+        if since.is_some_and(|since| entry.time * 1000 < since)
+            || args.command.as_ref().is_some_and(|c| &entry.command != c)
+            || args
+                .path
+                .as_ref()
+                .is_some_and(|p| !entry.path.display().to_string().contains(p.as_str()))
+        {
+            continue;
+        }
+        entries.push(entry);
+    }
+
+    let skip = entries.len().saturating_sub(args.limit);
+    for entry in &entries[skip..] {
+        println!(
+            "{} UTC  {:<10} {:<8} {:<8} {:>10}  {}  {}",
+            &format_timestamp(entry.time * 1000)[..19],
+            entry.user,
+            entry.command,
+            entry.action,
+            format_size(entry.size),
+            &entry.sha256[..12.min(entry.sha256.len())],
+            entry.path.display()
+        );
+    }
+    println!(
+        "records: {}, shown: {}",
+        entries.len(),
+        entries.len() - skip
+    );
+
+    Ok(())
+}
+
+/// 用 `new_path` 原子替换 `path` 并记录审计，`backup` 不为空时原文件先硬链接 (不支持时复制) 为备份，
+/// 替换前后 `path` 始终存在，中途崩溃不会丢失原文件
+pub fn replace_audited(
+    ctx: &AppContext,
+    command: &str,
+    path: &Path,
+    new_path: &Path,
+    backup: Option<&Path>,
+) -> Result<()> {
+    let entries = audit_entries(command, "rewrite", path)?;
+
+    if let Some(backup) = backup {
+        // 与改名一致，覆盖已有的备份
+        if backup.exists() {
+            fs::remove_file(backup)?;
+        }
+        if fs::hard_link(path, backup).is_err() {
+            fs::copy(path, backup)?;
+        }
+    }
+    fs::rename(new_path, path)?;
+
+    append_audit(ctx, &entries)
+}
+
+/// 移到系统回收站，审计记录的 action 为 `trash`
+pub fn trash_audited(ctx: &AppContext, command: &str, path: &Path) -> Result<()> {
+    let entries = audit_entries(command, "trash", path)?;
+    trash::delete(path)?;
+
+    append_audit(ctx, &entries)
+}
+
+/// 删除 `path` 并写审计日志
+pub fn remove_audited(ctx: &AppContext, command: &str, path: &Path) -> Result<()> {
+    let entries = audit_entries(command, "delete", path)?;
+
+    if path.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+
+    append_audit(ctx, &entries)
+}
@@ -0,0 +1,186 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use walkdir::WalkDir;
+use zip::{AesMode, CompressionMethod, ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    compress::plain_path,
+    context::AppContext,
+    entries::{OUTPUT_SUFFIXES, is_internal},
+    temp::InFlight,
+    units::format_size,
+};
+
+#[derive(Parser)]
+pub struct BundleArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 输出的 zip 文件
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// 只打包 rl、transform、sample、dedup 生成的 xxx_filtered、xxx_transformed、xxx_sample、xxx_dedup 文件，
+    /// 包括 `.gz` 结果
+    #[arg(long, default_value_t = false)]
+    pub filtered: bool,
+
+    /// 使用 AES-256 加密
+    #[arg(long, default_value_t = false, requires = "password_env")]
+    pub encrypt: bool,
+
+    /// 保存密码的环境变量名，避免密码出现在命令行和历史记录中
+    #[arg(long, value_name = "VAR")]
+    pub password_env: Option<String>,
+}
+
+/// 打包时使用的条目名：相对路径，统一使用 `/` 分隔
+fn entry_name(root: &Path, file: &Path) -> String {
+    let rel = file.strip_prefix(root).unwrap_or(file);
+    let rel = if rel.as_os_str().is_empty() {
+        Path::new(file.file_name().unwrap_or_default())
+    } else {
+        rel
+    };
+
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 是否为处理结果，`.gz` 按解压后的文件名判断
+fn is_output(path: &Path) -> bool {
+    plain_path(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| OUTPUT_SUFFIXES.iter().any(|suffix| stem.ends_with(suffix)))
+}
+
+pub fn process_bundle(ctx: &AppContext, args: BundleArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    let password = match (&args.password_env, args.encrypt) {
+        (Some(var), true) => match env::var(var) {
+            Ok(password) if !password.is_empty() => Some(password),
+            _ => bail!("❌ environment variable {var} is not set or empty"),
+        },
+        (Some(_), false) => bail!("❌ --password-env only works with --encrypt"),
+        _ => None,
+    };
+
+    let root = if path.is_dir() {
+        path.clone()
+    } else {
+        path.parent().map(Path::to_path_buf).unwrap_or_default()
+    };
+    let output = fs::canonicalize(args.output.parent().unwrap_or(Path::new(".")))
+        .map(|dir| dir.join(args.output.file_name().unwrap_or_default()))
+        .unwrap_or_else(|_| args.output.clone());
+
+    let mut files = WalkDir::new(&path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| !is_internal(e.path().strip_prefix(&path).unwrap_or(e.path())))
+        .map(|e| e.into_path())
+        .filter(|file| !args.filtered || is_output(file))
+        .filter(|file| fs::canonicalize(file).map_or(true, |file| file != output))
+        .collect::<Vec<_>>();
+    files.sort();
+    if files.is_empty() {
+        bail!("❌ no files to bundle under {}", path.display());
+    }
+
+    let mut options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+    if let Some(password) = &password {
+        options = options.with_aes_encryption(AesMode::Aes256, password);
+    }
+
+    // 中途失败时不留下不完整的压缩包
+    let partial = InFlight::register(&args.output);
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(&args.output)?));
+    let mut total = 0;
+    for file in &files {
+        zip.start_file(entry_name(&root, file), options)?;
+        total += io::copy(&mut File::open(file)?, &mut zip)?;
+    }
+    zip.finish()?.into_inner().map_err(|e| e.into_error())?;
+    partial.commit();
+
+    println!(
+        "write bundle, path: {:?}, files: {}, size: {}, encrypted: {}",
+        args.output.display(),
+        files.len(),
+        format_size(total),
+        password.is_some()
+    );
+    if password.is_none() {
+        println!(
+            "⚠️ bundle is not encrypted, use --encrypt --password-env VAR before sending it outside"
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_name() {
+        let root = Path::new("logs");
+        assert_eq!(
+            entry_name(root, Path::new("logs/node1/a_filtered.log")),
+            "node1/a_filtered.log"
+        );
+        assert_eq!(entry_name(root, Path::new("logs")), "logs");
+        assert!(is_output(Path::new("logs/a_filtered.log")));
+        assert!(is_output(Path::new("logs/a_filtered.log.gz")));
+        assert!(is_output(Path::new("logs/a_transformed.log")));
+        assert!(is_output(Path::new("logs/a_dedup.log.gz")));
+        assert!(!is_output(Path::new("logs/a_filtered.stats.json")));
+        assert!(!is_output(Path::new("logs/a.log")));
+        assert!(!is_output(Path::new("logs/a.log.gz")));
+    }
+
+    #[test]
+    fn test_bundle_skips_internal_files() {
+        let dir = env::temp_dir().join(format!("lp_bundle_test_{}", std::process::id()));
+        let logs = dir.join("logs");
+        fs::create_dir_all(logs.join(".lp_undo/1")).unwrap();
+        fs::write(logs.join("a.log"), "a\n").unwrap();
+        fs::write(logs.join(".lp_undo/1/a.log"), "original\n").unwrap();
+        fs::write(logs.join(".a.log.lp.lock"), "").unwrap();
+        let output = dir.join("bundle.zip");
+
+        let ctx = AppContext::new("/nonexistent/config.json");
+        let args = BundleArgs::try_parse_from([
+            "bundle",
+            "-p",
+            logs.to_str().unwrap(),
+            "-o",
+            output.to_str().unwrap(),
+        ])
+        .unwrap();
+        process_bundle(&ctx, args).unwrap();
+        let zip = zip::ZipArchive::new(File::open(&output).unwrap()).unwrap();
+        assert_eq!(zip.file_names().collect::<Vec<_>>(), ["a.log"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
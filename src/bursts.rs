@@ -0,0 +1,195 @@
+use std::{
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    compress::open_log,
+    context::AppContext,
+    exit::Failures,
+    subcommand::get_entries,
+    table::print_table,
+    time::format_timestamp,
+    timestamp::{TimestampFormat, Timestamps},
+};
+
+#[derive(Parser)]
+pub struct BurstsArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 连续多少行时间戳完全相同 (精确到毫秒) 时视为突发
+    #[arg(short, long, default_value_t = 50)]
+    pub threshold: usize,
+
+    /// 以 json 格式输出
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+/// 一段连续的、时间戳相同的行，行号从 1 开始
+#[derive(Serialize)]
+struct Burst {
+    time: i64,
+    first_line: usize,
+    last_line: usize,
+    lines: usize,
+}
+
+#[derive(Serialize)]
+struct FileBursts {
+    path: PathBuf,
+    bursts: Vec<Burst>,
+}
+
+/// 找出时间戳相同的连续行数不少于 `threshold` 的片段；没有时间戳的续行不计数，也不打断片段
+fn find_bursts<I: Iterator<Item = Result<String>>>(
+    lines: I,
+    threshold: usize,
+    format: TimestampFormat,
+) -> Result<Vec<Burst>> {
+    let mut bursts = Vec::new();
+    let mut current: Option<Burst> = None;
+    for (i, line) in lines.enumerate() {
+        let Some(time) = format.parse(&line?) else {
+            continue;
+        };
+        let line_no = i + 1;
+        match &mut current {
+            Some(burst) if burst.time == time => {
+                burst.last_line = line_no;
+                burst.lines += 1;
+            }
+            _ => {
+                if let Some(burst) = current.take().filter(|b| b.lines >= threshold) {
+                    bursts.push(burst);
+                }
+                current = Some(Burst {
+                    time,
+                    first_line: line_no,
+                    last_line: line_no,
+                    lines: 1,
+                });
+            }
+        }
+    }
+    bursts.extend(current.filter(|b| b.lines >= threshold));
+
+    Ok(bursts)
+}
+
+fn file_bursts(path: &Path, threshold: usize, format: TimestampFormat) -> Result<Vec<Burst>> {
+    let lines = open_log(path)?.lines().map(|line| Ok(line?));
+    find_bursts(lines, threshold, format)
+}
+
+pub fn process_bursts(ctx: &AppContext, args: BurstsArgs) -> Result<()> {
+    if args.threshold < 2 {
+        bail!("❌ threshold should be at least 2");
+    }
+    let path = ctx.resolve_path(args.path)?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    let mut files = if path.is_dir() {
+        get_entries(&path)
+            .into_iter()
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        vec![path]
+    };
+    files.sort();
+    let timestamps = Timestamps::load(ctx)?;
+
+    let failures = Failures::new(ctx);
+    let reports = files
+        .into_par_iter()
+        .filter_map(|file| {
+            if failures.should_stop() {
+                return None;
+            }
+            file_bursts(&file, args.threshold, timestamps.format_for(&file))
+                .inspect_err(|e| {
+                    eprintln!("❌ bursts failed, path {:?}, reason: {}", file, e);
+                    failures.record(&file, e);
+                })
+                .ok()
+                .filter(|bursts| !bursts.is_empty())
+                .map(|bursts| FileBursts { path: file, bursts })
+        })
+        .collect::<Vec<_>>();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return failures.finish();
+    }
+
+    for report in &reports {
+        println!(
+            "⚠️ {}: {} bursts",
+            report.path.display(),
+            report.bursts.len()
+        );
+        let rows = report
+            .bursts
+            .iter()
+            .map(|b| {
+                vec![
+                    format_timestamp(b.time),
+                    format!("{}-{}", b.first_line, b.last_line),
+                    b.lines.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(&["time", "line range", "lines"], &rows);
+        println!();
+    }
+    println!(
+        "files with bursts: {}, bursts: {}",
+        reports.len(),
+        reports.iter().map(|r| r.bursts.len()).sum::<usize>()
+    );
+
+    failures.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_bursts() {
+        let lines = [
+            "[2026-01-06 10:29:10.765] [info] [Global]  a",
+            "[2026-01-06 10:29:10.765] [info] [Global]  b",
+            "    at stack frame",
+            "[2026-01-06 10:29:10.765] [info] [Global]  c",
+            "[2026-01-06 10:29:10.766] [info] [Global]  d",
+            "[2026-01-06 10:29:10.766] [info] [Global]  e",
+            "[2026-01-06 10:29:11.000] [info] [Global]  f",
+            "[2026-01-06 10:29:11.000] [info] [Global]  g",
+            "[2026-01-06 10:29:11.000] [info] [Global]  h",
+        ];
+        let lines = || lines.iter().map(|l| Ok(l.to_string()));
+
+        let bursts = find_bursts(lines(), 3, TimestampFormat::Bracket).unwrap();
+        assert_eq!(bursts.len(), 2);
+        assert_eq!((bursts[0].first_line, bursts[0].last_line), (1, 4));
+        assert_eq!(bursts[0].lines, 3);
+        assert_eq!((bursts[1].first_line, bursts[1].last_line), (7, 9));
+        assert_eq!(
+            find_bursts(lines(), 2, TimestampFormat::Bracket)
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    path::Path,
+    process,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use anyhow::Result;
+
+use crate::temp::rollback;
+
+/// 未开始的文件最多列出多少个
+const MAX_LISTED: usize = 10;
+
+/// 收到第一次 Ctrl-C 后置位，不再开始新的文件
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// 正在进行的可平滑取消的批处理数量
+static GRACEFUL: AtomicUsize = AtomicUsize::new(0);
+
+/// 批处理期间持有：第一次 Ctrl-C 只停止开始新的文件，已开始的文件照常完成
+pub struct Graceful(());
+
+impl Drop for Graceful {
+    fn drop(&mut self) {
+        GRACEFUL.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn graceful() -> Graceful {
+    GRACEFUL.fetch_add(1, Ordering::SeqCst);
+    Graceful(())
+}
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// 批处理中 Ctrl-C / SIGTERM 时等待已开始的文件完成；
+/// 再次按下或不在批处理中时，清理未完成的输出文件后退出
+pub fn install_interrupt_handler() -> Result<()> {
+    ctrlc::set_handler(|| {
+        if GRACEFUL.load(Ordering::SeqCst) > 0 && !CANCELLED.swap(true, Ordering::SeqCst) {
+            eprintln!(
+                "⏹ stopping, waiting for in-flight files to finish, press Ctrl-C again to abort"
+            );
+            return;
+        }
+
+        for path in rollback() {
+            eprintln!("↩ rolled back partial output, path: {:?}", path.display());
+        }
+        eprintln!("❌ interrupted");
+        process::exit(130);
+    })?;
+
+    Ok(())
+}
+
+/// 取消后的部分结果汇总与 `command` 继续处理的提示
+pub fn print_cancelled(command: &str, total: usize, not_started: &[&Path]) {
+    eprintln!(
+        "⏹ cancelled, finished {} of {} files, not started: {}",
+        total - not_started.len(),
+        total,
+        not_started.len()
+    );
+    for path in not_started.iter().take(MAX_LISTED) {
+        eprintln!("  {}", path.display());
+    }
+    if not_started.len() > MAX_LISTED {
+        eprintln!("  ... and {} more", not_started.len() - MAX_LISTED);
+    }
+    match command {
+        "cl" => eprintln!(
+            "hint: results above cover only the finished files; run `lp cl` again to check all files, or `lp cl -p <file>` with the same options for each file above"
+        ),
+        "rl" => eprintln!(
+            "hint: outputs of finished files are complete; run `lp rl -p <file>` with the same options for each file above to process the rest"
+        ),
+        _ => eprintln!(
+            "hint: run `lp {command} -p <file>` with the same options for each file above to process the rest"
+        ),
+    }
+}
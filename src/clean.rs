@@ -0,0 +1,271 @@
+use std::{collections::BTreeMap, path::PathBuf, time::SystemTime};
+
+use anyhow::{Ok, Result, anyhow, bail};
+use clap::Parser;
+use globset::Glob;
+use walkdir::WalkDir;
+
+use crate::{
+    audit::remove_audited,
+    config::RetentionPolicy,
+    context::AppContext,
+    entries::is_internal,
+    exit::Failures,
+    time::parse_duration,
+    units::{format_size, parse_size},
+};
+
+#[derive(Parser)]
+pub struct CleanArgs {
+    /// 按配置中的保留策略 (retention) 清理根路径下的文件
+    #[arg(long, default_value_t = false)]
+    pub apply_policy: bool,
+
+    /// 只列出将要删除的文件，不实际删除
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+}
+
+struct Candidate {
+    rel: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+pub fn process_clean(ctx: &AppContext, args: CleanArgs) -> Result<()> {
+    if !args.apply_policy {
+        bail!(
+            "❌ nothing to clean, use --apply-policy to enforce the retention policies in config"
+        );
+    }
+    if !args.dry_run {
+        ctx.ensure_writable("clean")?;
+    }
+
+    let config = ctx.load_config()?;
+    if config.retention.is_empty() {
+        println!("no retention policy configured");
+        return Ok(());
+    }
+
+    let root = ctx.base_dir()?.to_path_buf();
+    let files = WalkDir::new(&root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let rel = e.path().strip_prefix(&root).ok()?;
+            // `lp undo` 需要暂存的原文件，锁文件由持有者删除
+            if is_internal(rel) {
+                return None;
+            }
+            let meta = e.metadata().ok()?;
+            Some(Candidate {
+                rel: rel.to_path_buf(),
+                size: meta.len(),
+                modified: meta.modified().ok()?,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let selected = select_files(&config.retention, &files, SystemTime::now())?;
+    let total_size = selected.values().map(|(size, _)| size).sum::<u64>();
+    for (rel, (size, reasons)) in &selected {
+        println!(
+            "🗑 {} ({}): {}",
+            rel.display(),
+            format_size(*size),
+            reasons.join("; ")
+        );
+    }
+    println!(
+        "policies: {}, files to remove: {}, size: {}",
+        config.retention.len(),
+        selected.len(),
+        format_size(total_size)
+    );
+
+    if args.dry_run {
+        println!("dry run, nothing removed");
+        return Ok(());
+    }
+
+    let failures = Failures::new(ctx);
+    for rel in selected.keys() {
+        if failures.should_stop() {
+            break;
+        }
+        let path = root.join(rel);
+        if let Err(e) = remove_audited(ctx, "clean", &path) {
+            eprintln!("❌ remove file failed, path {:?}, reason: {}", path, e);
+            failures.record(&path, &e);
+        }
+    }
+
+    failures.finish()
+}
+
+/// 按各策略选出要删除的文件及原因，`files` 中同一文件被多个策略选中时原因合并
+fn select_files<'a>(
+    policies: &[RetentionPolicy],
+    files: &'a [Candidate],
+    now: SystemTime,
+) -> Result<BTreeMap<&'a PathBuf, (u64, Vec<String>)>> {
+    let mut selected: BTreeMap<&PathBuf, (u64, Vec<String>)> = BTreeMap::new();
+    for policy in policies {
+        let glob = Glob::new(&policy.pattern)?.compile_matcher();
+        let max_age = policy
+            .max_age
+            .as_deref()
+            .map(parse_duration)
+            .transpose()
+            .map_err(|e| anyhow!("❌ retention policy {}: {e}", policy.pattern))?;
+        let max_total_size = policy
+            .max_total_size
+            .as_deref()
+            .map(parse_size)
+            .transpose()
+            .map_err(|e| anyhow!("❌ retention policy {}: {e}", policy.pattern))?;
+
+        let mut matched = files
+            .iter()
+            .filter(|f| glob.is_match(&f.rel))
+            .collect::<Vec<_>>();
+        matched.sort_by_key(|f| std::cmp::Reverse(f.modified));
+
+        let mut kept_size = 0;
+        for (i, file) in matched.into_iter().enumerate() {
+            let mut reasons = Vec::new();
+            if let Some(keep_last) = policy.keep_last
+                && i >= keep_last
+            {
+                reasons.push(format!("beyond keep-last {keep_last}"));
+            }
+            if let Some(max_age) = max_age
+                && now.duration_since(file.modified).unwrap_or_default() > max_age
+            {
+                reasons.push(format!("older than {}", policy.max_age.as_deref().unwrap()));
+            }
+            if let Some(max_total_size) = max_total_size
+                && reasons.is_empty()
+                && kept_size + file.size > max_total_size
+            {
+                reasons.push(format!(
+                    "total size over {}",
+                    policy.max_total_size.as_deref().unwrap()
+                ));
+            }
+
+            if reasons.is_empty() {
+                kept_size += file.size;
+            } else {
+                let entry = selected.entry(&file.rel).or_insert((file.size, Vec::new()));
+                entry
+                    .1
+                    .push(format!("[{}] {}", policy.pattern, reasons.join(", ")));
+            }
+        }
+    }
+
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn test_select_files() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(100 * 86_400);
+        let file = |rel: &str, size: u64, days_ago: u64| Candidate {
+            rel: PathBuf::from(rel),
+            size,
+            modified: now - Duration::from_secs(days_ago * 86_400),
+        };
+        let files = [
+            file("app/a.log", 400, 1),
+            file("app/b.log", 400, 2),
+            file("app/c.log", 400, 3),
+            file("app/d.log", 400, 40),
+            file("db/a.log", 100, 60),
+        ];
+        let policy =
+            |pattern: &str, max_age: Option<&str>, max_total_size: Option<&str>, keep_last| {
+                RetentionPolicy {
+                    pattern: pattern.to_string(),
+                    max_age: max_age.map(String::from),
+                    max_total_size: max_total_size.map(String::from),
+                    keep_last,
+                }
+            };
+
+        // 按最新在前计算：第 3 个起超出 keep-last，d 同时过期；总大小只计入保留的文件
+        let selected = select_files(
+            &[
+                policy("app/*.log", Some("30d"), None, Some(3)),
+                policy("app/*.log", None, Some("1K"), None),
+            ],
+            &files,
+            now,
+        )
+        .unwrap();
+        let reasons = |rel: &str| selected[&PathBuf::from(rel)].1.join("; ");
+        assert_eq!(
+            selected.keys().copied().collect::<Vec<_>>(),
+            [&PathBuf::from("app/c.log"), &PathBuf::from("app/d.log")]
+        );
+        assert_eq!(reasons("app/c.log"), "[app/*.log] total size over 1K");
+        assert_eq!(
+            reasons("app/d.log"),
+            "[app/*.log] beyond keep-last 3, older than 30d; [app/*.log] total size over 1K"
+        );
+
+        // 不匹配任何策略的文件不受影响
+        let selected =
+            select_files(&[policy("db/*", Some("90d"), None, None)], &files, now).unwrap();
+        assert!(selected.is_empty());
+
+        assert!(select_files(&[policy("app/*", Some("soon"), None, None)], &files, now).is_err());
+        assert!(select_files(&[policy("app/*", None, Some("1X"), None)], &files, now).is_err());
+    }
+
+    #[test]
+    fn test_clean_uses_context_base_dir() {
+        let dir = std::env::temp_dir().join(format!("lp_clean_test_{}", std::process::id()));
+        let configured = dir.join("configured");
+        let scratch = dir.join("scratch");
+        fs::create_dir_all(&configured).unwrap();
+        fs::create_dir_all(&scratch).unwrap();
+        fs::write(configured.join("a.log"), "a\n").unwrap();
+        fs::write(scratch.join("a.log"), "a\n").unwrap();
+        fs::create_dir_all(scratch.join(".lp_undo/1")).unwrap();
+        fs::write(scratch.join(".lp_undo/1/a.log"), "a\n").unwrap();
+        fs::write(scratch.join(".a.log.lp.lock"), "").unwrap();
+        let config_path = dir.join("config.json");
+        fs::write(
+            &config_path,
+            format!(
+                r#"{{ "base_dir": {:?}, "retention": [{{ "pattern": "*.log", "keep_last": 0 }}] }}"#,
+                configured
+            ),
+        )
+        .unwrap();
+
+        // 只清理上下文中的根路径，配置中的 base_dir 不受影响
+        let ctx = AppContext::new(&config_path).with_base_dir(&scratch);
+        let args = CleanArgs {
+            apply_policy: true,
+            dry_run: false,
+        };
+        process_clean(&ctx, args).unwrap();
+        assert!(!scratch.join("a.log").exists());
+        assert!(configured.join("a.log").exists());
+        // 暂存的原文件与锁文件不受保留策略影响
+        assert!(scratch.join(".lp_undo/1/a.log").exists());
+        assert!(scratch.join(".a.log.lp.lock").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use serde::Deserialize;
+
+use crate::{
+    stats::StatsReport,
+    subcommand::{CheckReport, CheckSummary},
+};
+
+#[derive(Parser)]
+pub struct CompareArgs {
+    /// 基准结果 (`cl --json` 或 `stats --json` 输出)
+    pub base: PathBuf,
+
+    /// 新的结果，与基准为同一命令的输出
+    pub target: PathBuf,
+}
+
+/// 可对比的结果：`cl --json` 为一个对象，`stats --json` 为逐文件的数组
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Report {
+    Check(CheckReport),
+    Stats(Vec<StatsReport>),
+}
+
+pub fn load_report(path: &Path) -> Result<CheckReport> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("❌ failed to read {}", path.display()))?;
+    let report = serde_json::from_str(&content)
+        .with_context(|| format!("❌ {} is not a `cl --json` report", path.display()))?;
+
+    Ok(report)
+}
+
+fn load_any_report(path: &Path) -> Result<Report> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("❌ failed to read {}", path.display()))?;
+    let report = serde_json::from_str(&content).with_context(|| {
+        format!(
+            "❌ {} is not a `cl --json` or `stats --json` report",
+            path.display()
+        )
+    })?;
+
+    Ok(report)
+}
+
+/// 以相对于 `root` 的路径作为文件的对比键，路径就是 `root` 时取文件名
+pub fn file_key(path: &Path, root: &Path) -> PathBuf {
+    match path.strip_prefix(root) {
+        Ok(rel) if !rel.as_os_str().is_empty() => rel.to_path_buf(),
+        _ => PathBuf::from(path.file_name().unwrap_or_default()),
+    }
+}
+
+fn keyed_files(report: &CheckReport) -> BTreeMap<PathBuf, &CheckSummary> {
+    report
+        .files
+        .iter()
+        .map(|summary| (file_key(&summary.path, &report.root), summary))
+        .collect()
+}
+
+/// `stats --json` 不记录根路径，以各文件的公共上级目录代替
+fn keyed_stats(reports: &[StatsReport]) -> BTreeMap<PathBuf, &StatsReport> {
+    let root = reports.iter().skip(1).fold(
+        reports.first().map(|report| report.path.clone()),
+        |root, report| {
+            root.map(|root| {
+                root.ancestors()
+                    .find(|dir| report.path.starts_with(dir))
+                    .unwrap_or(Path::new(""))
+                    .to_path_buf()
+            })
+        },
+    );
+    let root = root.unwrap_or_default();
+    reports
+        .iter()
+        .map(|report| (file_key(&report.path, &root), report))
+        .collect()
+}
+
+/// 一个文件在 `cl` 结果中变差的地方
+fn check_changes(old: &CheckSummary, new: &CheckSummary) -> Vec<String> {
+    let mut changes = Vec::new();
+    let new_codes = new
+        .error_codes
+        .difference(&old.error_codes)
+        .cloned()
+        .collect::<Vec<_>>();
+    if !new_codes.is_empty() {
+        changes.push(format!("new error codes [{}]", new_codes.join(", ")));
+    }
+    if new.errors > old.errors {
+        changes.push(format!("errors {} -> {}", old.errors, new.errors));
+    }
+    if let (Some(cpu), Some(old_cpu)) = (new.cpu_peak, old.cpu_peak)
+        && cpu > old_cpu
+    {
+        changes.push(format!("cpu peak {old_cpu:.2}% -> {cpu:.2}%"));
+    }
+    if new.matches > old.matches {
+        changes.push(format!("keyword lines {} -> {}", old.matches, new.matches));
+    }
+
+    changes
+}
+
+/// 一个文件在 `stats` 结果中变差的地方：新出现的级别、error 行变多、指标峰值变高
+fn stats_changes(old: &StatsReport, new: &StatsReport) -> Vec<String> {
+    let mut changes = Vec::new();
+    let new_levels = new
+        .levels
+        .keys()
+        .filter(|level| !old.levels.contains_key(*level))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !new_levels.is_empty() {
+        changes.push(format!("new levels [{}]", new_levels.join(", ")));
+    }
+    let errors = |report: &StatsReport| report.levels.get("error").copied().unwrap_or(0);
+    if errors(new) > errors(old) {
+        changes.push(format!("errors {} -> {}", errors(old), errors(new)));
+    }
+    for metric in &new.metrics {
+        if let Some(old_metric) = old.metrics.iter().find(|m| m.name == metric.name)
+            && metric.max > old_metric.max
+        {
+            changes.push(format!(
+                "{} max {:.2}{} -> {:.2}{}",
+                metric.name, old_metric.max, old_metric.unit, metric.max, metric.unit
+            ));
+        }
+    }
+
+    changes
+}
+
+/// 逐个对比两边都有的文件，输出变差的文件及只在一边出现的文件
+fn compare_files<T>(
+    args: &CompareArgs,
+    base_files: BTreeMap<PathBuf, &T>,
+    target_files: BTreeMap<PathBuf, &T>,
+    changes: impl Fn(&T, &T) -> Vec<String>,
+) {
+    let mut regressions = 0;
+    for (key, new) in &target_files {
+        let Some(old) = base_files.get(key) else {
+            println!("🆕 {}: only in {}", key.display(), args.target.display());
+            continue;
+        };
+
+        let changes = changes(old, new);
+        if !changes.is_empty() {
+            regressions += 1;
+            println!("⚠️ {}: {}", key.display(), changes.join(", "));
+        }
+    }
+
+    for key in base_files.keys().filter(|k| !target_files.contains_key(*k)) {
+        println!("➖ {}: only in {}", key.display(), args.base.display());
+    }
+
+    println!(
+        "compared files: {}, regressed: {}",
+        target_files.len(),
+        regressions
+    );
+}
+
+pub fn process_compare(args: CompareArgs) -> Result<()> {
+    match (load_any_report(&args.base)?, load_any_report(&args.target)?) {
+        (Report::Check(base), Report::Check(target)) => {
+            if base.filters != target.filters {
+                println!("⚠️ filters differ, keyword line counts may not be comparable");
+            }
+            compare_files(
+                &args,
+                keyed_files(&base),
+                keyed_files(&target),
+                check_changes,
+            );
+        }
+        (Report::Stats(base), Report::Stats(target)) => {
+            compare_files(
+                &args,
+                keyed_stats(&base),
+                keyed_stats(&target),
+                stats_changes,
+            );
+        }
+        _ => bail!("❌ cannot compare a `cl --json` report with a `stats --json` report"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::MetricReport;
+
+    fn stats(path: &str, levels: &[(&str, usize)], cpu_max: f64) -> StatsReport {
+        StatsReport {
+            path: PathBuf::from(path),
+            lines: levels.iter().map(|(_, count)| count).sum(),
+            unparsed: 0,
+            levels: levels
+                .iter()
+                .map(|(level, count)| (level.to_string(), *count))
+                .collect(),
+            modules: BTreeMap::new(),
+            first: None,
+            last: None,
+            span_ms: None,
+            metrics: vec![MetricReport {
+                name: "cpu".to_string(),
+                unit: "%".to_string(),
+                samples: 1,
+                min: cpu_max,
+                avg: cpu_max,
+                max: cpu_max,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_stats_changes() {
+        let old = stats("/a/app.log", &[("info", 2), ("error", 1)], 10.0);
+        let new = stats(
+            "/b/app.log",
+            &[("info", 1), ("error", 2), ("fatal", 1)],
+            20.0,
+        );
+        assert_eq!(
+            stats_changes(&old, &new),
+            [
+                "new levels [fatal]",
+                "errors 1 -> 2",
+                "cpu max 10.00% -> 20.00%"
+            ]
+        );
+        assert!(stats_changes(&new, &old).is_empty());
+
+        // 不同根路径下的同名文件按相对路径对应
+        let base = [
+            stats("/a/x/app.log", &[], 0.0),
+            stats("/a/y/app.log", &[], 0.0),
+        ];
+        assert_eq!(
+            keyed_stats(&base).into_keys().collect::<Vec<_>>(),
+            [PathBuf::from("x/app.log"), PathBuf::from("y/app.log")]
+        );
+        assert_eq!(
+            keyed_stats(&[stats("/b/app.log", &[], 0.0)])
+                .into_keys()
+                .collect::<Vec<_>>(),
+            [PathBuf::from("app.log")]
+        );
+    }
+}
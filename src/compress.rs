@@ -0,0 +1,116 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::ValueEnum;
+use flate2::{Compression, read::MultiGzDecoder, write::GzEncoder};
+
+/// 输出文件的压缩方式
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputCompression {
+    /// 与输入文件一致
+    Auto,
+    Gzip,
+    None,
+}
+
+impl OutputCompression {
+    /// 处理 `input` 时输出是否需要 gzip 压缩
+    pub fn gzip_for(self, input: &Path) -> bool {
+        match self {
+            OutputCompression::Auto => is_gzip(input),
+            OutputCompression::Gzip => true,
+            OutputCompression::None => false,
+        }
+    }
+}
+
+/// 按扩展名判断是否为 gzip 文件，如 `app.log.gz`
+pub fn is_gzip(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// 去掉 `.gz` 后缀得到解压后的文件名，不是 gzip 文件时原样返回
+pub fn plain_path(path: &Path) -> PathBuf {
+    if is_gzip(path) {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// 打开日志文件，`.gz` 文件透明解压 (支持多段拼接的 gzip)
+pub fn open_log(path: &Path) -> io::Result<Box<dyn BufRead + Send>> {
+    let file = File::open(path)?;
+    if is_gzip(path) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// 按需压缩的输出文件，写完后需调用 [`LogWriter::finish`] 写入 gzip 尾部
+pub enum LogWriter {
+    Plain(File),
+    Gzip(GzEncoder<File>),
+}
+
+impl LogWriter {
+    pub fn create(path: &Path, gzip: bool) -> io::Result<Self> {
+        let file = File::create(path)?;
+        if gzip {
+            Ok(LogWriter::Gzip(GzEncoder::new(
+                file,
+                Compression::default(),
+            )))
+        } else {
+            Ok(LogWriter::Plain(file))
+        }
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            LogWriter::Plain(mut file) => file.flush(),
+            LogWriter::Gzip(encoder) => encoder.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            LogWriter::Plain(file) => file.write(buf),
+            LogWriter::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            LogWriter::Plain(file) => file.flush(),
+            LogWriter::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gzip_paths() {
+        let gz = Path::new("/var/log/app.log.gz");
+        assert!(is_gzip(gz));
+        assert!(is_gzip(Path::new("app.log.GZ")));
+        assert!(!is_gzip(Path::new("app.log")));
+        assert_eq!(plain_path(gz), PathBuf::from("/var/log/app.log"));
+        assert_eq!(plain_path(Path::new("app.log")), PathBuf::from("app.log"));
+
+        assert!(OutputCompression::Auto.gzip_for(gz));
+        assert!(!OutputCompression::Auto.gzip_for(Path::new("app.log")));
+        assert!(!OutputCompression::None.gzip_for(gz));
+        assert!(OutputCompression::Gzip.gzip_for(Path::new("app.log")));
+    }
+}
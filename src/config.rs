@@ -0,0 +1,372 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Ok, Result, bail};
+use clap::{Parser, Subcommand, ValueEnum, builder::PossibleValuesParser};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::AppContext,
+    extractor::MetricExtractor,
+    subcommand::{BaseDirArgs, get_base_dir, set_base_dir},
+    table::print_table,
+    temp::InFlight,
+    timestamp::TimestampRule,
+};
+
+/// 配置中没有为命令指定默认关键字时使用的内置关键字
+pub const DEFAULT_FILTERS: [&str; 3] = ["tid:", "pid:", "cpu usage"];
+
+/// 使用默认关键字的命令：查找类命令 (cl、follow、prom 等) 使用 cl 的，rl 使用 rl 的
+pub const FILTER_COMMANDS: [&str; 2] = ["cl", "rl"];
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub base_dir: PathBuf,
+
+    /// 命名的根路径，用于同时处理多个产品的日志
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, PathBuf>,
+
+    /// `lp profile use` 选择的根路径名称，为空时使用 base_dir
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+
+    /// 处理结果 (如 rl 的 `_filtered` 文件) 的输出目录，为空时输出到源文件旁；相对路径基于根路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<PathBuf>,
+
+    /// 并行处理的线程数，为空时为 CPU 核数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+
+    /// 只读模式，同 `--read-only`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retention: Vec<RetentionPolicy>,
+
+    /// 各命令未指定 `-f` 时的默认关键字，键为 `cl` 或 `rl`，如 cl 查错误关键字而 rl 去除噪声
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub default_filters: BTreeMap<String, Vec<String>>,
+
+    /// 命名的关键字集合，供 `--preset` 使用，如 noise、network、gltf
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub presets: BTreeMap<String, Vec<String>>,
+
+    /// 自定义数值指标，stats、anomalies、metrics 可按名称使用
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metrics: Vec<MetricExtractor>,
+
+    /// 按路径指定的时间戳格式，用于根路径下混有 nginx 等不同格式的日志
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timestamps: Vec<TimestampRule>,
+
+    /// 已知问题知识库，键为关键字，值为说明，如 `ERRCODE_MSOPTIMEOUT` → `known issue JIRA-1234, fixed in 2.3`；
+    /// `cl --annotate` 在含有关键字的命中行下输出说明
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+/// 清理策略，`pattern` 为相对根路径的 glob，各项限制满足其一即删除
+#[derive(Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub pattern: String,
+
+    /// 最长保留时间，如 `30d`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<String>,
+
+    /// 匹配文件的总大小上限，如 `10G`，超出时先删除最旧的文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_size: Option<String>,
+
+    /// 只保留最新的若干个文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_last: Option<usize>,
+}
+
+/// 以共享锁读取 `path` 处的配置
+pub fn read_config(path: &Path) -> Result<Config> {
+    let _lock = lock_config(path, false)?;
+    parse_config(path)
+}
+
+/// 以独占锁读取、修改并原子替换 `path` 处的配置文件，配置不存在时从默认值开始
+pub fn update_config<F: FnOnce(&mut Config)>(path: &Path, f: F) -> Result<()> {
+    let _lock = lock_config(path, true)?;
+
+    let mut config = if path.exists() {
+        parse_config(path)?
+    } else {
+        Config::default()
+    };
+    f(&mut config);
+
+    let content = serde_json::to_string_pretty(&config)?;
+
+    let tmp = InFlight::register(path.with_extension("json.tmp"));
+    fs::write(tmp.path(), content)?;
+    fs::rename(tmp.path(), path)?;
+    tmp.commit();
+
+    Ok(())
+}
+
+fn parse_config(path: &Path) -> Result<Config> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("❌ failed to read config {}", path.display()))?;
+    let config = serde_json::from_str(&content)?;
+
+    Ok(config)
+}
+
+/// 配置文件 (或配置目录下其他 JSON 文件) 旁的 `.lock` 文件上的建议锁，随返回的 `File` 释放
+pub fn lock_config(path: &Path, exclusive: bool) -> Result<File> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_extension("json.lock"))?;
+    if exclusive {
+        lock.lock()?;
+    } else {
+        lock.lock_shared()?;
+    }
+
+    Ok(lock)
+}
+
+#[derive(Parser)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// 查看各命令实际生效的默认关键字及其来源
+    ShowDefaults,
+
+    /// 查看配置项
+    Get(KeyArgs),
+
+    /// 修改配置项
+    Set(SetArgs),
+
+    /// 清除配置项，恢复默认行为
+    Unset(KeyArgs),
+
+    /// 列出所有配置项
+    List,
+}
+
+/// 可通过 `lp config` 查看和修改的配置项
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ConfigKey {
+    /// 要操作文件的根路径，同 sbd / gbd
+    BaseDir,
+    /// 未指定 `-f` 时的默认关键字
+    DefaultFilters,
+    /// 处理结果的输出目录
+    OutputDir,
+    /// 并行处理的线程数
+    Threads,
+    /// 只读模式，true 时拒绝执行修改或删除日志的命令
+    ReadOnly,
+}
+
+#[derive(Parser)]
+pub struct KeyArgs {
+    pub key: ConfigKey,
+
+    /// 只操作该命令的默认关键字，仅用于 default-filters，默认为 cl 和 rl
+    #[arg(long, value_parser = PossibleValuesParser::new(FILTER_COMMANDS))]
+    pub command: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct SetArgs {
+    pub key: ConfigKey,
+
+    /// 配置值，default-filters 可指定多个关键字
+    #[arg(required = true)]
+    pub values: Vec<String>,
+
+    /// 只修改该命令的默认关键字，仅用于 default-filters，默认同时修改 cl 和 rl
+    #[arg(long, value_parser = PossibleValuesParser::new(FILTER_COMMANDS))]
+    pub command: Option<String>,
+}
+
+impl ConfigKey {
+    fn name(self) -> String {
+        self.to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// `--command` 指定的命令，未指定时为所有使用默认关键字的命令
+fn filter_commands(key: ConfigKey, command: Option<String>) -> Result<Vec<String>> {
+    match (key, command) {
+        (ConfigKey::DefaultFilters, Some(command)) => Ok(vec![command]),
+        (ConfigKey::DefaultFilters, None) => Ok(FILTER_COMMANDS.map(String::from).to_vec()),
+        (_, Some(_)) => bail!("❌ --command only applies to default-filters"),
+        (_, None) => Ok(Vec::new()),
+    }
+}
+
+fn single_value(key: ConfigKey, values: &[String]) -> Result<String> {
+    match values {
+        [value] => Ok(value.clone()),
+        _ => bail!("❌ {} takes a single value", key.name()),
+    }
+}
+
+/// 配置项当前的值，未设置时为 `(not set)`；default-filters 显示实际生效的关键字
+fn config_value(config: &Config, key: ConfigKey, commands: &[String]) -> String {
+    let not_set = || "(not set)".to_string();
+    match key {
+        ConfigKey::BaseDir if config.base_dir.as_os_str().is_empty() => not_set(),
+        ConfigKey::BaseDir => config.base_dir.display().to_string(),
+        ConfigKey::DefaultFilters => commands
+            .iter()
+            .map(|command| {
+                let filters = match config.default_filters.get(command) {
+                    Some(filters) => filters.join(", "),
+                    None => format!("{} (built-in)", DEFAULT_FILTERS.join(", ")),
+                };
+                format!("{command}: {filters}")
+            })
+            .collect::<Vec<_>>()
+            .join("; "),
+        ConfigKey::OutputDir => config
+            .output_dir
+            .as_ref()
+            .map_or_else(not_set, |dir| dir.display().to_string()),
+        ConfigKey::Threads => config.threads.map_or_else(not_set, |n| n.to_string()),
+        ConfigKey::ReadOnly => config.read_only.to_string(),
+    }
+}
+
+fn set_config(ctx: &AppContext, args: SetArgs) -> Result<()> {
+    let commands = filter_commands(args.key, args.command)?;
+    match args.key {
+        ConfigKey::BaseDir => {
+            let path = PathBuf::from(single_value(args.key, &args.values)?);
+            return set_base_dir(ctx, BaseDirArgs { path });
+        }
+        ConfigKey::DefaultFilters => ctx.update_config(|config| {
+            for command in &commands {
+                config
+                    .default_filters
+                    .insert(command.clone(), args.values.clone());
+            }
+        })?,
+        ConfigKey::OutputDir => {
+            let dir = PathBuf::from(single_value(args.key, &args.values)?);
+            ctx.update_config(|config| config.output_dir = Some(dir))?;
+        }
+        ConfigKey::Threads => {
+            let threads = single_value(args.key, &args.values)?
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n > 0)
+                .context("❌ threads should be a positive integer")?;
+            ctx.update_config(|config| config.threads = Some(threads))?;
+        }
+        ConfigKey::ReadOnly => {
+            let read_only = single_value(args.key, &args.values)?
+                .parse::<bool>()
+                .ok()
+                .context("❌ read-only should be true or false")?;
+            ctx.update_config(|config| config.read_only = read_only)?;
+        }
+    }
+
+    let config = ctx.load_config()?;
+    println!(
+        "{} set to: {}",
+        args.key.name(),
+        config_value(&config, args.key, &commands)
+    );
+
+    Ok(())
+}
+
+fn unset_config(ctx: &AppContext, args: KeyArgs) -> Result<()> {
+    let commands = filter_commands(args.key, args.command)?;
+    ctx.update_config(|config| match args.key {
+        ConfigKey::BaseDir => config.base_dir = PathBuf::new(),
+        ConfigKey::DefaultFilters => {
+            for command in &commands {
+                config.default_filters.remove(command);
+            }
+        }
+        ConfigKey::OutputDir => config.output_dir = None,
+        ConfigKey::Threads => config.threads = None,
+        ConfigKey::ReadOnly => config.read_only = false,
+    })?;
+    println!("unset {}", args.key.name());
+
+    Ok(())
+}
+
+pub fn process_config(ctx: &AppContext, args: ConfigArgs) -> Result<()> {
+    match args.command {
+        ConfigCommand::ShowDefaults => {
+            let configured = ctx.configured_default_filters()?;
+            let mut commands = FILTER_COMMANDS.map(String::from).to_vec();
+            commands.extend(
+                configured
+                    .keys()
+                    .filter(|k| !FILTER_COMMANDS.contains(&k.as_str()))
+                    .cloned(),
+            );
+
+            let rows = commands
+                .into_iter()
+                .map(|command| {
+                    let (source, filters) = match configured.get(&command) {
+                        Some(filters) => ("config", filters.clone()),
+                        None => ("built-in", DEFAULT_FILTERS.map(String::from).to_vec()),
+                    };
+                    vec![command, source.to_string(), filters.join(", ")]
+                })
+                .collect::<Vec<_>>();
+            print_table(&["command", "source", "filters"], &rows);
+        }
+        // 与 gbd 一致，显示实际生效的根路径 (LP_BASE_DIR、--base-dir、profile 优先于配置)
+        ConfigCommand::Get(args) if matches!(args.key, ConfigKey::BaseDir) => {
+            filter_commands(args.key, args.command)?;
+            println!("{}", get_base_dir(ctx)?.path.display());
+        }
+        ConfigCommand::Get(args) => {
+            let commands = filter_commands(args.key, args.command)?;
+            let config = ctx.config_or_default()?;
+            println!("{}", config_value(&config, args.key, &commands));
+        }
+        ConfigCommand::Set(args) => set_config(ctx, args)?,
+        ConfigCommand::Unset(args) => unset_config(ctx, args)?,
+        ConfigCommand::List => {
+            let config = ctx.config_or_default()?;
+            let commands = FILTER_COMMANDS.map(String::from);
+            let rows = ConfigKey::value_variants()
+                .iter()
+                .map(|&key| vec![key.name(), config_value(&config, key, &commands)])
+                .collect::<Vec<_>>();
+            print_table(&["key", "value"], &rows);
+        }
+    }
+
+    Ok(())
+}
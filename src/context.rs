@@ -0,0 +1,361 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
+
+use anyhow::{Ok, Result, bail};
+
+use crate::{
+    annotate::Annotations,
+    config::{Config, DEFAULT_FILTERS, read_config, update_config},
+    extractor::MetricExtractor,
+    output::OutputFormat,
+    progress::{Progress, ProgressMode},
+};
+
+/// 默认的配置文件位置
+const CONFIG_PATH: &str = "config/config.json";
+
+/// 指定配置文件位置的环境变量
+const CONFIG_ENV: &str = "LP_CONFIG";
+
+/// 指定根路径的环境变量，优先于配置文件，便于 CI 与脚本不修改共享的配置
+const BASE_DIR_ENV: &str = "LP_BASE_DIR";
+
+/// 非空的环境变量值
+fn env_path(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// 环境变量 `LP_BASE_DIR` 指定的根路径
+pub fn env_base_dir() -> Option<PathBuf> {
+    env_path(BASE_DIR_ENV)
+}
+
+/// 一次运行的上下文：配置文件位置与根路径，在 main 中构造后传给各子命令
+#[derive(Clone)]
+pub struct AppContext {
+    config_path: PathBuf,
+    base_dir: OnceLock<PathBuf>,
+    profile: Option<String>,
+    read_only: bool,
+    fail_fast: bool,
+    output_format: OutputFormat,
+    progress: Arc<Progress>,
+}
+
+impl Default for AppContext {
+    /// 配置文件位置可由环境变量 `LP_CONFIG` 指定
+    fn default() -> Self {
+        AppContext::new(env_path(CONFIG_ENV).unwrap_or_else(|| PathBuf::from(CONFIG_PATH)))
+    }
+}
+
+impl AppContext {
+    pub fn new<P: Into<PathBuf>>(config_path: P) -> Self {
+        AppContext {
+            config_path: config_path.into(),
+            base_dir: OnceLock::new(),
+            profile: None,
+            read_only: false,
+            fail_fast: false,
+            output_format: OutputFormat::Text,
+            progress: Arc::new(Progress::new(ProgressMode::Off)),
+        }
+    }
+
+    /// 使用指定的根路径，不再从配置中读取
+    pub fn with_base_dir<P: Into<PathBuf>>(self, base_dir: P) -> Self {
+        AppContext {
+            base_dir: OnceLock::from(base_dir.into()),
+            ..self
+        }
+    }
+
+    /// 改用 `config_path` 处的配置文件，其所在目录同时存放历史记录等文件
+    pub fn with_config_path<P: Into<PathBuf>>(self, config_path: P) -> Self {
+        AppContext {
+            config_path: config_path.into(),
+            ..self
+        }
+    }
+
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// 使用配置中名为 `profile` 的根路径，覆盖 `lp profile use` 的选择与之前指定的根路径
+    pub fn with_profile(self, profile: String) -> Self {
+        AppContext {
+            base_dir: OnceLock::new(),
+            profile: Some(profile),
+            ..self
+        }
+    }
+
+    /// 只读模式，拒绝执行修改或删除日志的命令
+    pub fn with_read_only(self) -> Self {
+        AppContext {
+            read_only: true,
+            ..self
+        }
+    }
+
+    /// 文件夹模式下第一个文件失败后不再开始新的文件
+    pub fn with_fail_fast(self) -> Self {
+        AppContext {
+            fail_fast: true,
+            ..self
+        }
+    }
+
+    pub fn fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    /// `lp --format` 指定的输出格式
+    pub fn with_output_format(self, output_format: OutputFormat) -> Self {
+        AppContext {
+            output_format,
+            ..self
+        }
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        self.output_format
+    }
+
+    /// 处理多个文件时在 stderr 显示进度条
+    pub fn with_progress_bar(self) -> Self {
+        AppContext {
+            progress: Arc::new(Progress::new(ProgressMode::Bar)),
+            ..self
+        }
+    }
+
+    /// 不显示进度
+    pub fn without_progress(self) -> Self {
+        AppContext {
+            progress: Arc::new(Progress::new(ProgressMode::Off)),
+            ..self
+        }
+    }
+
+    /// 在 stderr 输出每行一个 JSON 的进度事件
+    pub fn with_progress_json(self) -> Self {
+        AppContext {
+            progress: Arc::new(Progress::new(ProgressMode::Json)),
+            ..self
+        }
+    }
+
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    /// 以共享锁读取配置
+    pub fn load_config(&self) -> Result<Config> {
+        read_config(&self.config_path)
+    }
+
+    /// 以独占锁读取、修改并原子替换配置文件，配置不存在时从默认值开始
+    pub fn update_config<F: FnOnce(&mut Config)>(&self, f: F) -> Result<()> {
+        update_config(&self.config_path, f)
+    }
+
+    /// 配置目录下的文件路径，目录不存在时自动创建
+    pub fn config_file(&self, name: &str) -> Result<PathBuf> {
+        let dir = self.config_path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)?;
+
+        Ok(dir.join(name))
+    }
+
+    /// 读取配置，配置文件不存在时为默认值
+    pub fn config_or_default(&self) -> Result<Config> {
+        if !self.config_path.exists() {
+            return Ok(Config::default());
+        }
+
+        self.load_config()
+    }
+
+    /// 配置中的输出目录，相对路径基于根路径
+    pub fn output_dir(&self) -> Result<Option<PathBuf>> {
+        self.config_or_default()?
+            .output_dir
+            .map(|dir| self.resolve_path(dir))
+            .transpose()
+    }
+
+    /// `--read-only` 或配置中开启了只读模式时拒绝执行 `action`
+    pub fn ensure_writable(&self, action: &str) -> Result<()> {
+        if self.read_only || self.config_or_default()?.read_only {
+            bail!("❌ {action} modifies files and is not allowed in read-only mode");
+        }
+
+        Ok(())
+    }
+
+    /// 配置中的并行线程数
+    pub fn threads(&self) -> Result<Option<usize>> {
+        Ok(self.config_or_default()?.threads)
+    }
+
+    /// 配置中按命令指定的默认关键字，配置文件不存在时为空
+    pub fn configured_default_filters(&self) -> Result<BTreeMap<String, Vec<String>>> {
+        Ok(self.config_or_default()?.default_filters)
+    }
+
+    /// 配置中的命名关键字集合，配置文件不存在时为空
+    pub fn presets(&self) -> Result<BTreeMap<String, Vec<String>>> {
+        Ok(self.config_or_default()?.presets)
+    }
+
+    /// 当前使用的 profile：`--profile` 指定的，否则为 `lp profile use` 选择的
+    pub fn profile_name(&self) -> Result<Option<String>> {
+        match &self.profile {
+            Some(profile) => Ok(Some(profile.clone())),
+            None => Ok(self.config_or_default()?.profile),
+        }
+    }
+
+    /// 配置中的自定义指标，配置文件不存在时为空
+    pub fn metric_extractors(&self) -> Result<Vec<MetricExtractor>> {
+        Ok(self.config_or_default()?.metrics)
+    }
+
+    /// 配置中的已知问题说明，配置文件不存在时为空
+    pub fn annotations(&self) -> Result<Annotations> {
+        Ok(Annotations::new(self.config_or_default()?.annotations))
+    }
+
+    /// 名为 `name` 的关键字集合
+    pub fn preset(&self, name: &str) -> Result<Vec<String>> {
+        let mut presets = self.presets()?;
+        match presets.remove(name) {
+            Some(filters) => Ok(filters),
+            None if presets.is_empty() => bail!("❌ preset `{name}` not found, no presets defined"),
+            None => bail!(
+                "❌ preset `{name}` not found, available: {}",
+                presets.into_keys().collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+
+    /// `command` 未指定 `-f` 时的默认关键字，配置中没有指定时为内置的 DEFAULT_FILTERS
+    pub fn default_filters(&self, command: &str) -> Result<Vec<String>> {
+        Ok(self
+            .configured_default_filters()?
+            .remove(command)
+            .unwrap_or_else(|| DEFAULT_FILTERS.map(String::from).to_vec()))
+    }
+
+    /// 根路径，未指定时首次使用从配置中读取：选中了 profile 时为其路径，否则为 base_dir
+    pub fn base_dir(&self) -> Result<&Path> {
+        if let Some(base_dir) = self.base_dir.get() {
+            return Ok(base_dir);
+        }
+
+        let mut config = self.load_config()?;
+        let base_dir = match self.profile.clone().or(config.profile) {
+            Some(name) => match config.profiles.remove(&name) {
+                Some(path) => path,
+                None if config.profiles.is_empty() => {
+                    bail!("❌ profile `{name}` not found, no profiles defined")
+                }
+                None => bail!(
+                    "❌ profile `{name}` not found, available: {}",
+                    config.profiles.into_keys().collect::<Vec<_>>().join(", ")
+                ),
+            },
+            None if config.base_dir.as_os_str().is_empty() => {
+                bail!(
+                    "❌ base dir is not set, run `lp config set base-dir <path>` or set LP_BASE_DIR"
+                )
+            }
+            None => config.base_dir,
+        };
+        Ok(self.base_dir.get_or_init(|| base_dir))
+    }
+
+    /// 相对路径基于根路径解析
+    pub fn resolve_path(&self, path: PathBuf) -> Result<PathBuf> {
+        if path.is_absolute() {
+            return Ok(path);
+        }
+
+        Ok(self.base_dir()?.join(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_path() {
+        let ctx = AppContext::new("/nonexistent/config.json").with_base_dir("/var/log/app");
+        assert_eq!(
+            ctx.resolve_path(PathBuf::from("a/b.log")).unwrap(),
+            PathBuf::from("/var/log/app/a/b.log")
+        );
+        assert_eq!(
+            ctx.resolve_path(PathBuf::from("/tmp/c.log")).unwrap(),
+            PathBuf::from("/tmp/c.log")
+        );
+
+        let ctx = AppContext::new("/nonexistent/config.json");
+        assert!(ctx.resolve_path(PathBuf::from("a.log")).is_err());
+        assert!(ctx.resolve_path(PathBuf::from("/tmp/c.log")).is_ok());
+    }
+
+    #[test]
+    fn test_default_filters() {
+        let ctx = AppContext::new("/nonexistent/config.json");
+        assert_eq!(ctx.default_filters("cl").unwrap(), DEFAULT_FILTERS);
+        assert_eq!(ctx.default_filters("rl").unwrap(), DEFAULT_FILTERS);
+        assert!(ctx.presets().unwrap().is_empty());
+        assert!(ctx.preset("noise").is_err());
+    }
+
+    #[test]
+    fn test_profile_base_dir() {
+        let dir = std::env::temp_dir().join(format!("lp_profile_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.json");
+        fs::write(
+            &config_path,
+            r#"{ "base_dir": "/var/log", "profiles": { "a": "/data/a", "b": "/data/b" }, "profile": "a" }"#,
+        )
+        .unwrap();
+
+        let ctx = AppContext::new(&config_path);
+        assert_eq!(ctx.base_dir().unwrap(), Path::new("/data/a"));
+        let ctx = AppContext::new(&config_path).with_profile("b".to_string());
+        assert_eq!(ctx.base_dir().unwrap(), Path::new("/data/b"));
+        let ctx = AppContext::new(&config_path).with_profile("c".to_string());
+        assert!(ctx.base_dir().is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_only() {
+        let ctx = AppContext::new("/nonexistent/config.json");
+        assert!(ctx.ensure_writable("rf").is_ok());
+        assert!(ctx.with_read_only().ensure_writable("rf").is_err());
+
+        let dir = std::env::temp_dir().join(format!("lp_read_only_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.json");
+        fs::write(&config_path, r#"{ "read_only": true }"#).unwrap();
+        assert!(AppContext::new(&config_path).ensure_writable("rf").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
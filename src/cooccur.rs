@@ -0,0 +1,116 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+
+use crate::{context::AppContext, record::line_timestamp, time::parse_duration};
+
+#[derive(Parser)]
+pub struct CooccurArgs {
+    /// 文件路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 两个关键字，前者为原因，后者为结果
+    #[arg(short, long, num_args = 1, required = true)]
+    pub filters: Vec<String>,
+
+    /// 时间窗口，如 500ms、5s、1m
+    #[arg(short, long, default_value = "5s", value_parser = parse_duration)]
+    pub window: Duration,
+}
+
+pub fn process_cooccur(ctx: &AppContext, args: CooccurArgs) -> Result<()> {
+    let [first, second] = args.filters.as_slice() else {
+        bail!("❌ cooccur needs exactly two keywords, e.g. -f 'cpu usage' -f timeout");
+    };
+
+    let path = ctx.resolve_path(args.path)?;
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    let mut first_times = Vec::new();
+    let mut second_times = Vec::new();
+    // 记下行号，同时包含两个关键字的行不算作与自身同时出现
+    for (n, line) in content.lines().enumerate() {
+        let Some(time) = line_timestamp(line) else {
+            continue;
+        };
+        if line.contains(first.as_str()) {
+            first_times.push((time, n));
+        }
+        if line.contains(second.as_str()) {
+            second_times.push((time, n));
+        }
+    }
+    first_times.sort_unstable();
+    second_times.sort_unstable();
+
+    let window = args.window.as_millis() as i64;
+    let followed = count_followed(&first_times, &second_times, window);
+    let preceded = count_followed(&second_times, &first_times, window);
+
+    println!("file: {}, window: {:?}", path.display(), args.window);
+    println!("{first:?}: {} lines", first_times.len());
+    println!("{second:?}: {} lines", second_times.len());
+    println!(
+        "{first:?} followed by {second:?}: {followed}/{} ({:.1}%)",
+        first_times.len(),
+        percent(followed, first_times.len())
+    );
+    println!(
+        "{second:?} followed by {first:?}: {preceded}/{} ({:.1}%)",
+        second_times.len(),
+        percent(preceded, second_times.len())
+    );
+
+    Ok(())
+}
+
+/// `causes` 中有多少次在 `window` 毫秒内出现了 `effects`，两者均为 (时间戳, 行号) 并已排序；
+/// 同一行不算作出现
+fn count_followed(causes: &[(i64, usize)], effects: &[(i64, usize)], window: i64) -> usize {
+    causes
+        .iter()
+        .filter(|&&(t, line)| {
+            let i = effects.partition_point(|&(e, _)| e < t);
+            effects[i..]
+                .iter()
+                .take_while(|&&(e, _)| e - t <= window)
+                .any(|&(_, n)| n != line)
+        })
+        .count()
+}
+
+fn percent(n: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        n as f64 * 100.0 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_followed() {
+        let cpu = [(1_000, 0), (10_000, 2), (20_000, 3)];
+        let timeout = [(3_000, 1), (26_000, 4), (40_000, 5)];
+
+        assert_eq!(count_followed(&cpu, &timeout, 5_000), 1);
+        assert_eq!(count_followed(&cpu, &timeout, 6_000), 2);
+        assert_eq!(count_followed(&timeout, &cpu, 10_000), 1);
+        assert_eq!(count_followed(&cpu, &[], 5_000), 0);
+
+        // 同时包含两个关键字的行只与其他行配对
+        let cpu = [(1_000, 0), (5_000, 1)];
+        let timeout = [(1_000, 0), (1_000, 2)];
+        assert_eq!(count_followed(&cpu, &timeout, 1_000), 1);
+        assert_eq!(count_followed(&timeout, &cpu, 10_000), 2);
+        assert_eq!(count_followed(&cpu[..1], &timeout[..1], 5_000), 0);
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+
+use crate::{
+    compress::{LogWriter, is_gzip, open_log, plain_path},
+    context::AppContext,
+    entries::EntryFilter,
+    exit::Failures,
+    out_name::{OutName, output_path, parse_out_name},
+    record::{line_timestamp, strip_timestamp},
+    subcommand::filtered_entries,
+    temp::InFlight,
+    time::parse_duration,
+};
+
+/// 淘汰窗口外的消息时额外保留的时长，容忍多个 sink 交错写入造成的少量乱序
+const REORDER_MS: i64 = 1000;
+
+#[derive(Parser)]
+pub struct DedupArgs {
+    /// 文件路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 时间容差，如 50ms：消息相同且时间相差小于该值的行视为重复 (常见于两个 sink 重复写入)；
+    /// 不指定时只去除时间与消息完全相同的行
+    #[arg(short, long, value_name = "DURATION", value_parser = parse_duration)]
+    pub tolerance: Option<Duration>,
+
+    /// 输出文件名模板，支持 {stem}、{ext}、{date}、{preset}、{profile}，如 `{stem}.{date}.clean.{ext}`；
+    /// 默认为 `{stem}_dedup.{ext}`，`.gz` 输入按解压后的文件名计算
+    #[arg(long, value_name = "TEMPLATE", value_parser = parse_out_name)]
+    pub out_name: Option<String>,
+}
+
+/// 按时间容差去重，消息为行内时间戳之后的部分 (含级别与模块)；
+/// 没有时间戳的续行跟随它前面的行一起保留或去除
+struct Dedup {
+    /// 时间差小于该毫秒数视为重复，精确去重时为 1 (即时间完全相同)
+    tolerance_ms: i64,
+    /// 窗口内每条消息各次保留时的时间，按保留顺序排列
+    kept: HashMap<String, VecDeque<i64>>,
+    /// 按保留顺序排列的 (时间, 消息)，用于淘汰窗口外的消息
+    window: VecDeque<(i64, String)>,
+    /// 目前为止最大的时间，允许少量乱序
+    latest: i64,
+    keep_continuation: bool,
+}
+
+impl Dedup {
+    fn new(tolerance: Option<Duration>) -> Self {
+        Dedup {
+            tolerance_ms: tolerance.map_or(1, |t| (t.as_millis() as i64).max(1)),
+            kept: HashMap::new(),
+            window: VecDeque::new(),
+            latest: i64::MIN,
+            keep_continuation: true,
+        }
+    }
+
+    fn keep(&mut self, line: &str) -> bool {
+        let Some(time) = line_timestamp(line) else {
+            return self.keep_continuation;
+        };
+
+        self.latest = self.latest.max(time);
+        while let Some((kept_at, _)) = self.window.front()
+            && *kept_at <= self.latest - self.tolerance_ms - REORDER_MS
+        {
+            let (_, message) = self.window.pop_front().unwrap();
+            if let Some(times) = self.kept.get_mut(&message) {
+                times.pop_front();
+                if times.is_empty() {
+                    self.kept.remove(&message);
+                }
+            }
+        }
+
+        let message = strip_timestamp(line);
+        let duplicate = self.kept.get(message).is_some_and(|times| {
+            times
+                .iter()
+                .any(|&kept_at| (time - kept_at).abs() < self.tolerance_ms)
+        });
+        if !duplicate {
+            self.kept
+                .entry(message.to_string())
+                .or_default()
+                .push_back(time);
+            self.window.push_back((time, message.to_string()));
+        }
+        self.keep_continuation = !duplicate;
+
+        !duplicate
+    }
+}
+
+pub fn process_dedup(ctx: &AppContext, args: DedupArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+    let out_name = args
+        .out_name
+        .map(|template| OutName::new(ctx, template, &[]))
+        .transpose()?;
+
+    if path.is_dir() {
+        let failures = Failures::new(ctx);
+        let entries = EntryFilter::default().skip_outputs(out_name.as_ref())?;
+        filtered_entries(&path, &entries).par_iter().for_each(|e| {
+            if failures.should_stop() {
+                return;
+            }
+            let file_path = e.path();
+            if let Err(e) = dedup_file(file_path, args.tolerance, out_name.as_ref()) {
+                eprintln!("❌ dedup failed, path {:?}, reason: {}", file_path, e);
+                failures.record(file_path, &e);
+            }
+        });
+        failures.finish()?;
+    } else {
+        dedup_file(&path, args.tolerance, out_name.as_ref())?;
+    }
+
+    Ok(())
+}
+
+fn dedup_file(path: &Path, tolerance: Option<Duration>, out_name: Option<&OutName>) -> Result<()> {
+    // 与 rl 一致，`.gz` 输入按解压后的文件名命名，输出同样压缩
+    let gzip = is_gzip(path);
+    let new_path = output_path(&plain_path(path), out_name, "_dedup")?;
+    let new_path = if gzip {
+        new_path.with_file_name(format!(
+            "{}.gz",
+            new_path.file_name().unwrap_or_default().display()
+        ))
+    } else {
+        new_path
+    };
+    let partial = InFlight::register(&new_path);
+    let mut output = BufWriter::new(LogWriter::create(&new_path, gzip)?);
+
+    let mut dedup = Dedup::new(tolerance);
+    let mut removed = 0;
+    for line in open_log(path)?.lines() {
+        let line = line?;
+        if dedup.keep(&line) {
+            output.write_all(line.as_bytes())?;
+            output.write_all(b"\n")?;
+        } else {
+            removed += 1;
+        }
+    }
+    output.into_inner().map_err(|e| e.into_error())?.finish()?;
+    partial.commit();
+
+    println!(
+        "write file after dedup, path: {:?}, removed lines: {}",
+        new_path.display(),
+        removed
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kept(lines: &[&str], tolerance: Option<Duration>) -> Vec<usize> {
+        let mut dedup = Dedup::new(tolerance);
+        (0..lines.len()).filter(|&i| dedup.keep(lines[i])).collect()
+    }
+
+    #[test]
+    fn test_dedup() {
+        let lines = [
+            "[2026-01-06 10:29:10.765] [error] [Global]  exception callback",
+            "    at ModelServer::load",
+            "[2026-01-06 10:29:10.770] [error] [Global]  exception callback",
+            "    at ModelServer::load",
+            "[2026-01-06 10:29:10.765] [error] [Global]  exception callback",
+            "[2026-01-06 10:29:10.768] [info] [Global]  exception callback",
+            "[2026-01-06 10:29:10.900] [error] [Global]  exception callback",
+        ];
+
+        assert_eq!(kept(&lines, None), vec![0, 1, 2, 3, 5, 6]);
+        assert_eq!(
+            kept(&lines, Some(Duration::from_millis(50))),
+            vec![0, 1, 5, 6]
+        );
+        assert_eq!(
+            kept(&lines, Some(Duration::from_millis(500))),
+            vec![0, 1, 5]
+        );
+    }
+}
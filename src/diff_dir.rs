@@ -0,0 +1,392 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashSet},
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    compress::open_log, context::AppContext, exit::Failures, new_lines::template,
+    record::parse_line, subcommand::get_entries, table::print_table,
+};
+
+/// 每个文件最多列出的新模板数
+const MAX_EXAMPLES: usize = 3;
+
+#[derive(Parser)]
+pub struct DiffDirArgs {
+    /// 作为基准的日志目录 (如上一次收集的日志包)
+    pub base: PathBuf,
+
+    /// 要对比的日志目录
+    pub target: PathBuf,
+
+    /// 列出变化最大的前 N 个文件
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub top: usize,
+
+    /// 以 json 格式输出
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+/// 单个文件的行数、错误数与消息模板
+#[derive(Default)]
+struct FileProfile {
+    lines: usize,
+    errors: usize,
+    templates: HashSet<String>,
+}
+
+impl FileProfile {
+    fn add_line(&mut self, line: &str) {
+        self.lines += 1;
+        if parse_line(line).is_some_and(|record| record.level.eq_ignore_ascii_case("error")) {
+            self.errors += 1;
+        }
+        let template = template(line);
+        if !template.is_empty() {
+            self.templates.insert(template);
+        }
+    }
+}
+
+fn file_profile(path: &Path) -> Result<FileProfile> {
+    let mut profile = FileProfile::default();
+    for line in open_log(path)?.lines() {
+        profile.add_line(&line?);
+    }
+
+    Ok(profile)
+}
+
+/// 目录下所有文件的概况，以相对路径为键，读取失败的文件记入 `failures`
+fn dir_profiles(dir: &Path, failures: &Failures) -> BTreeMap<PathBuf, FileProfile> {
+    get_entries(dir)
+        .into_par_iter()
+        .filter_map(|entry| {
+            if failures.should_stop() {
+                return None;
+            }
+            let path = entry.into_path();
+            let profile = file_profile(&path)
+                .inspect_err(|e| {
+                    eprintln!("❌ read failed, path {:?}, reason: {}", path, e);
+                    failures.record(&path, e);
+                })
+                .ok()?;
+            let key = path.strip_prefix(dir).unwrap_or(&path).to_path_buf();
+            Some((key, profile))
+        })
+        .collect()
+}
+
+/// 文件所属的组件：相对路径的第一级目录，根目录下的文件取第一个 `.` 之前的文件名
+pub fn component(key: &Path) -> String {
+    let mut parts = key.components();
+    let first = parts
+        .next()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned());
+    match (first, parts.next()) {
+        (Some(dir), Some(_)) => dir,
+        (Some(name), None) => name.split('.').next().unwrap_or_default().to_string(),
+        (None, _) => String::new(),
+    }
+}
+
+#[derive(Serialize)]
+struct FileDiff {
+    path: PathBuf,
+    component: String,
+    /// 只存在于其中一个目录时为 `base` / `target`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    only_in: Option<&'static str>,
+    base_lines: usize,
+    target_lines: usize,
+    base_errors: usize,
+    target_errors: usize,
+    new_templates: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<String>,
+}
+
+impl FileDiff {
+    fn new(path: &Path, base: Option<&FileProfile>, target: Option<&FileProfile>) -> Self {
+        let empty = FileProfile::default();
+        let only_in = match (base, target) {
+            (Some(_), None) => Some("base"),
+            (None, Some(_)) => Some("target"),
+            _ => None,
+        };
+        let base = base.unwrap_or(&empty);
+        let target = target.unwrap_or(&empty);
+
+        // 新增的文件整体都是新内容，不再逐条列出模板
+        let mut new_templates = Vec::new();
+        if only_in.is_none() {
+            new_templates = target
+                .templates
+                .difference(&base.templates)
+                .cloned()
+                .collect::<Vec<_>>();
+            new_templates.sort();
+        }
+
+        FileDiff {
+            path: path.to_path_buf(),
+            component: component(path),
+            only_in,
+            base_lines: base.lines,
+            target_lines: target.lines,
+            base_errors: base.errors,
+            target_errors: target.errors,
+            new_templates: new_templates.len(),
+            examples: new_templates.into_iter().take(MAX_EXAMPLES).collect(),
+        }
+    }
+
+    fn line_change(&self) -> usize {
+        self.base_lines.abs_diff(self.target_lines)
+    }
+
+    fn error_change(&self) -> usize {
+        self.base_errors.abs_diff(self.target_errors)
+    }
+
+    fn is_changed(&self) -> bool {
+        self.only_in.is_some()
+            || self.new_templates > 0
+            || self.error_change() > 0
+            || self.line_change() > 0
+    }
+
+    /// 变化程度的排序键：新模板优先，其次错误数与行数的变化
+    fn rank(&self) -> (usize, usize, usize) {
+        (self.new_templates, self.error_change(), self.line_change())
+    }
+}
+
+/// 同一组件下所有文件的变化汇总
+#[derive(Serialize, Default)]
+struct ComponentDiff {
+    component: String,
+    files: usize,
+    changed_files: usize,
+    base_lines: usize,
+    target_lines: usize,
+    base_errors: usize,
+    target_errors: usize,
+    new_templates: usize,
+}
+
+impl ComponentDiff {
+    fn rank(&self) -> (usize, usize, usize) {
+        (
+            self.new_templates,
+            self.base_errors.abs_diff(self.target_errors),
+            self.base_lines.abs_diff(self.target_lines),
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct DiffDirReport {
+    base: PathBuf,
+    target: PathBuf,
+    components: Vec<ComponentDiff>,
+    files: Vec<FileDiff>,
+}
+
+fn diff_profiles(
+    base: &BTreeMap<PathBuf, FileProfile>,
+    target: &BTreeMap<PathBuf, FileProfile>,
+) -> (Vec<ComponentDiff>, Vec<FileDiff>) {
+    let keys = base.keys().chain(target.keys()).collect::<HashSet<_>>();
+    let mut files = keys
+        .into_iter()
+        .map(|key| FileDiff::new(key, base.get(key), target.get(key)))
+        .collect::<Vec<_>>();
+
+    let mut components = BTreeMap::<String, ComponentDiff>::new();
+    for file in &files {
+        let entry = components
+            .entry(file.component.clone())
+            .or_insert_with(|| ComponentDiff {
+                component: file.component.clone(),
+                ..Default::default()
+            });
+        entry.files += 1;
+        entry.changed_files += usize::from(file.is_changed());
+        entry.base_lines += file.base_lines;
+        entry.target_lines += file.target_lines;
+        entry.base_errors += file.base_errors;
+        entry.target_errors += file.target_errors;
+        entry.new_templates += file.new_templates;
+    }
+
+    let mut components = components.into_values().collect::<Vec<_>>();
+    components.sort_by_key(|c| Reverse(c.rank()));
+    files.retain(FileDiff::is_changed);
+    files.sort_by(|a, b| b.rank().cmp(&a.rank()).then_with(|| a.path.cmp(&b.path)));
+
+    (components, files)
+}
+
+fn format_change(base: usize, target: usize) -> String {
+    let delta = target as i64 - base as i64;
+    format!("{base} -> {target} ({delta:+})")
+}
+
+fn print_report(report: &DiffDirReport, top: usize) {
+    println!(
+        "diff {} -> {}",
+        report.base.display(),
+        report.target.display()
+    );
+    println!();
+
+    let rows = report
+        .components
+        .iter()
+        .map(|c| {
+            vec![
+                c.component.clone(),
+                format!("{}/{}", c.changed_files, c.files),
+                format_change(c.base_lines, c.target_lines),
+                format_change(c.base_errors, c.target_errors),
+                c.new_templates.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_table(
+        &["component", "changed", "lines", "errors", "new templates"],
+        &rows,
+    );
+
+    if report.files.is_empty() {
+        println!("\nno changed files");
+        return;
+    }
+
+    println!();
+    let rows = report
+        .files
+        .iter()
+        .take(top)
+        .map(|f| {
+            let path = match f.only_in {
+                Some(side) => format!("{} (only in {side})", f.path.display()),
+                None => f.path.display().to_string(),
+            };
+            vec![
+                path,
+                format_change(f.base_lines, f.target_lines),
+                format_change(f.base_errors, f.target_errors),
+                f.new_templates.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_table(&["file", "lines", "errors", "new templates"], &rows);
+    if report.files.len() > top {
+        println!("... {} more changed files", report.files.len() - top);
+    }
+
+    for file in report.files.iter().take(top) {
+        if file.examples.is_empty() {
+            continue;
+        }
+        println!("\n🆕 {}:", file.path.display());
+        for example in &file.examples {
+            println!("  {example}");
+        }
+        if file.new_templates > file.examples.len() {
+            println!("  ... {} more", file.new_templates - file.examples.len());
+        }
+    }
+}
+
+pub fn process_diff_dir(ctx: &AppContext, args: DiffDirArgs) -> Result<()> {
+    let base = ctx.resolve_path(args.base)?;
+    let target = ctx.resolve_path(args.target)?;
+    for dir in [&base, &target] {
+        if !dir.is_dir() {
+            bail!("❌ {} is not a directory", dir.display());
+        }
+    }
+
+    let failures = Failures::new(ctx);
+    let (base_profiles, target_profiles) = rayon::join(
+        || dir_profiles(&base, &failures),
+        || dir_profiles(&target, &failures),
+    );
+    let (components, files) = diff_profiles(&base_profiles, &target_profiles);
+    let report = DiffDirReport {
+        base,
+        target,
+        components,
+        files,
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return failures.finish();
+    }
+    print_report(&report, args.top);
+
+    failures.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(lines: &[&str]) -> FileProfile {
+        let mut profile = FileProfile::default();
+        for line in lines {
+            profile.add_line(line);
+        }
+        profile
+    }
+
+    #[test]
+    fn test_diff_profiles() {
+        assert_eq!(component(Path::new("infer/a.log")), "infer");
+        assert_eq!(component(Path::new("gateway.log.1")), "gateway");
+
+        let base = BTreeMap::from([
+            (
+                PathBuf::from("infer/a.log"),
+                profile(&["[2026-01-06 10:29:10.765] [info] [Infer]  load model 1"]),
+            ),
+            (PathBuf::from("gateway.log"), profile(&["started"])),
+        ]);
+        let target = BTreeMap::from([
+            (
+                PathBuf::from("infer/a.log"),
+                profile(&[
+                    "[2026-01-06 11:29:10.765] [info] [Infer]  load model 2",
+                    "[2026-01-06 11:29:11.000] [error] [Infer]  oom, size 30",
+                ]),
+            ),
+            (PathBuf::from("gateway.log"), profile(&["started"])),
+            (PathBuf::from("infer/b.log"), profile(&["x"])),
+        ]);
+
+        let (components, files) = diff_profiles(&base, &target);
+        assert_eq!(components[0].component, "infer");
+        assert_eq!(components[0].changed_files, 2);
+        assert_eq!(components[0].new_templates, 1);
+        assert_eq!(components[1].changed_files, 0);
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, PathBuf::from("infer/a.log"));
+        assert_eq!(files[0].target_errors, 1);
+        assert_eq!(files[0].examples, vec!["[error] [Infer]  oom, size <*>"]);
+        assert_eq!(files[1].only_in, Some("target"));
+    }
+}
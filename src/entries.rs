@@ -0,0 +1,236 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use clap::Args;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use walkdir::WalkDir;
+
+use crate::{compress::plain_path, lock::LOCK_SUFFIX, out_name::OutName, undo::is_stashed};
+
+/// rl、transform、sample、dedup 的处理结果在文件名 (扩展名前) 加上的后缀
+pub const OUTPUT_SUFFIXES: [&str; 4] = ["_filtered", "_transformed", "_sample", "_dedup"];
+
+/// 文件夹模式下选择要处理的文件
+#[derive(Args, Clone, Default)]
+pub struct EntryArgs {
+    /// 只处理这些扩展名的文件，逗号分隔，如 `log,txt`；`.gz` 文件按解压后的扩展名判断
+    #[arg(long, value_delimiter = ',')]
+    pub ext: Vec<String>,
+
+    /// 只处理文件名或相对路径匹配这些 glob 的文件，如 `server_*`
+    #[arg(long)]
+    pub include: Vec<String>,
+
+    /// 跳过文件名或相对路径匹配这些 glob 的文件，默认为 `*_filtered*`、`*_transformed*`、`*_sample*`、`*_dedup*`
+    #[arg(long)]
+    pub exclude: Vec<String>,
+
+    /// 最多进入几层子目录，0 为只处理该目录下的文件
+    #[arg(long, value_name = "N")]
+    pub max_depth: Option<usize>,
+
+    /// 进入符号链接指向的目录与文件，默认跳过
+    #[arg(long, default_value_t = false)]
+    pub follow_symlinks: bool,
+
+    /// 不进入挂载在其他文件系统上的目录
+    #[arg(long, default_value_t = false)]
+    pub same_file_system: bool,
+}
+
+/// 遍历目录的方式
+#[derive(Clone, Copy, Default)]
+pub struct WalkOptions {
+    pub max_depth: Option<usize>,
+    pub follow_links: bool,
+    pub same_file_system: bool,
+}
+
+impl WalkOptions {
+    /// 从 `root` 开始遍历，`max_depth` 按 `root` 下的子目录层数计算
+    pub fn walk(self, root: &Path) -> WalkDir {
+        let walk = WalkDir::new(root)
+            .follow_links(self.follow_links)
+            .same_file_system(self.same_file_system);
+        match self.max_depth {
+            Some(depth) => walk.max_depth(depth + 1),
+            None => walk,
+        }
+    }
+}
+
+impl EntryArgs {
+    pub fn filter(&self) -> Result<EntryFilter> {
+        // 未指定 `--exclude` 时跳过各命令的处理结果
+        let exclude = if self.exclude.is_empty() {
+            OUTPUT_SUFFIXES.map(|suffix| format!("*{suffix}*")).to_vec()
+        } else {
+            self.exclude.clone()
+        };
+
+        Ok(EntryFilter {
+            walk: WalkOptions {
+                max_depth: self.max_depth,
+                follow_links: self.follow_symlinks,
+                same_file_system: self.same_file_system,
+            },
+            exts: self
+                .ext
+                .iter()
+                .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            include: (!self.include.is_empty())
+                .then(|| glob_set(&self.include))
+                .transpose()?,
+            exclude: glob_set(&exclude)?,
+            outputs: None,
+            output_dir: None,
+            backup_suffix: None,
+        })
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| anyhow!("❌ invalid glob `{pattern}`: {e}"))?;
+        builder.add(glob);
+    }
+
+    Ok(builder.build()?)
+}
+
+/// lp 自己的锁文件与 `.lp_undo` 中暂存的原文件，`rel` 为相对遍历起点的路径，任何遍历都应跳过
+pub fn is_internal(rel: &Path) -> bool {
+    rel.file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(LOCK_SUFFIX))
+        || is_stashed(rel)
+}
+
+/// 遍历目录的方式，以及按扩展名与 include/exclude glob 过滤文件，锁文件与 `.lp_undo` 中暂存的文件总是跳过
+pub struct EntryFilter {
+    pub walk: WalkOptions,
+    exts: Vec<String>,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    /// `--out-name` 模板生成的文件，不受 `--exclude` 影响
+    outputs: Option<GlobSet>,
+    /// `--out-dir` 或配置的输出目录，位于要处理的文件夹内时跳过
+    output_dir: Option<PathBuf>,
+    /// `rl --in-place --backup` 的备份后缀，备份总是跳过，不受 `--include` 影响
+    backup_suffix: Option<String>,
+}
+
+impl Default for EntryFilter {
+    fn default() -> Self {
+        EntryArgs::default()
+            .filter()
+            .expect("default exclude pattern is valid")
+    }
+}
+
+impl EntryFilter {
+    /// 同时跳过按 `out_name` 模板生成的文件，再次处理文件夹时不处理之前的结果
+    pub fn skip_outputs(self, out_name: Option<&OutName>) -> Result<Self> {
+        let Some(out_name) = out_name else {
+            return Ok(self);
+        };
+
+        Ok(EntryFilter {
+            outputs: Some(glob_set(&out_name.output_globs())?),
+            ..self
+        })
+    }
+
+    /// 同时跳过输出目录 `dir` 下的文件，输出目录在要处理的文件夹内时不处理之前的结果
+    pub fn skip_dir(self, dir: Option<&Path>) -> Self {
+        EntryFilter {
+            output_dir: dir.map(Path::to_path_buf),
+            ..self
+        }
+    }
+
+    /// 同时跳过以备份后缀 `suffix` 结尾的文件，再次处理文件夹时不改写之前的备份
+    pub fn skip_backups(self, suffix: Option<&str>) -> Self {
+        EntryFilter {
+            backup_suffix: suffix.filter(|s| !s.is_empty()).map(String::from),
+            ..self
+        }
+    }
+
+    /// `path` 为遍历得到的路径，位于输出目录下时为 true
+    pub fn is_output(&self, path: &Path) -> bool {
+        self.output_dir
+            .as_ref()
+            .is_some_and(|dir| path.starts_with(dir))
+    }
+
+    /// `rel` 为文件相对遍历起点的路径
+    pub fn is_match(&self, rel: &Path) -> bool {
+        let Some(name) = rel.file_name().and_then(|name| name.to_str()) else {
+            return false;
+        };
+        if is_internal(rel)
+            || self
+                .backup_suffix
+                .as_ref()
+                .is_some_and(|suffix| name.ends_with(suffix.as_str()))
+        {
+            return false;
+        }
+
+        if !self.exts.is_empty() {
+            let ext = plain_path(rel)
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase());
+            if !ext.is_some_and(|ext| self.exts.contains(&ext)) {
+                return false;
+            }
+        }
+
+        let matches = |set: &GlobSet| set.is_match(name) || set.is_match(rel);
+        self.include.as_ref().is_none_or(matches)
+            && !matches(&self.exclude)
+            && !self.outputs.as_ref().is_some_and(matches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_filter() {
+        let default = EntryFilter::default();
+        assert!(default.is_match(Path::new("a/server.log")));
+        assert!(!default.is_match(Path::new("a/server_filtered.log")));
+        assert!(!default.is_match(Path::new("a/server_transformed.log")));
+        assert!(!default.is_match(Path::new("a/server_sample.log")));
+        assert!(!default.is_match(Path::new("a/server_dedup.log.gz")));
+        assert!(!default.is_match(Path::new("a/.server.log.lp.lock")));
+
+        let filter = EntryArgs {
+            ext: vec!["log".to_string(), ".TXT".to_string()],
+            include: vec!["server_*".to_string()],
+            exclude: vec!["old/**".to_string()],
+            ..Default::default()
+        }
+        .filter()
+        .unwrap();
+        assert!(filter.is_match(Path::new("a/server_1.log")));
+        assert!(filter.is_match(Path::new("server_2.txt")));
+        assert!(filter.is_match(Path::new("a/server_3.log.gz")));
+        assert!(filter.is_match(Path::new("a/server_filtered.log")));
+        assert!(!filter.is_match(Path::new("a/server_1.json")));
+        assert!(!filter.is_match(Path::new("a/client_1.log")));
+        assert!(!filter.is_match(Path::new("old/server_1.log")));
+
+        let filter = filter.skip_backups(Some(".bak"));
+        assert!(filter.is_match(Path::new("a/server_1.log")));
+        assert!(!filter.is_match(Path::new("a/server_1.log.bak")));
+    }
+}
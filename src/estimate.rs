@@ -0,0 +1,290 @@
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{BufRead, BufReader, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::Result;
+
+use crate::{
+    compress::{is_gzip, open_log},
+    context::AppContext,
+    entries::EntryFilter,
+    exit::{Failures, NoMatches},
+    matcher::Matcher,
+    schedule::{Schedule, par_map_scheduled},
+    subcommand::filtered_entries,
+    units::format_size,
+};
+
+/// 抽样读取的块大小
+const BLOCK_SIZE: u64 = 1 << 20;
+
+/// 每个文件至少抽样的块数，不超过这么多块的文件直接精确计数
+const MIN_BLOCKS: u64 = 16;
+
+/// 95% 置信区间对应的正态分布分位数
+const Z_95: f64 = 1.96;
+
+/// 一个文件的估算结果，`margin` 为 95% 置信区间的半宽，精确计数时为 0
+struct Estimate {
+    matches: f64,
+    lines: f64,
+    margin: f64,
+    bytes: u64,
+    sampled_bytes: u64,
+}
+
+impl Estimate {
+    fn exact(&self) -> bool {
+        self.sampled_bytes >= self.bytes
+    }
+}
+
+/// 一个抽样块中起始于块内的行
+#[derive(Default)]
+struct BlockCount {
+    bytes: u64,
+    lines: u64,
+    matches: u64,
+}
+
+/// 确定性的伪随机数 (xorshift64*)，同一文件每次抽到相同的块，结果可复现
+struct Rng(u64);
+
+impl Rng {
+    fn new(path: &Path) -> Self {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        Rng(hasher.finish() | 1)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) % n
+    }
+}
+
+/// 要抽样的块：首块、末块与随机的其余块，共 `count` 个
+fn sample_blocks(path: &Path, blocks: u64, count: u64) -> BTreeSet<u64> {
+    let mut sampled = BTreeSet::from([0, blocks - 1]);
+    let mut rng = Rng::new(path);
+    while (sampled.len() as u64) < count {
+        sampled.insert(rng.below(blocks));
+    }
+
+    sampled
+}
+
+/// 统计起始于 `[start, start + BLOCK_SIZE)` 的行，跨越块起点的行属于前一块
+fn count_block(file: &mut File, start: u64, matcher: &Matcher) -> Result<BlockCount> {
+    file.seek(SeekFrom::Start(start))?;
+    let mut reader = BufReader::new(file);
+    let mut pos = start;
+    let mut buf = Vec::new();
+    if start > 0 {
+        pos += reader.read_until(b'\n', &mut buf)? as u64;
+    }
+
+    let mut count = BlockCount::default();
+    while pos < start + BLOCK_SIZE {
+        buf.clear();
+        let n = reader.read_until(b'\n', &mut buf)? as u64;
+        if n == 0 {
+            break;
+        }
+        pos += n;
+        count.bytes += n;
+        count.lines += 1;
+        let line = String::from_utf8_lossy(&buf);
+        if matcher.is_match(line.trim_end_matches(['\n', '\r'])) {
+            count.matches += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// 小文件与 `.gz` (无法随机读取) 精确计数，其余按块抽样后以命中数/字节数的比率外推
+fn estimate_file(path: &Path, matcher: &Matcher, fraction: f64) -> Result<Estimate> {
+    let bytes = path.metadata()?.len();
+    let blocks = bytes.div_ceil(BLOCK_SIZE);
+    let count = ((blocks as f64 * fraction).ceil() as u64).max(MIN_BLOCKS);
+    if is_gzip(path) || count >= blocks {
+        let mut lines = 0;
+        let mut matches = 0;
+        for line in open_log(path)?.lines() {
+            lines += 1;
+            if matcher.is_match(&line?) {
+                matches += 1;
+            }
+        }
+        return Ok(Estimate {
+            matches: matches as f64,
+            lines: lines as f64,
+            margin: 0.0,
+            bytes,
+            sampled_bytes: bytes,
+        });
+    }
+
+    let mut file = File::open(path)?;
+    let counts = sample_blocks(path, blocks, count)
+        .into_iter()
+        .map(|block| count_block(&mut file, block * BLOCK_SIZE, matcher))
+        .collect::<Result<Vec<_>>>()?;
+
+    let sampled_bytes = counts.iter().map(|c| c.bytes).sum::<u64>();
+    let sampled_matches = counts.iter().map(|c| c.matches).sum::<u64>();
+    let sampled_lines = counts.iter().map(|c| c.lines).sum::<u64>();
+    let scale = bytes as f64 / sampled_bytes.max(1) as f64;
+    let ratio = sampled_matches as f64 / sampled_bytes.max(1) as f64;
+
+    // 比率估计的方差：以块为单位，含有限总体校正
+    let n = counts.len() as f64;
+    let residuals = counts
+        .iter()
+        .map(|c| (c.matches as f64 - ratio * c.bytes as f64).powi(2))
+        .sum::<f64>();
+    let variance = (blocks as f64).powi(2) * (1.0 - n / blocks as f64) * residuals / (n - 1.0) / n;
+
+    Ok(Estimate {
+        matches: sampled_matches as f64 * scale,
+        lines: sampled_lines as f64 * scale,
+        margin: Z_95 * variance.sqrt(),
+        bytes,
+        sampled_bytes,
+    })
+}
+
+fn print_estimate(label: &str, estimate: &Estimate) {
+    if estimate.exact() {
+        println!(
+            "{label}, keyword lines: {:.0}, lines: {:.0} (exact)",
+            estimate.matches, estimate.lines
+        );
+        return;
+    }
+
+    println!(
+        "{label}, keyword lines: ~{:.0} ± {:.0} (95% confidence), lines: ~{:.0}, sampled {} of {} ({:.2}%)",
+        estimate.matches,
+        estimate.margin,
+        estimate.lines,
+        format_size(estimate.sampled_bytes),
+        format_size(estimate.bytes),
+        estimate.sampled_bytes as f64 * 100.0 / estimate.bytes as f64
+    );
+}
+
+/// `cl --estimate`：每个文件抽样 `fraction` 比例的块估算命中行数，文件夹时同时输出合计
+pub fn estimate_check(
+    ctx: &AppContext,
+    path: &Path,
+    is_dir: bool,
+    matcher: &Matcher,
+    fraction: f64,
+    entries: &EntryFilter,
+    schedule: Schedule,
+) -> Result<()> {
+    if !is_dir {
+        let estimate = estimate_file(path, matcher, fraction)?;
+        print_estimate(&format!("file: {}", path.display()), &estimate);
+        if estimate.matches == 0.0 {
+            return Err(NoMatches.into());
+        }
+        return Ok(());
+    }
+
+    let entries = filtered_entries(path, entries);
+    let failures = Failures::new(ctx);
+    let estimates = par_map_scheduled("cl", &entries, schedule, |e| {
+        if failures.should_stop() {
+            return None;
+        }
+        let file_path = e.path();
+        estimate_file(file_path, matcher, fraction)
+            .inspect(|estimate| print_estimate(&format!("file: {}", file_path.display()), estimate))
+            .inspect_err(|e| {
+                eprintln!("❌ estimate failed, path {:?}, reason: {}", file_path, e);
+                failures.record(file_path, e);
+            })
+            .ok()
+    })
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    // 各文件独立抽样，合计的方差为各文件方差之和
+    let total = Estimate {
+        matches: estimates.iter().map(|e| e.matches).sum(),
+        lines: estimates.iter().map(|e| e.lines).sum(),
+        margin: estimates
+            .iter()
+            .map(|e| e.margin.powi(2))
+            .sum::<f64>()
+            .sqrt(),
+        bytes: estimates.iter().map(|e| e.bytes).sum(),
+        sampled_bytes: estimates.iter().map(|e| e.sampled_bytes).sum(),
+    };
+    print_estimate(&format!("total: files: {}", estimates.len()), &total);
+
+    failures.finish()?;
+    if total.matches == 0.0 {
+        return Err(NoMatches.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        io::{BufWriter, Write},
+        process,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_estimate_file() {
+        let path = env::temp_dir().join(format!("lp_estimate_{}.log", process::id()));
+        let mut file = BufWriter::new(File::create(&path).unwrap());
+        // 约 40 MB，每 10 行一行命中
+        let mut expected = 0;
+        for i in 0..600_000 {
+            let keyword = if i % 10 == 0 {
+                expected += 1;
+                "timeout"
+            } else {
+                "ok"
+            };
+            writeln!(
+                file,
+                "[2026-01-06 10:29:10.765] [info] [Net]  request {i} {keyword}"
+            )
+            .unwrap();
+        }
+        drop(file);
+
+        let matcher = Matcher::new(&["timeout".to_string()], false).unwrap();
+        let estimate = estimate_file(&path, &matcher, 0.1).unwrap();
+        assert!(!estimate.exact());
+        assert!(estimate.sampled_bytes < estimate.bytes / 2);
+        assert!(
+            (estimate.matches - expected as f64).abs()
+                <= estimate.margin.max(expected as f64 * 0.01)
+        );
+        assert!((estimate.lines - 600_000.0).abs() < 6_000.0);
+
+        let exact = estimate_file(&path, &matcher, 1.0).unwrap();
+        assert!(exact.exact());
+        assert_eq!(exact.matches, expected as f64);
+        fs::remove_file(&path).unwrap();
+    }
+}
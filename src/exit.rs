@@ -0,0 +1,120 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Mutex,
+};
+
+use anyhow::{Result, bail};
+
+use crate::context::AppContext;
+
+/// 与 grep 一致：0 成功 (cl 有命中)，1 cl 没有命中，2 出错或有文件处理失败
+const EXIT_NO_MATCHES: u8 = 1;
+const EXIT_ERROR: u8 = 2;
+
+/// cl 没有命中任何行，不输出错误信息，只以退出码 1 结束
+#[derive(Debug)]
+pub struct NoMatches;
+
+impl fmt::Display for NoMatches {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no matching lines")
+    }
+}
+
+impl std::error::Error for NoMatches {}
+
+/// 文件夹模式下失败 (含超时) 的文件：并行处理时逐个记录，结束后统一汇总
+pub struct Failures {
+    fail_fast: bool,
+    files: Mutex<Vec<(PathBuf, String)>>,
+}
+
+impl Failures {
+    pub fn new(ctx: &AppContext) -> Self {
+        Failures {
+            fail_fast: ctx.fail_fast(),
+            files: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn record(&self, path: &Path, error: &anyhow::Error) {
+        self.files
+            .lock()
+            .unwrap()
+            .push((path.to_path_buf(), error.to_string()));
+    }
+
+    /// `--fail-fast` 且已有文件失败，不再开始新的文件
+    pub fn should_stop(&self) -> bool {
+        self.fail_fast && !self.files.lock().unwrap().is_empty()
+    }
+
+    /// 按路径输出失败的文件及原因，有失败时返回汇总的错误
+    pub fn finish(self) -> Result<()> {
+        let mut files = self.files.into_inner().unwrap();
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        files.sort();
+        eprintln!("❌ failed files: {}", files.len());
+        for (path, reason) in &files {
+            eprintln!("  {}: {}", path.display(), reason);
+        }
+        if self.fail_fast {
+            bail!(
+                "❌ stopped after {} failed files (--fail-fast)",
+                files.len()
+            );
+        }
+        bail!("❌ {} files failed", files.len());
+    }
+}
+
+pub fn exit_code(result: &Result<()>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) if e.is::<NoMatches>() => ExitCode::from(EXIT_NO_MATCHES),
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn test_failures() {
+        let ctx = AppContext::new("config.json");
+        assert!(Failures::new(&ctx).finish().is_ok());
+
+        let failures = Failures::new(&ctx);
+        failures.record(Path::new("b.log"), &anyhow!("timeout"));
+        failures.record(Path::new("a.log"), &anyhow!("denied"));
+        assert!(!failures.should_stop());
+        let result = failures.finish();
+        assert_eq!(
+            result.as_ref().unwrap_err().to_string(),
+            "❌ 2 files failed"
+        );
+        assert_eq!(exit_code(&result), ExitCode::from(EXIT_ERROR));
+
+        let failures = Failures::new(&ctx.with_fail_fast());
+        assert!(!failures.should_stop());
+        failures.record(Path::new("a.log"), &anyhow!("denied"));
+        assert!(failures.should_stop());
+
+        assert_eq!(exit_code(&Ok(())), ExitCode::SUCCESS);
+        assert_eq!(
+            exit_code(&Err(NoMatches.into())),
+            ExitCode::from(EXIT_NO_MATCHES)
+        );
+    }
+}
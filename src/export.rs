@@ -0,0 +1,503 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow, bail};
+use clap::{Parser, ValueEnum};
+use rayon::prelude::*;
+
+use crate::{
+    compress::open_log,
+    context::AppContext,
+    entries::EntryFilter,
+    exit::Failures,
+    mapping::{ColumnMap, Value, apply_maps, mapped_columns, parse_column_map},
+    out_name::{OutName, OverwriteArgs, mirror_path, parse_out_name, template_name},
+    output::OutputFormat,
+    record::{Position, PositionedLines, parse_line, positioned_lines},
+    subcommand::{filtered_entries, get_entries},
+    table::TableWriter,
+    temp::InFlight,
+    time::{TimeRange, parse_timestamp},
+};
+
+/// 导出格式
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Xlsx,
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Parser)]
+pub struct ExportArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 导出格式，输出到源文件旁的同名文件
+    #[arg(short, long, value_enum, default_value = "xlsx")]
+    pub format: ExportFormat,
+
+    /// 输出文件名模板，支持 {stem}、{ext}、{date}、{preset}、{profile}，{ext} 为导出格式的扩展名，如 `{stem}_{date}.{ext}`
+    #[arg(
+        long,
+        visible_alias = "out-template",
+        value_name = "TEMPLATE",
+        value_parser = parse_out_name
+    )]
+    pub out_name: Option<String>,
+
+    /// 输出目录，覆盖配置中的 `output_dir`；导出文件夹时保留其下的目录结构
+    #[arg(long, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub overwrite: OverwriteArgs,
+
+    #[command(flatten)]
+    pub time_range: TimeRange,
+
+    /// 对每条记录计算一列，`名称=模板`，模板中 `{列|处理|...}` 引用已有的列，同名时替换该列；
+    /// 处理有 trunc:N、extract:正则、num、div:N、mul:N、round:N、upper、lower、map:a=1,b=2、default:X，
+    /// 如 `mem_gb={message|extract:mem (\d+)MB|div:1024|round:2}`、`severity_num={level|map:info=2,warn=3,error=4}`
+    #[arg(long = "map", value_name = "NAME=TEMPLATE", value_parser = parse_column_map)]
+    pub maps: Vec<ColumnMap>,
+
+    /// 追加 source、line、offset 列：记录所在的源文件、首行的行号与字节偏移 (`.gz` 为解压后的)，
+    /// 便于从导出的每一行追溯到原始日志
+    #[arg(long, default_value_t = false)]
+    pub provenance: bool,
+}
+
+/// 导出的基础列，与 [`ExportRecord`] 的字段一一对应
+const COLUMNS: [&str; 4] = ["time", "level", "module", "message"];
+
+/// `--provenance` 追加的列
+const PROVENANCE_COLUMNS: [&str; 3] = ["source", "line", "offset"];
+
+fn base_columns(provenance: bool) -> Vec<&'static str> {
+    let mut columns = COLUMNS.to_vec();
+    if provenance {
+        columns.extend(PROVENANCE_COLUMNS);
+    }
+    columns
+}
+
+/// 一条结构化的日志记录
+struct ExportRecord {
+    time: String,
+    level: String,
+    module: String,
+    message: String,
+    /// 首行的位置
+    position: Position,
+}
+
+/// 逐条解析 `[time] [level] [module] message` 结构，续行 (如堆栈) 合并到上一条的 message 中；
+/// 限定了时间范围时跳过范围外及没有时间的记录
+struct ExportRecords<R> {
+    lines: PositionedLines<R>,
+    pending: Option<ExportRecord>,
+    time_range: TimeRange,
+}
+
+fn parse_records<R: BufRead>(reader: R, time_range: TimeRange) -> ExportRecords<R> {
+    ExportRecords {
+        lines: positioned_lines(reader),
+        pending: None,
+        time_range,
+    }
+}
+
+impl<R> ExportRecords<R> {
+    fn in_range(&self, record: &ExportRecord) -> bool {
+        self.time_range.is_unbounded()
+            || parse_timestamp(&record.time).is_some_and(|ms| self.time_range.contains(ms))
+    }
+}
+
+impl<R: BufRead> Iterator for ExportRecords<R> {
+    type Item = io::Result<ExportRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (position, line) = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    let record = self.pending.take()?;
+                    return self.in_range(&record).then_some(Ok(record));
+                }
+            };
+
+            match (parse_line(&line), &mut self.pending) {
+                (Some(parsed), _) => {
+                    let record = self.pending.replace(ExportRecord {
+                        time: parsed.time.to_string(),
+                        level: parsed.level.to_string(),
+                        module: parsed.module.to_string(),
+                        message: parsed.message.to_string(),
+                        position,
+                    });
+                    if let Some(record) = record
+                        && self.in_range(&record)
+                    {
+                        return Some(Ok(record));
+                    }
+                }
+                (None, Some(last)) => {
+                    last.message.push('\n');
+                    last.message.push_str(&line);
+                }
+                (None, None) => {
+                    self.pending = Some(ExportRecord {
+                        time: String::new(),
+                        level: String::new(),
+                        module: String::new(),
+                        message: line,
+                        position,
+                    })
+                }
+            }
+        }
+    }
+}
+
+impl ExportRecord {
+    /// 应用列映射后的各列取值，与 [`mapped_columns`] 一一对应；`source` 为 `--provenance` 时的源文件
+    fn values(self, source: Option<&str>, maps: &[ColumnMap]) -> Result<Vec<Value>> {
+        let mut values = [self.time, self.level, self.module, self.message]
+            .map(Value::Text)
+            .to_vec();
+        if let Some(source) = source {
+            values.extend([
+                Value::Text(source.to_string()),
+                Value::Number(self.position.line as f64),
+                Value::Number(self.position.offset as f64),
+            ]);
+        }
+        apply_maps(&base_columns(source.is_some()), values, maps).map_err(|e| anyhow!("❌ {e}"))
+    }
+}
+
+/// json 对象，按列的顺序写出
+fn write_json_object<W: Write>(output: &mut W, columns: &[String], values: &[Value]) -> Result<()> {
+    output.write_all(b"{")?;
+    for (i, (column, value)) in columns.iter().zip(values).enumerate() {
+        if i > 0 {
+            output.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut *output, column)?;
+        output.write_all(b":")?;
+        match value {
+            Value::Text(s) => serde_json::to_writer(&mut *output, s)?,
+            // 整数不带小数点写出
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => {
+                serde_json::to_writer(&mut *output, &(*n as i64))?
+            }
+            Value::Number(n) => serde_json::to_writer(&mut *output, n)?,
+        }
+    }
+    output.write_all(b"}")?;
+
+    Ok(())
+}
+
+/// 逐条写出 json 对象：ndjson 时每行一个，否则为数组的元素；`count` 为此前已写出的条数
+fn write_json_records<R: BufRead, W: Write>(
+    records: ExportRecords<R>,
+    output: &mut W,
+    columns: &[String],
+    source: Option<&str>,
+    maps: &[ColumnMap],
+    ndjson: bool,
+    count: &mut usize,
+) -> Result<()> {
+    for record in records {
+        let values = record?.values(source, maps)?;
+        if !ndjson {
+            output.write_all(if *count == 0 { b"\n  " } else { b",\n  " })?;
+        }
+        write_json_object(output, columns, &values)?;
+        if ndjson {
+            output.write_all(b"\n")?;
+        }
+        *count += 1;
+    }
+
+    Ok(())
+}
+
+/// 导出文件的路径：源文件旁的同名文件，或按模板命名；指定了输出目录时放到其下与 `root` 相同的相对位置
+fn export_path(
+    path: &Path,
+    root: &Path,
+    format: ExportFormat,
+    out_name: Option<&OutName>,
+    out_dir: Option<&Path>,
+) -> Result<PathBuf> {
+    let mut new_path = path.with_extension(format.extension());
+    if let Some(out_name) = out_name {
+        new_path = new_path.with_file_name(template_name(out_name, &new_path)?);
+    }
+    if let Some(dir) = out_dir {
+        new_path = mirror_path(&new_path, root, dir);
+    }
+    if new_path == path {
+        bail!("❌ export would overwrite {}", path.display());
+    }
+
+    Ok(new_path)
+}
+
+/// 边解析边写出，内存占用与文件大小无关
+fn export_file(
+    path: &Path,
+    new_path: &Path,
+    format: ExportFormat,
+    time_range: TimeRange,
+    maps: &[ColumnMap],
+    provenance: bool,
+) -> Result<()> {
+    let records = parse_records(open_log(path)?, time_range);
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let columns = mapped_columns(&base_columns(provenance), maps).map_err(|e| anyhow!("❌ {e}"))?;
+    let source = path.display().to_string();
+    let source = provenance.then_some(source.as_str());
+
+    match format {
+        ExportFormat::Json => {
+            let partial = InFlight::register(new_path);
+            let mut output = BufWriter::new(File::create(new_path)?);
+            output.write_all(b"[")?;
+            let mut count = 0;
+            write_json_records(
+                records,
+                &mut output,
+                &columns,
+                source,
+                maps,
+                false,
+                &mut count,
+            )?;
+            output.write_all(b"\n]\n")?;
+            output.flush()?;
+            partial.commit();
+        }
+        ExportFormat::Xlsx | ExportFormat::Csv => {
+            let headers = columns.iter().map(String::as_str).collect::<Vec<_>>();
+            let mut writer = TableWriter::create(new_path, &headers)?;
+            for record in records {
+                let values = record?.values(source, maps)?;
+                writer.write_row(&values.iter().map(Value::text).collect::<Vec<_>>())?;
+            }
+            writer.finish()?;
+        }
+    }
+    println!("write export file, path: {:?}", new_path.display());
+
+    Ok(())
+}
+
+pub fn process_export(ctx: &AppContext, args: ExportArgs) -> Result<()> {
+    // 先检查映射引用的列，避免逐个文件报同样的错
+    mapped_columns(&base_columns(args.provenance), &args.maps).map_err(|e| anyhow!("❌ {e}"))?;
+    let path = ctx.resolve_path(args.path.clone())?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    if ctx.output_format() != OutputFormat::Text {
+        return export_stdout(
+            ctx,
+            &path,
+            &args,
+            ctx.output_format() == OutputFormat::Ndjson,
+        );
+    }
+
+    let out_dir = match &args.out_dir {
+        Some(dir) => Some(ctx.resolve_path(dir.clone())?),
+        None => ctx.output_dir()?,
+    };
+    let out_name = args
+        .out_name
+        .clone()
+        .map(|template| OutName::new(ctx, template, &[]))
+        .transpose()?;
+    let export = |file_path: &Path, root: &Path| {
+        let new_path = args.overwrite.claim(&export_path(
+            file_path,
+            root,
+            args.format,
+            out_name.as_ref(),
+            out_dir.as_deref(),
+        )?)?;
+        export_file(
+            file_path,
+            &new_path,
+            args.format,
+            args.time_range,
+            &args.maps,
+            args.provenance,
+        )
+    };
+
+    if path.is_dir() {
+        let extension = args.format.extension();
+        let entries = EntryFilter::default()
+            .skip_outputs(out_name.as_ref())?
+            .skip_dir(out_dir.as_deref());
+        let failures = Failures::new(ctx);
+        filtered_entries(&path, &entries)
+            .par_iter()
+            .filter(|e| e.path().extension().is_none_or(|ext| ext != extension))
+            .for_each(|e| {
+                if failures.should_stop() {
+                    return;
+                }
+                let file_path = e.path();
+                if let Err(e) = export(file_path, &path) {
+                    eprintln!("❌ export failed, path {:?}, reason: {}", file_path, e);
+                    failures.record(file_path, &e);
+                }
+            });
+        failures.finish()?;
+    } else {
+        export(&path, path.parent().unwrap_or(Path::new("")))?;
+    }
+
+    Ok(())
+}
+
+/// `lp --format json|ndjson export`：所有文件的记录按路径顺序写到 stdout，不生成文件
+fn export_stdout(ctx: &AppContext, path: &Path, args: &ExportArgs, ndjson: bool) -> Result<()> {
+    let files = if path.is_dir() {
+        let mut files = get_entries(path)
+            .into_iter()
+            .map(|e| e.into_path())
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let columns = mapped_columns(&base_columns(args.provenance), &args.maps)
+        .map_err(|e| anyhow!("❌ {e}"))?;
+
+    let mut output = BufWriter::new(io::stdout().lock());
+    if !ndjson {
+        output.write_all(b"[")?;
+    }
+    let mut count = 0;
+    let failures = Failures::new(ctx);
+    for file in &files {
+        if failures.should_stop() {
+            break;
+        }
+        let source = file.display().to_string();
+        let source = args.provenance.then_some(source.as_str());
+        let result = open_log(file)
+            .map_err(anyhow::Error::from)
+            .and_then(|reader| {
+                let records = parse_records(reader, args.time_range);
+                write_json_records(
+                    records,
+                    &mut output,
+                    &columns,
+                    source,
+                    &args.maps,
+                    ndjson,
+                    &mut count,
+                )
+            });
+        // 已写出的都是完整的记录，跳过出错的文件不影响输出的格式
+        if let Err(e) = result {
+            eprintln!("❌ export failed, path {:?}, reason: {}", file, e);
+            failures.record(file, &e);
+        }
+    }
+    if !ndjson {
+        output.write_all(b"\n]\n")?;
+    }
+    output.flush()?;
+
+    failures.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_records() {
+        let content = "\
+banner
+[2026-01-06 10:29:10.765] [error] [Global]  exception callback: ERRCODE_MSOPTIMEOUT
+    at ModelServer::load
+[2026-01-06 10:29:11.000] [info] [ModelServer]  generateAllGltfModel called
+";
+        let parse = |time_range| {
+            parse_records(content.as_bytes(), time_range)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap()
+        };
+
+        let records = parse(TimeRange::default());
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].message, "banner");
+        assert_eq!(records[0].time, "");
+        assert_eq!(records[1].level, "error");
+        assert_eq!(records[1].module, "Global");
+        assert_eq!(
+            records[1].message,
+            "exception callback: ERRCODE_MSOPTIMEOUT\n    at ModelServer::load"
+        );
+        assert_eq!(records[2].time, "2026-01-06 10:29:11.000");
+        assert_eq!(
+            records[2].position,
+            Position {
+                line: 4,
+                offset: 116
+            }
+        );
+
+        let values = parse(TimeRange::default())
+            .pop()
+            .unwrap()
+            .values(Some("a.log"), &[])
+            .unwrap();
+        assert_eq!(values.len(), 7);
+        assert_eq!(
+            values[4..],
+            [
+                Value::Text("a.log".into()),
+                Value::Number(4.0),
+                Value::Number(116.0)
+            ]
+        );
+
+        let time_range = TimeRange {
+            since: None,
+            until: parse_timestamp("2026-01-06 10:29:11"),
+        };
+        let records = parse(time_range);
+        assert_eq!(records.len(), 1);
+        assert!(records[0].message.ends_with("at ModelServer::load"));
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::{Result, bail};
+
+/// 关键字布尔表达式，如 `(error AND ModelServer) OR "cpu usage"`；
+/// 优先级 NOT > AND > OR，关键字为子串匹配，含空格或括号时用双引号
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    Term(String),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            bail!(
+                "❌ invalid expression `{input}`: unexpected {}",
+                token.describe()
+            );
+        }
+
+        Ok(expr)
+    }
+
+    /// `contains(term)` 判断单个关键字是否命中
+    pub fn eval(&self, contains: &impl Fn(&str) -> bool) -> bool {
+        match self {
+            Expr::Term(term) => contains(term),
+            Expr::Not(expr) => !expr.eval(contains),
+            Expr::And(exprs) => exprs.iter().all(|e| e.eval(contains)),
+            Expr::Or(exprs) => exprs.iter().any(|e| e.eval(contains)),
+        }
+    }
+
+    /// 对每个关键字做同样的变换，如简繁体归一
+    pub fn map_terms(self, f: &impl Fn(String) -> String) -> Self {
+        match self {
+            Expr::Term(term) => Expr::Term(f(term)),
+            Expr::Not(expr) => Expr::Not(Box::new(expr.map_terms(f))),
+            Expr::And(exprs) => Expr::And(exprs.into_iter().map(|e| e.map_terms(f)).collect()),
+            Expr::Or(exprs) => Expr::Or(exprs.into_iter().map(|e| e.map_terms(f)).collect()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Term(String),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Term(term) => format!("`{term}`"),
+            Token::And => "AND".to_string(),
+            Token::Or => "OR".to_string(),
+            Token::Not => "NOT".to_string(),
+            Token::Open => "`(`".to_string(),
+            Token::Close => "`)`".to_string(),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut chars = input.char_indices().peekable();
+    let mut tokens = Vec::new();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let mut term = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => term.push(c),
+                            None => bail!("❌ invalid expression `{input}`: unterminated quote"),
+                        },
+                        Some((_, c)) => term.push(c),
+                        None => bail!("❌ invalid expression `{input}`: unterminated quote"),
+                    }
+                }
+                tokens.push(Token::Term(term));
+            }
+            _ => {
+                let mut end = input.len();
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                        end = i;
+                        break;
+                    }
+                    chars.next();
+                }
+                tokens.push(match &input[start..end] {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    word => Token::Term(word.to_string()),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// 递归下降：or := and (OR and)*，and := not (AND not)*，not := NOT not | (or) | term
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn eat(&mut self, token: &Token) -> bool {
+        let matched = self.tokens.get(self.pos) == Some(token);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.and()?];
+        while self.eat(&Token::Or) {
+            exprs.push(self.and()?);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.pop().unwrap()
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.not()?];
+        while self.eat(&Token::And) {
+            exprs.push(self.not()?);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.pop().unwrap()
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        if self.eat(&Token::Open) {
+            let expr = self.or()?;
+            if !self.eat(&Token::Close) {
+                bail!("❌ invalid expression: missing `)`");
+            }
+            return Ok(expr);
+        }
+
+        match self.tokens.get(self.pos) {
+            Some(Token::Term(term)) => {
+                let term = term.clone();
+                self.pos += 1;
+                Ok(Expr::Term(term))
+            }
+            Some(token) => bail!(
+                "❌ invalid expression: expected a keyword, found {}",
+                token.describe()
+            ),
+            None => bail!("❌ invalid expression: expected a keyword, found end of input"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(s: &str) -> Expr {
+        Expr::Term(s.to_string())
+    }
+
+    #[test]
+    fn test_parse_expr() {
+        assert_eq!(
+            Expr::parse(r#"(error AND ModelServer) OR "cpu usage""#).unwrap(),
+            Expr::Or(vec![
+                Expr::And(vec![term("error"), term("ModelServer")]),
+                term("cpu usage"),
+            ])
+        );
+        assert_eq!(
+            Expr::parse("a OR b AND NOT c").unwrap(),
+            Expr::Or(vec![
+                term("a"),
+                Expr::And(vec![term("b"), Expr::Not(Box::new(term("c")))]),
+            ])
+        );
+        assert_eq!(
+            Expr::parse(r#""say \"hi\"" AND tid:"#).unwrap(),
+            Expr::And(vec![term(r#"say "hi""#), term("tid:")])
+        );
+
+        assert!(Expr::parse("a AND").is_err());
+        assert!(Expr::parse("(a OR b").is_err());
+        assert!(Expr::parse("a b").is_err());
+        assert!(Expr::parse(r#""open"#).is_err());
+        assert!(Expr::parse("").is_err());
+
+        let expr = Expr::parse(r#"(error AND ModelServer) OR "cpu usage""#).unwrap();
+        let eval = |line: &str| expr.eval(&|term| line.contains(term));
+        assert!(eval("[error] [ModelServer]  load failed"));
+        assert!(eval("[info] [Global]  cpu usage: 5.83%"));
+        assert!(!eval("[error] [Global]  timeout"));
+    }
+}
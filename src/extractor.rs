@@ -0,0 +1,140 @@
+use anyhow::{Result, anyhow, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    context::AppContext,
+    record::{Metric, parse_percent},
+};
+
+/// 配置中的自定义指标，如
+/// `{ "name": "latency", "pattern": "cost: (\\d+(?:\\.\\d+)?)ms", "unit": "ms" }`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MetricExtractor {
+    pub name: String,
+
+    /// 从消息中提取数值的正则，只能有一个捕获组
+    pub pattern: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub unit: String,
+}
+
+enum Kind {
+    Builtin(Metric),
+    Regex(Regex),
+}
+
+/// 从日志消息中提取一个数值指标
+pub struct Extractor {
+    name: String,
+    unit: String,
+    kind: Kind,
+}
+
+impl Extractor {
+    fn builtin(name: &str, metric: Metric) -> Self {
+        Extractor {
+            name: name.to_string(),
+            unit: "%".to_string(),
+            kind: Kind::Builtin(metric),
+        }
+    }
+
+    fn compile(config: &MetricExtractor) -> Result<Self> {
+        let regex = Regex::new(&config.pattern)
+            .map_err(|e| anyhow!("❌ invalid pattern for metric `{}`: {e}", config.name))?;
+        if regex.captures_len() != 2 {
+            bail!(
+                "❌ pattern for metric `{}` should have exactly one capture group",
+                config.name
+            );
+        }
+
+        Ok(Extractor {
+            name: config.name.clone(),
+            unit: config.unit.clone(),
+            kind: Kind::Regex(regex),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    pub fn extract(&self, message: &str) -> Option<f64> {
+        match &self.kind {
+            Kind::Builtin(metric) => parse_percent(message, metric.key()),
+            Kind::Regex(regex) => regex.captures(message)?.get(1)?.as_str().parse().ok(),
+        }
+    }
+}
+
+/// 按名称查找指标：配置中的同名指标优先，其次为内置的 cpu / mem
+pub fn metric_extractor(ctx: &AppContext, name: &str) -> Result<Extractor> {
+    let configured = ctx.metric_extractors()?;
+    if let Some(config) = configured.iter().find(|m| m.name == name) {
+        return Extractor::compile(config);
+    }
+
+    match name {
+        "cpu" => Ok(Extractor::builtin(name, Metric::Cpu)),
+        "mem" => Ok(Extractor::builtin(name, Metric::Mem)),
+        _ => {
+            let names = ["cpu", "mem"]
+                .into_iter()
+                .chain(configured.iter().map(|m| m.name.as_str()))
+                .collect::<Vec<_>>();
+            bail!(
+                "❌ metric `{name}` not found, available: {}",
+                names.join(", ")
+            )
+        }
+    }
+}
+
+pub fn metric_extractors(ctx: &AppContext, names: &[String]) -> Result<Vec<Extractor>> {
+    names
+        .iter()
+        .map(|name| metric_extractor(ctx, name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extractor() {
+        let latency = Extractor::compile(&MetricExtractor {
+            name: "latency".to_string(),
+            pattern: r"cost: (\d+(?:\.\d+)?)ms".to_string(),
+            unit: "ms".to_string(),
+        })
+        .unwrap();
+        assert_eq!(latency.extract("request done, cost: 35.5ms"), Some(35.5));
+        assert_eq!(latency.extract("request done"), None);
+        assert_eq!(latency.unit(), "ms");
+
+        let cpu = Extractor::builtin("cpu", Metric::Cpu);
+        assert_eq!(
+            cpu.extract("cpu usage: 5.83%, memory usage: 0.35%"),
+            Some(5.83)
+        );
+
+        let no_group = MetricExtractor {
+            name: "bad".to_string(),
+            pattern: r"cost: \d+ms".to_string(),
+            unit: String::new(),
+        };
+        assert!(Extractor::compile(&no_group).is_err());
+
+        let ctx = AppContext::new("/nonexistent/config.json");
+        assert_eq!(metric_extractor(&ctx, "mem").unwrap().name(), "mem");
+        assert!(metric_extractor(&ctx, "gpu").is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand, builder::PossibleValuesParser};
+use regex::Regex;
+
+use crate::{compress::open_log, config::FILTER_COMMANDS, context::AppContext, matcher::MatchArgs};
+
+#[derive(Parser)]
+pub struct FiltersArgs {
+    #[command(subcommand)]
+    pub command: FiltersCommand,
+}
+
+#[derive(Subcommand)]
+pub enum FiltersCommand {
+    /// 检查关键字中重复、被其他关键字覆盖、不会命中的项
+    Lint(LintArgs),
+}
+
+#[derive(Parser)]
+pub struct LintArgs {
+    #[command(flatten)]
+    pub matching: MatchArgs,
+
+    /// 未指定 `-f` 时检查哪个命令的默认关键字
+    #[arg(long, default_value = "cl", value_parser = PossibleValuesParser::new(FILTER_COMMANDS))]
+    pub command: String,
+
+    /// 样本日志，额外报告在其中没有命中任何行的关键字
+    #[arg(long)]
+    pub sample: Option<PathBuf>,
+}
+
+/// `^` 前面必须消耗字符、或 `$` 后面还必须消耗字符时，正则永远不会命中；
+/// 只识别明显的情况，如 `error$x`、`a^b`，多行模式 `(?m)` 下不检查；开头的 `(?i)` 等标志组不消耗字符
+fn misplaced_anchor(pattern: &str) -> bool {
+    if pattern.contains("(?m") {
+        return false;
+    }
+
+    let mut pattern = pattern;
+    while let Some(rest) = pattern.strip_prefix("(?") {
+        let Some(end) = rest.find([')', ':']) else {
+            break;
+        };
+        if !rest[..end]
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || c == '-')
+        {
+            break;
+        }
+        pattern = &rest[end + 1..];
+    }
+
+    let chars = pattern.chars().collect::<Vec<_>>();
+    let mut escaped = false;
+    let mut in_class = false;
+    for (i, &c) in chars.iter().enumerate() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            _ if in_class => {}
+            '^' if i > 0 => {
+                let escaped_prev = i >= 2 && chars[i - 2] == '\\';
+                if escaped_prev || !"(|:^*?}".contains(chars[i - 1]) {
+                    return true;
+                }
+            }
+            '$' => {
+                if let Some(&next) = chars.get(i + 1)
+                    && !")|$*?{".contains(next)
+                {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// 检查关键字集合，返回发现的问题
+fn lint(filters: &[String], regex: bool) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    for (i, filter) in filters.iter().enumerate() {
+        if filters[..i].contains(filter) {
+            issues.push(format!("`{filter}` is listed more than once"));
+            continue;
+        }
+        if filter.is_empty() {
+            issues.push("empty filter matches every line".to_string());
+            continue;
+        }
+
+        if regex {
+            match Regex::new(filter) {
+                Err(e) => issues.push(format!("`{filter}` is not a valid regex: {e}")),
+                Ok(re) if re.is_match("") => issues.push(format!(
+                    "`{filter}` matches the empty string, so every line"
+                )),
+                Ok(_) if misplaced_anchor(filter) => issues.push(format!(
+                    "`{filter}` can never match, check the `^`/`$` anchors"
+                )),
+                Ok(_) => {}
+            }
+        } else if let Some(shorter) = filters
+            .iter()
+            .find(|other| !other.is_empty() && *other != filter && filter.contains(other.as_str()))
+        {
+            issues.push(format!(
+                "`{filter}` is redundant, every line containing it also contains `{shorter}`"
+            ));
+        }
+    }
+
+    issues
+}
+
+fn lint_sample(args: &LintArgs, filters: &[String], sample: &Path) -> Result<Vec<String>> {
+    let matcher = args.matching.matcher(filters)?;
+    let mut counts = vec![0; filters.len()];
+    for line in open_log(sample)?.lines() {
+        for i in matcher.matched_filters(&line?) {
+            counts[i] += 1;
+        }
+    }
+
+    Ok(filters
+        .iter()
+        .zip(counts)
+        .filter(|(_, count)| *count == 0)
+        .map(|(filter, _)| format!("`{filter}` matched no lines in {}", sample.display()))
+        .collect())
+}
+
+pub fn process_filters(ctx: &AppContext, args: FiltersArgs) -> Result<()> {
+    match args.command {
+        FiltersCommand::Lint(args) => {
+            let filters = args.matching.keywords(ctx, &args.command)?;
+            if filters.is_empty() {
+                bail!("❌ no filters to lint");
+            }
+            println!("filters: {}", filters.join(", "));
+
+            let mut issues = lint(&filters, args.matching.regex);
+            if let Some(sample) = &args.sample {
+                let sample = ctx.resolve_path(sample.clone())?;
+                // 正则无效时已在上面报告，跳过样本检查
+                match lint_sample(&args, &filters, &sample) {
+                    Ok(sample_issues) => issues.extend(sample_issues),
+                    Err(e) => eprintln!("❌ sample check skipped, reason: {}", e),
+                }
+            }
+
+            for issue in &issues {
+                println!("⚠ {}", issue);
+            }
+            println!("issues: {}", issues.len());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_lint() {
+        let issues = lint(&filters(&["tid:", "pid:", "tid: 12", "pid:"]), false);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].starts_with("`tid: 12` is redundant"));
+        assert!(issues[1].starts_with("`pid:` is listed more than once"));
+
+        let issues = lint(
+            &filters(&[r"tid: \d{4,}", "a|", "(unclosed", "error$x"]),
+            true,
+        );
+        assert_eq!(issues.len(), 3);
+        assert!(issues[0].contains("empty string"));
+        assert!(issues[1].contains("not a valid regex"));
+        assert!(issues[2].contains("never match"));
+
+        assert!(misplaced_anchor("a^b"));
+        assert!(misplaced_anchor("error$x"));
+        assert!(!misplaced_anchor("^error|^warn$"));
+        assert!(!misplaced_anchor(r"cost \$5"));
+        assert!(!misplaced_anchor("[^a]b"));
+        assert!(!misplaced_anchor("(?m)a$\n^b"));
+        assert!(!misplaced_anchor("(?i)^error"));
+        assert!(!misplaced_anchor("(?i-s)(?x)^error"));
+        assert!(!misplaced_anchor("(?i:^error)"));
+        assert!(misplaced_anchor("(?i)a^b"));
+        assert!(misplaced_anchor("(a)^b"));
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    fs::{self, File, Metadata},
+    io::{BufRead, BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use notify::{RecursiveMode, Watcher};
+
+use crate::{
+    compress::open_log,
+    context::AppContext,
+    matcher::{MatchArgs, Matcher},
+};
+
+/// 没有收到文件事件时的轮询间隔，兼容不支持 inotify 的文件系统 (如网络共享)
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Parser)]
+pub struct FollowArgs {
+    /// 日志文件路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub matching: MatchArgs,
+
+    /// 启动时先输出最近 N 个轮转文件 (如 app.log.1、app.log.2.gz) 和当前文件已有的内容，
+    /// 不指定时只输出启动之后新写入的行
+    #[arg(long, value_name = "N")]
+    pub backfill: Option<usize>,
+}
+
+/// 轮转序号，`app.log.2` 与 `app.log.2.gz` 均为 2，不是 `path` 的轮转文件时为 `None`
+fn rotation_index(path: &Path, candidate: &Path) -> Option<u32> {
+    let name = path.file_name()?.to_str()?;
+    let candidate = candidate.file_name()?.to_str()?;
+    let suffix = candidate.strip_prefix(name)?.strip_prefix('.')?;
+    let index = suffix.strip_suffix(".gz").unwrap_or(suffix);
+    if index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    index.parse().ok()
+}
+
+/// 日志文件所在目录，相对路径的文件名为当前目录
+fn log_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// 最近的 `count` 个轮转文件，按从旧到新排列 (序号越大越旧)
+fn rotated_predecessors(path: &Path, count: usize) -> Result<Vec<PathBuf>> {
+    let mut rotated = fs::read_dir(log_dir(path))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter_map(|p| rotation_index(path, &p).map(|index| (index, p)))
+        .collect::<Vec<_>>();
+    rotated.sort_by_key(|(index, _)| *index);
+    rotated.truncate(count);
+
+    Ok(rotated.into_iter().rev().map(|(_, p)| p).collect())
+}
+
+/// 文件的标识，用于判断路径是否已指向轮转后新建的文件
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(meta: &Metadata) -> Option<u64> {
+    meta.created()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+}
+
+/// 跟踪一个日志文件，轮转 (路径指向新文件) 后先读完旧文件剩余内容再从头读新文件
+struct Follower<'a> {
+    path: &'a Path,
+    matcher: &'a Matcher,
+    reader: Option<BufReader<File>>,
+    id: Option<u64>,
+    /// 还没有写完的最后一行
+    partial: String,
+}
+
+impl<'a> Follower<'a> {
+    fn new(path: &'a Path, matcher: &'a Matcher) -> Self {
+        Follower {
+            path,
+            matcher,
+            reader: None,
+            id: None,
+            partial: String::new(),
+        }
+    }
+
+    /// 打开文件，`from_end` 时跳过已有的内容
+    fn open(&mut self, from_end: bool) -> Result<bool> {
+        let Ok(file) = File::open(self.path) else {
+            return Ok(false);
+        };
+        self.id = file_id(&file.metadata()?);
+        let mut reader = BufReader::new(file);
+        if from_end {
+            reader.seek(SeekFrom::End(0))?;
+        }
+        self.reader = Some(reader);
+        self.partial.clear();
+
+        Ok(true)
+    }
+
+    /// 输出新写入的完整行中命中的行
+    fn read_new_lines(&mut self) -> Result<()> {
+        let Some(reader) = &mut self.reader else {
+            return Ok(());
+        };
+
+        while reader.read_line(&mut self.partial)? > 0 {
+            if !self.partial.ends_with('\n') {
+                break;
+            }
+            let line = self.partial.trim_end_matches(['\n', '\r']);
+            if self.matcher.is_match(line) {
+                println!("{}", line);
+            }
+            self.partial.clear();
+        }
+
+        Ok(())
+    }
+
+    /// 读取新写入的行并处理轮转与截断 (如 `> app.log` 或 copytruncate)
+    fn poll(&mut self) -> Result<()> {
+        self.read_new_lines()?;
+
+        let meta = fs::metadata(self.path).ok();
+        let current = meta.as_ref().and_then(file_id);
+        if self.reader.is_none() || (current.is_some() && current != self.id) {
+            if self.reader.is_some() {
+                let line = self.partial.trim_end_matches('\r');
+                if !line.is_empty() && self.matcher.is_match(line) {
+                    println!("{}", line);
+                }
+                println!("==> {} rotated <==", self.path.display());
+            }
+            if self.open(false)? {
+                self.read_new_lines()?;
+            }
+        } else if let (Some(reader), Some(meta)) = (&mut self.reader, meta)
+            && meta.len() < reader.stream_position()?
+        {
+            println!("==> {} truncated <==", self.path.display());
+            reader.seek(SeekFrom::Start(0))?;
+            self.partial.clear();
+            self.read_new_lines()?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn process_follow(ctx: &AppContext, args: FollowArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    let filters = args.matching.keywords(ctx, "cl")?;
+    let matcher = args.matching.matcher(&filters)?;
+
+    follow_file(&path, &matcher, args.backfill)
+}
+
+/// 持续输出 `path` 新写入的命中行，直到进程被中断；`backfill` 为启动时回填的轮转文件数
+pub fn follow_file(path: &Path, matcher: &Matcher, backfill: Option<usize>) -> Result<()> {
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+
+    if let Some(count) = backfill {
+        for rotated in rotated_predecessors(path, count)? {
+            println!("==> {} <==", rotated.display());
+            for line in open_log(&rotated)?.lines() {
+                let line = line?;
+                if matcher.is_match(&line) {
+                    println!("{}", line);
+                }
+            }
+        }
+        println!("==> {} <==", path.display());
+    }
+
+    let mut follower = Follower::new(path, matcher);
+    follower.open(backfill.is_none())?;
+    follower.read_new_lines()?;
+
+    // 监听所在目录而不是文件本身，轮转时重命名/新建文件也能收到事件
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    let dir = log_dir(path);
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+    loop {
+        match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(Err(e)) => eprintln!("❌ watch failed, path {:?}, reason: {}", dir, e),
+            Ok(Ok(_)) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => bail!("❌ watcher stopped"),
+        }
+        follower.poll()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_index() {
+        let path = Path::new("/var/log/app.log");
+        assert_eq!(
+            rotation_index(path, Path::new("/var/log/app.log.1")),
+            Some(1)
+        );
+        assert_eq!(
+            rotation_index(path, Path::new("/var/log/app.log.12.gz")),
+            Some(12)
+        );
+        assert_eq!(rotation_index(path, Path::new("/var/log/app.log")), None);
+        assert_eq!(rotation_index(path, Path::new("/var/log/app.log.gz")), None);
+        assert_eq!(
+            rotation_index(path, Path::new("/var/log/app.log.bak")),
+            None
+        );
+        assert_eq!(rotation_index(path, Path::new("/var/log/app.log1")), None);
+    }
+}
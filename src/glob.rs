@@ -0,0 +1,110 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Result, anyhow, bail};
+use globset::{GlobBuilder, GlobMatcher};
+use walkdir::DirEntry;
+
+use crate::entries::WalkOptions;
+
+/// 含 glob 通配符的路径分量
+fn has_wildcard(s: &str) -> bool {
+    s.contains(['*', '?', '[', '{'])
+}
+
+/// `--path` 中含通配符时按 glob 处理，如 `logs/**/server_*.log`
+pub fn is_glob(path: &Path) -> bool {
+    !path.exists() && path.to_str().is_some_and(has_wildcard)
+}
+
+/// glob 中第一个含通配符的分量之前的目录，作为遍历的起点；其余部分为相对它的模式
+fn split_glob(path: &Path) -> (PathBuf, String) {
+    let mut root = PathBuf::new();
+    let mut components = path.components();
+    for component in components.by_ref() {
+        if let Component::Normal(name) = component
+            && name.to_str().is_some_and(has_wildcard)
+        {
+            let rest = std::iter::once(component)
+                .chain(components)
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            return (root, rest);
+        }
+        root.push(component);
+    }
+
+    (root, String::new())
+}
+
+/// glob 遍历的起点目录，加锁等需要实际路径的操作使用
+pub fn glob_root(path: &Path) -> PathBuf {
+    split_glob(path).0
+}
+
+fn compile(pattern: &str) -> Result<GlobMatcher> {
+    // 与 shell 一致，`*` 不跨目录，`**` 匹配任意层目录
+    let glob = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| anyhow!("❌ invalid glob `{pattern}`: {e}"))?;
+
+    Ok(glob.compile_matcher())
+}
+
+/// glob 匹配的所有文件
+pub fn glob_files(path: &Path, walk: WalkOptions) -> Result<Vec<DirEntry>> {
+    let (root, pattern) = split_glob(path);
+    let matcher = compile(&pattern)?;
+
+    Ok(walk
+        .walk(&root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            e.path()
+                .strip_prefix(&root)
+                .is_ok_and(|rel| matcher.is_match(rel))
+        })
+        .collect())
+}
+
+/// 检查 `--path` 存在，glob 时检查其合法且至少匹配一个文件
+pub fn check_target(path: &Path) -> Result<()> {
+    if is_glob(path) {
+        if glob_files(path, WalkOptions::default())?.is_empty() {
+            bail!("❌ no files match {}", path.display());
+        }
+        return Ok(());
+    }
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_glob() {
+        assert_eq!(
+            split_glob(Path::new("/var/log/app/**/server_*.log")),
+            (PathBuf::from("/var/log/app"), "**/server_*.log".to_string())
+        );
+        assert_eq!(
+            split_glob(Path::new("logs/node?/a.log")),
+            (PathBuf::from("logs"), "node?/a.log".to_string())
+        );
+        assert!(!is_glob(Path::new("/var/log/app/a.log")));
+        assert!(is_glob(Path::new("/nonexistent/*.log")));
+
+        let matcher = compile("**/server_*.log").unwrap();
+        assert!(matcher.is_match("server_1.log"));
+        assert!(matcher.is_match("a/b/server_1.log"));
+        assert!(!matcher.is_match("a/client_1.log"));
+    }
+}
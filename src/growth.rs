@@ -0,0 +1,226 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::BufRead,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::{Result, bail};
+use clap::{Parser, ValueEnum};
+use rayon::prelude::*;
+
+use crate::{
+    compress::open_log,
+    context::AppContext,
+    diff_dir::component,
+    exit::Failures,
+    out_name::OverwriteArgs,
+    subcommand::get_entries,
+    table::{print_table, write_table},
+    time::format_timestamp,
+    timestamp::{TimestampFormat, Timestamps},
+    units::format_size,
+};
+
+const DAY_MS: i64 = 86_400_000;
+
+/// 统计的时间粒度
+#[derive(Clone, Copy, ValueEnum)]
+pub enum GroupBy {
+    Day,
+    /// 按周一开始的自然周
+    Week,
+}
+
+impl GroupBy {
+    /// 时间所在周期的起始日 (自 1970-01-01 起的天数)
+    fn period(self, ms: i64) -> i64 {
+        let day = ms.div_euclid(DAY_MS);
+        match self {
+            GroupBy::Day => day,
+            // 1970-01-01 是周四
+            GroupBy::Week => day - (day + 3).rem_euclid(7),
+        }
+    }
+}
+
+#[derive(Parser)]
+pub struct GrowthArgs {
+    /// 文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 统计粒度
+    #[arg(short, long, value_enum, default_value = "day")]
+    pub group_by: GroupBy,
+
+    /// 输出 csv 或 xlsx 文件 (按扩展名)，默认打印表格
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub overwrite: OverwriteArgs,
+}
+
+/// 一个周期内产生的日志量
+#[derive(Clone, Copy, Default)]
+struct Volume {
+    lines: u64,
+    bytes: u64,
+}
+
+impl Volume {
+    fn add(&mut self, other: Volume) {
+        self.lines += other.lines;
+        self.bytes += other.bytes;
+    }
+}
+
+/// 按行首时间戳把每行计入所在周期，没有时间戳的行计入前一行的周期；
+/// 文件开头尚未出现时间戳的行计入文件修改时间所在的周期；字节数按解压后计算
+fn file_growth(
+    path: &Path,
+    group_by: GroupBy,
+    format: TimestampFormat,
+) -> Result<BTreeMap<i64, Volume>> {
+    let modified = path.metadata()?.modified()?;
+    let mtime = modified.duration_since(UNIX_EPOCH)?.as_millis() as i64;
+
+    let mut periods = BTreeMap::<i64, Volume>::new();
+    let mut pending = Volume::default();
+    let mut current = None;
+    for line in open_log(path)?.lines() {
+        let line = line?;
+        if let Some(time) = format.parse(&line) {
+            current = Some(group_by.period(time));
+        }
+        let volume = Volume {
+            lines: 1,
+            bytes: line.len() as u64 + 1,
+        };
+        match current {
+            Some(period) => periods.entry(period).or_default().add(volume),
+            None => pending.add(volume),
+        }
+    }
+    if pending.lines > 0 {
+        periods
+            .entry(group_by.period(mtime))
+            .or_default()
+            .add(pending);
+    }
+
+    Ok(periods)
+}
+
+pub fn process_growth(ctx: &AppContext, args: GrowthArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.is_dir() {
+        bail!("❌ {} is not a directory", path.display());
+    }
+
+    let timestamps = Timestamps::load(ctx)?;
+    let files = get_entries(&path)
+        .into_iter()
+        .map(|e| e.into_path())
+        .collect::<Vec<_>>();
+    let failures = Failures::new(ctx);
+    let results = files
+        .par_iter()
+        .filter_map(|file| {
+            if failures.should_stop() {
+                return None;
+            }
+            file_growth(file, args.group_by, timestamps.format_for(file))
+                .inspect_err(|e| {
+                    eprintln!("❌ growth failed, path {:?}, reason: {}", file, e);
+                    failures.record(file, e);
+                })
+                .ok()
+                .map(|periods| (component(file.strip_prefix(&path).unwrap_or(file)), periods))
+        })
+        .collect::<Vec<_>>();
+
+    let mut volumes = BTreeMap::<(i64, String), Volume>::new();
+    for (component, periods) in results {
+        for (period, volume) in periods {
+            volumes
+                .entry((period, component.clone()))
+                .or_default()
+                .add(volume);
+        }
+    }
+    if volumes.is_empty() {
+        println!("no log lines");
+        return failures.finish();
+    }
+
+    let headers = ["period", "component", "lines", "bytes"];
+    let period_label = |period: i64| format_timestamp(period * DAY_MS)[..10].to_string();
+    match args.output {
+        Some(output) => {
+            let output = args.overwrite.claim(&ctx.resolve_path(output)?)?;
+            let rows = volumes
+                .iter()
+                .map(|((period, component), volume)| {
+                    vec![
+                        period_label(*period),
+                        component.clone(),
+                        volume.lines.to_string(),
+                        volume.bytes.to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            write_table(&output, &headers, &rows)?;
+            println!("write growth report, path: {:?}", output.display());
+        }
+        None => {
+            let rows = volumes
+                .iter()
+                .map(|((period, component), volume)| {
+                    vec![
+                        period_label(*period),
+                        component.clone(),
+                        volume.lines.to_string(),
+                        format_size(volume.bytes),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            print_table(&headers, &rows);
+
+            let mut total = Volume::default();
+            volumes.values().for_each(|v| total.add(*v));
+            let periods = volumes
+                .keys()
+                .map(|(period, _)| period)
+                .collect::<BTreeSet<_>>()
+                .len();
+            println!(
+                "\ntotal: {} lines, {} over {} periods, average {} per period",
+                total.lines,
+                format_size(total.bytes),
+                periods,
+                format_size(total.bytes / periods as u64)
+            );
+        }
+    }
+
+    failures.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::parse_timestamp;
+
+    #[test]
+    fn test_group_by() {
+        // 2026-01-07 是周三，所在周从 2026-01-05 开始
+        let ms = parse_timestamp("2026-01-07 10:29:10.765").unwrap();
+        let label = |period: i64| format_timestamp(period * DAY_MS)[..10].to_string();
+        assert_eq!(label(GroupBy::Day.period(ms)), "2026-01-07");
+        assert_eq!(label(GroupBy::Week.period(ms)), "2026-01-05");
+        let monday = parse_timestamp("2026-01-05 00:00:00").unwrap();
+        assert_eq!(label(GroupBy::Week.period(monday)), "2026-01-05");
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    io::BufRead,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    compress::open_log,
+    context::AppContext,
+    exit::Failures,
+    subcommand::get_entries,
+    table::print_table,
+    time::{format_duration_ms, format_timestamp, parse_duration},
+    timestamp::{TimestampFormat, Timestamps},
+    units::parse_fraction,
+};
+
+#[derive(Parser)]
+pub struct HeartbeatArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 周期性状态行中的关键字，如 `cpu usage`
+    #[arg(long)]
+    pub pattern: String,
+
+    /// 状态行应出现的间隔，如 `60s`
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub expected_every: Duration,
+
+    /// 允许的延迟，为间隔的比例，如 `0.5` 或 `50%`：间隔超过 1.5 倍时记为漏报
+    #[arg(long, default_value = "0.5", value_parser = parse_fraction)]
+    pub tolerance: f64,
+
+    /// 以 json 格式输出
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+}
+
+/// 一段没有状态行的时间，`next` 为空时表示直到文件中最后一行都没有再出现
+#[derive(Serialize)]
+struct Gap {
+    /// 上一次状态行的时间，文件开头就缺失时为第一行的时间
+    since: i64,
+    next: Option<i64>,
+    /// 到 `next` (或最后一行) 的毫秒数
+    gap: i64,
+    /// 漏掉的状态行数
+    missed: i64,
+}
+
+#[derive(Serialize)]
+struct FileHeartbeats {
+    path: PathBuf,
+    heartbeats: usize,
+    gaps: Vec<Gap>,
+}
+
+/// 找出状态行之间超过 `expected * (1 + tolerance)` 的间隔，含文件开头到第一次、最后一次到文件末尾；
+/// 没有时间戳的行不参与判断
+fn find_gaps<I: Iterator<Item = Result<String>>>(
+    lines: I,
+    pattern: &str,
+    format: TimestampFormat,
+    expected: i64,
+    tolerance: f64,
+) -> Result<(usize, Vec<Gap>)> {
+    let limit = (expected as f64 * (1.0 + tolerance)) as i64;
+    let gap = |since: i64, until: i64, next: Option<i64>| {
+        let gap = until - since;
+        // 两次状态行之间按最接近的整数倍估算漏掉的次数；末尾的缺失按已经过去的完整间隔数计算
+        let missed = match next {
+            Some(_) => (gap as f64 / expected as f64).round() as i64 - 1,
+            None => gap / expected,
+        };
+        (gap > limit).then(|| Gap {
+            since,
+            next,
+            gap,
+            missed: missed.max(1),
+        })
+    };
+
+    let mut gaps = Vec::new();
+    let mut heartbeats = 0;
+    let mut first = None;
+    let mut last_line = None;
+    let mut last_beat = None;
+    for line in lines {
+        let line = line?;
+        let Some(time) = format.parse(&line) else {
+            continue;
+        };
+        first.get_or_insert(time);
+        last_line = Some(time);
+        if !line.contains(pattern) {
+            continue;
+        }
+
+        heartbeats += 1;
+        let since = last_beat.or(first).unwrap();
+        gaps.extend(gap(since, time, Some(time)));
+        last_beat = Some(time);
+    }
+    if let (Some(since), Some(until)) = (last_beat.or(first), last_line) {
+        gaps.extend(gap(since, until, None));
+    }
+
+    Ok((heartbeats, gaps))
+}
+
+fn file_heartbeats(
+    path: &Path,
+    args: &HeartbeatArgs,
+    format: TimestampFormat,
+) -> Result<(usize, Vec<Gap>)> {
+    let lines = open_log(path)?.lines().map(|line| Ok(line?));
+    find_gaps(
+        lines,
+        &args.pattern,
+        format,
+        args.expected_every.as_millis() as i64,
+        args.tolerance,
+    )
+}
+
+pub fn process_heartbeat(ctx: &AppContext, args: HeartbeatArgs) -> Result<()> {
+    if args.pattern.is_empty() {
+        bail!("❌ pattern should not be empty");
+    }
+    if args.expected_every.as_millis() == 0 {
+        bail!("❌ --expected-every should be greater than 0");
+    }
+    let path = ctx.resolve_path(args.path.clone())?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    let mut files = if path.is_dir() {
+        get_entries(&path)
+            .into_iter()
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        vec![path]
+    };
+    files.sort();
+    let timestamps = Timestamps::load(ctx)?;
+
+    let failures = Failures::new(ctx);
+    let reports = files
+        .into_par_iter()
+        .filter_map(|file| {
+            if failures.should_stop() {
+                return None;
+            }
+            file_heartbeats(&file, &args, timestamps.format_for(&file))
+                .inspect_err(|e| {
+                    eprintln!("❌ heartbeat failed, path {:?}, reason: {}", file, e);
+                    failures.record(&file, e);
+                })
+                .ok()
+                .map(|(heartbeats, gaps)| FileHeartbeats {
+                    path: file,
+                    heartbeats,
+                    gaps,
+                })
+        })
+        .collect::<Vec<_>>();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return failures.finish();
+    }
+
+    for report in &reports {
+        if report.heartbeats == 0 {
+            println!(
+                "⚠️ {}: no `{}` lines found",
+                report.path.display(),
+                args.pattern
+            );
+            continue;
+        }
+        if report.gaps.is_empty() {
+            println!(
+                "{}: heartbeats: {}, no missed intervals",
+                report.path.display(),
+                report.heartbeats
+            );
+            continue;
+        }
+
+        println!(
+            "⚠️ {}: heartbeats: {}, missed intervals: {}",
+            report.path.display(),
+            report.heartbeats,
+            report.gaps.iter().map(|g| g.missed).sum::<i64>()
+        );
+        let rows = report
+            .gaps
+            .iter()
+            .map(|g| {
+                vec![
+                    format_timestamp(g.since),
+                    g.next
+                        .map_or_else(|| "(end of file)".to_string(), format_timestamp),
+                    format_duration_ms(g.gap),
+                    g.missed.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(&["since", "next", "gap", "missed"], &rows);
+        println!();
+    }
+    println!(
+        "files with missed heartbeats: {}, gaps: {}",
+        reports
+            .iter()
+            .filter(|r| r.heartbeats == 0 || !r.gaps.is_empty())
+            .count(),
+        reports.iter().map(|r| r.gaps.len()).sum::<usize>()
+    );
+
+    failures.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_gaps() {
+        let lines = [
+            "[2026-01-06 10:00:00.000] [info] [Global]  started",
+            "[2026-01-06 10:00:30.000] [info] [Global]  cpu usage: 5%",
+            "[2026-01-06 10:01:40.000] [info] [Global]  cpu usage: 6%",
+            "    continuation",
+            "[2026-01-06 10:04:40.000] [info] [Global]  cpu usage: 7%",
+            "[2026-01-06 10:05:30.000] [info] [Net]  request ok",
+            "[2026-01-06 10:07:00.000] [info] [Net]  request ok",
+        ];
+        let lines = || lines.iter().map(|l| Ok(l.to_string()));
+
+        let (heartbeats, gaps) =
+            find_gaps(lines(), "cpu usage", TimestampFormat::Bracket, 60_000, 0.5).unwrap();
+        assert_eq!(heartbeats, 3);
+        // 70s 在容忍范围内；3 分钟漏掉 2 次；最后一次之后 2 分 20 秒没有再出现
+        assert_eq!(gaps.len(), 2);
+        assert_eq!((gaps[0].gap, gaps[0].missed), (180_000, 2));
+        assert!(gaps[0].next.is_some());
+        assert_eq!((gaps[1].gap, gaps[1].missed), (140_000, 2));
+        assert!(gaps[1].next.is_none());
+
+        let (heartbeats, gaps) =
+            find_gaps(lines(), "tid:", TimestampFormat::Bracket, 60_000, 0.5).unwrap();
+        assert_eq!(heartbeats, 0);
+        assert_eq!((gaps.len(), gaps[0].missed), (1, 7));
+    }
+}
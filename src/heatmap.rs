@@ -0,0 +1,243 @@
+use std::{
+    collections::BTreeMap,
+    io::BufRead,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Result, bail};
+use clap::{Parser, ValueEnum};
+use rayon::prelude::*;
+use rust_xlsxwriter::{Color, ConditionalFormat2ColorScale, Workbook};
+
+use crate::{
+    compress::open_log,
+    context::AppContext,
+    exit::Failures,
+    matcher::{MatchArgs, Matcher},
+    out_name::OverwriteArgs,
+    subcommand::get_entries,
+    table::{print_table, write_table},
+    time::{format_timestamp, parse_duration},
+    timestamp::{TimestampFormat, Timestamps},
+};
+
+/// xlsx 单个工作表的最大列数
+const XLSX_MAX_COLS: usize = 16_384;
+
+/// 时间桶数的上限，各格式相同 (xlsx 还要留出文件名列)，避免个别异常时间戳导致按整个跨度分配
+const MAX_BUCKETS: i64 = XLSX_MAX_COLS as i64 - 1;
+
+/// 热力图输出格式
+#[derive(Clone, Copy, ValueEnum)]
+pub enum HeatmapFormat {
+    Text,
+    Xlsx,
+    Csv,
+}
+
+#[derive(Parser)]
+pub struct HeatmapArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub matching: MatchArgs,
+
+    /// 时间桶大小，如 10m、1h
+    #[arg(short, long, default_value = "10m", value_parser = parse_duration)]
+    pub bucket: Duration,
+
+    /// 输出格式，xlsx 按命中数着色
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: HeatmapFormat,
+
+    /// xlsx/csv 的输出路径，默认为当前目录下的 heatmap.xlsx / heatmap.csv
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub overwrite: OverwriteArgs,
+}
+
+/// 统计每个时间桶内命中的行数，没有时间戳的续行计入它前面的行所在的桶
+fn bucket_counts(
+    path: &Path,
+    matcher: &Matcher,
+    bucket_ms: i64,
+    format: TimestampFormat,
+) -> Result<BTreeMap<i64, usize>> {
+    let mut counts = BTreeMap::new();
+    let mut last_time = None;
+    for line in open_log(path)?.lines() {
+        let line = line?;
+        if let Some(time) = format.parse(&line) {
+            last_time = Some(time);
+        }
+
+        if let Some(time) = last_time
+            && matcher.is_match(&line)
+        {
+            *counts.entry(time.div_euclid(bucket_ms)).or_insert(0) += 1;
+        }
+    }
+
+    Ok(counts)
+}
+
+/// 桶的起始时间，精确到分钟
+fn bucket_label(bucket: i64, bucket_ms: i64) -> String {
+    let mut label = format_timestamp(bucket * bucket_ms);
+    label.truncate(16);
+    label
+}
+
+pub fn process_heatmap(ctx: &AppContext, args: HeatmapArgs) -> Result<()> {
+    let bucket_ms = args.bucket.as_millis() as i64;
+    if bucket_ms <= 0 {
+        bail!("❌ bucket should be at least 1ms");
+    }
+
+    let path = ctx.resolve_path(args.path)?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    let filters = args.matching.keywords(ctx, "cl")?;
+    let matcher = args.matching.matcher(&filters)?;
+
+    let files = if path.is_dir() {
+        get_entries(&path)
+            .into_iter()
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        vec![path.clone()]
+    };
+
+    let timestamps = Timestamps::load(ctx)?;
+    let failures = Failures::new(ctx);
+    let mut files = files
+        .par_iter()
+        .filter_map(|file| {
+            if failures.should_stop() {
+                return None;
+            }
+            bucket_counts(file, &matcher, bucket_ms, timestamps.format_for(file))
+                .inspect_err(|e| {
+                    eprintln!("❌ heatmap failed, path {:?}, reason: {}", file, e);
+                    failures.record(file, e);
+                })
+                .ok()
+                .map(|counts| {
+                    let display = file.strip_prefix(&path).unwrap_or(file);
+                    let display = if display.as_os_str().is_empty() {
+                        file.display().to_string()
+                    } else {
+                        display.display().to_string()
+                    };
+                    (display, counts)
+                })
+        })
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let first = files.iter().filter_map(|(_, c)| c.keys().next()).min();
+    let last = files.iter().filter_map(|(_, c)| c.keys().next_back()).max();
+    let (Some(&first), Some(&last)) = (first, last) else {
+        println!("no matching lines");
+        return failures.finish();
+    };
+
+    let count = last.saturating_sub(first).saturating_add(1);
+    if count > MAX_BUCKETS {
+        bail!(
+            "❌ {} to {} spans {count} buckets, more than {MAX_BUCKETS}, use a larger --bucket or limit the time range with --since/--until",
+            bucket_label(first, bucket_ms),
+            bucket_label(last, bucket_ms)
+        );
+    }
+    let buckets = (first..=last).collect::<Vec<_>>();
+    let mut headers = vec!["file".to_string()];
+    headers.extend(buckets.iter().map(|&b| bucket_label(b, bucket_ms)));
+    let rows = files
+        .iter()
+        .map(|(file, counts)| {
+            let mut row = vec![file.clone()];
+            row.extend(
+                buckets
+                    .iter()
+                    .map(|b| counts.get(b).copied().unwrap_or(0).to_string()),
+            );
+            row
+        })
+        .collect::<Vec<_>>();
+    let headers = headers.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let output = |ext: &str| {
+        args.output
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(format!("heatmap.{ext}")))
+    };
+    match args.format {
+        HeatmapFormat::Text => print_table(&headers, &rows),
+        HeatmapFormat::Csv => {
+            let output = args.overwrite.claim(&output("csv"))?;
+            write_table(&output, &headers, &rows)?;
+            println!("write heatmap, path: {:?}", output.display());
+        }
+        HeatmapFormat::Xlsx => {
+            let output = args.overwrite.claim(&output("xlsx"))?;
+            write_heatmap_xlsx(&output, &headers, &rows)?;
+            println!("write heatmap, path: {:?}", output.display());
+        }
+    }
+
+    failures.finish()
+}
+
+/// 写出 xlsx 热力图，命中数按白到红的色阶着色，冻结表头与文件名列
+fn write_heatmap_xlsx(path: &Path, headers: &[&str], rows: &[Vec<String>]) -> Result<()> {
+    let mut wb = Workbook::new();
+    let ws = wb.add_worksheet();
+
+    for (col, header) in headers.iter().enumerate() {
+        ws.write_string(0, col as u16, *header)?;
+    }
+    for (row, cells) in rows.iter().enumerate() {
+        let row = row as u32 + 1;
+        ws.write_string(row, 0, &cells[0])?;
+        for (col, cell) in cells.iter().enumerate().skip(1) {
+            ws.write_number(row, col as u16, cell.parse::<f64>()?)?;
+        }
+    }
+
+    let last_col = headers.len() as u16 - 1;
+    if !rows.is_empty() && last_col > 0 {
+        let scale = ConditionalFormat2ColorScale::new()
+            .set_minimum_color(Color::White)
+            .set_maximum_color(Color::RGB(0xF8696B));
+        ws.add_conditional_format(1, 1, rows.len() as u32, last_col, &scale)?;
+    }
+    ws.set_column_width(0, 40)?;
+    ws.set_freeze_panes(1, 1)?;
+    wb.save(path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::parse_timestamp;
+
+    #[test]
+    fn test_bucket_label() {
+        let bucket_ms = 10 * 60 * 1000;
+        let time = parse_timestamp("2026-01-06 10:29:10.765").unwrap();
+        let bucket = time.div_euclid(bucket_ms);
+        assert_eq!(bucket_label(bucket, bucket_ms), "2026-01-06 10:20");
+        assert_eq!(bucket_label(bucket + 1, bucket_ms), "2026-01-06 10:30");
+    }
+}
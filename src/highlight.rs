@@ -0,0 +1,286 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use regex::Regex;
+
+use crate::{
+    compress::{open_log, plain_path},
+    context::AppContext,
+    matcher::{MatchArgs, fold_variants},
+    subcommand::escape_xml,
+    temp::InFlight,
+};
+
+/// 各关键字的高亮颜色，关键字多于颜色数时循环使用
+const COLORS: [&str; 8] = [
+    "#ffe066", "#8ce99a", "#74c0fc", "#ffa8a8", "#d0bfff", "#ffc078", "#66d9e8", "#faa2c1",
+];
+
+/// 索引中最多列出的命中行数，更多时只给出数量
+const MAX_INDEX: usize = 5000;
+
+#[derive(Parser)]
+pub struct HighlightArgs {
+    /// 日志文件路径，支持 `.gz`
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub matching: MatchArgs,
+
+    /// 输出的 HTML 文件，默认为源文件旁的 xxx.html
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// 用于定位高亮位置的关键字
+enum Pattern {
+    Plain(String),
+    Regex(Regex),
+}
+
+/// 一处高亮：行内字节区间与命中的关键字下标
+#[derive(Debug, PartialEq)]
+struct Span {
+    start: usize,
+    end: usize,
+    filter: usize,
+}
+
+/// 行内所有关键字的命中位置；重叠时保留先开始的，同时开始时保留较长的
+fn find_spans(line: &str, patterns: &[Pattern]) -> Vec<Span> {
+    let mut spans = Vec::new();
+    for (filter, pattern) in patterns.iter().enumerate() {
+        match pattern {
+            Pattern::Plain(s) if !s.is_empty() => {
+                spans.extend(line.match_indices(s.as_str()).map(|(start, s)| Span {
+                    start,
+                    end: start + s.len(),
+                    filter,
+                }))
+            }
+            Pattern::Plain(_) => {}
+            Pattern::Regex(re) => {
+                spans.extend(re.find_iter(line).filter(|m| !m.is_empty()).map(|m| Span {
+                    start: m.start(),
+                    end: m.end(),
+                    filter,
+                }))
+            }
+        }
+    }
+    spans.sort_by_key(|s| (s.start, usize::MAX - s.end, s.filter));
+
+    let mut end = 0;
+    spans.retain(|s| {
+        let keep = s.start >= end;
+        if keep {
+            end = s.end;
+        }
+        keep
+    });
+
+    spans
+}
+
+fn write_line<W: Write>(output: &mut W, line_no: usize, line: &str, spans: &[Span]) -> Result<()> {
+    write!(
+        output,
+        "<div class=\"l\" id=\"L{line_no}\"><a class=\"n\" href=\"#L{line_no}\">{line_no}</a>"
+    )?;
+    let mut pos = 0;
+    for span in spans {
+        write!(
+            output,
+            "{}<mark class=\"k{}\">{}</mark>",
+            escape_xml(&line[pos..span.start]),
+            span.filter,
+            escape_xml(&line[span.start..span.end])
+        )?;
+        pos = span.end;
+    }
+    writeln!(output, "{}</div>", escape_xml(&line[pos..]))?;
+
+    Ok(())
+}
+
+fn write_head<W: Write>(output: &mut W, title: &str, filters: &[String]) -> Result<()> {
+    let title = escape_xml(title);
+    writeln!(
+        output,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>"
+    )?;
+    writeln!(
+        output,
+        "body {{ margin: 0; font: 13px/1.5 monospace; }}
+header {{ position: sticky; top: 0; padding: 6px 12px; background: #fff; border-bottom: 1px solid #ccc; }}
+main {{ margin-right: 240px; padding: 6px 0; }}
+nav {{ position: fixed; top: 0; right: 0; bottom: 0; width: 240px; overflow: auto; padding: 6px; box-sizing: border-box; background: #f8f9fa; border-left: 1px solid #ccc; }}
+nav a {{ display: block; color: inherit; text-decoration: none; }}
+mark {{ color: inherit; }}
+.l {{ white-space: pre-wrap; word-break: break-all; }}
+.l:target {{ background: #e7f5ff; }}
+.n {{ display: inline-block; min-width: 6em; padding-right: 1em; color: #adb5bd; text-align: right; text-decoration: none; }}"
+    )?;
+    for i in 0..filters.len() {
+        writeln!(
+            output,
+            ".k{i} {{ background: {}; }}",
+            COLORS[i % COLORS.len()]
+        )?;
+    }
+    writeln!(output, "</style>\n</head>\n<body>\n<header><b>{title}</b>")?;
+    for (i, filter) in filters.iter().enumerate() {
+        write!(
+            output,
+            " <mark class=\"k{i}\">{}</mark>",
+            escape_xml(filter)
+        )?;
+    }
+    writeln!(output, "</header>\n<main>")?;
+
+    Ok(())
+}
+
+/// 命中行的索引，先给出每个关键字的命中次数
+fn write_index<W: Write>(
+    output: &mut W,
+    filters: &[String],
+    counts: &[usize],
+    index: &[(usize, Vec<usize>)],
+    matched: usize,
+) -> Result<()> {
+    writeln!(output, "</main>\n<nav>\n<b>matches: {matched}</b>")?;
+    for (i, (filter, count)) in filters.iter().zip(counts).enumerate() {
+        writeln!(
+            output,
+            "<div><mark class=\"k{i}\">{}</mark> {count}</div>",
+            escape_xml(filter)
+        )?;
+    }
+    writeln!(output, "<hr>")?;
+    for (line_no, line_filters) in index {
+        write!(output, "<a href=\"#L{line_no}\">")?;
+        for i in line_filters {
+            write!(output, "<mark class=\"k{i}\">&nbsp;</mark>")?;
+        }
+        writeln!(output, " L{line_no}</a>")?;
+    }
+    if matched > index.len() {
+        writeln!(output, "<div>... {} more</div>", matched - index.len())?;
+    }
+    writeln!(output, "</nav>\n</body>\n</html>")?;
+
+    Ok(())
+}
+
+pub fn process_highlight(ctx: &AppContext, args: HighlightArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path.clone())?;
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+    if args.matching.fuzzy.is_some() || args.matching.expr.is_some() {
+        bail!("❌ highlight supports plain and --regex filters only");
+    }
+
+    let filters = args.matching.keywords(ctx, "cl")?;
+    let matcher = args.matching.matcher(&filters)?;
+    let variants = args.matching.variants;
+    let patterns = filters
+        .iter()
+        .map(|filter| {
+            Ok(if args.matching.regex {
+                Pattern::Regex(Regex::new(filter)?)
+            } else if variants {
+                Pattern::Plain(fold_variants(filter))
+            } else {
+                Pattern::Plain(filter.clone())
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let output_path = match args.output {
+        Some(output) => ctx.resolve_path(output)?,
+        None => plain_path(&path).with_extension("html"),
+    };
+    if output_path == path {
+        bail!("❌ output would overwrite input {}", path.display());
+    }
+    let partial = InFlight::register(&output_path);
+    let mut output = BufWriter::new(File::create(&output_path)?);
+
+    write_head(&mut output, &path.display().to_string(), &filters)?;
+    let mut counts = vec![0; filters.len()];
+    let mut index = Vec::new();
+    let mut matched = 0;
+    for (i, line) in open_log(&path)?.lines().enumerate() {
+        let line = line?;
+        let line_no = i + 1;
+        if !matcher.is_match(&line) {
+            write_line(&mut output, line_no, &line, &[])?;
+            continue;
+        }
+
+        // 简繁体归一只替换等长的汉字，字节位置与原行一致
+        let spans = if variants {
+            find_spans(&fold_variants(&line), &patterns)
+        } else {
+            find_spans(&line, &patterns)
+        };
+        write_line(&mut output, line_no, &line, &spans)?;
+
+        let mut line_filters = spans.iter().map(|s| s.filter).collect::<Vec<_>>();
+        line_filters.sort_unstable();
+        line_filters.dedup();
+        for &i in &line_filters {
+            counts[i] += 1;
+        }
+        matched += 1;
+        if index.len() < MAX_INDEX {
+            index.push((line_no, line_filters));
+        }
+    }
+    write_index(&mut output, &filters, &counts, &index, matched)?;
+    output.flush()?;
+    partial.commit();
+
+    println!(
+        "write highlight file, path: {:?}, matched lines: {}",
+        output_path.display(),
+        matched
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_spans() {
+        let patterns = [
+            Pattern::Plain("tid".to_string()),
+            Pattern::Plain("tid: 1".to_string()),
+            Pattern::Regex(Regex::new(r"\d+ms").unwrap()),
+        ];
+        let line = "tid: 12 cost 35ms, tid again";
+        let span = |start, end, filter| Span { start, end, filter };
+        assert_eq!(
+            find_spans(line, &patterns),
+            vec![span(0, 6, 1), span(13, 17, 2), span(19, 22, 0)]
+        );
+
+        let mut output = Vec::new();
+        write_line(&mut output, 3, "a<b> tid", &[span(5, 8, 0)]).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "<div class=\"l\" id=\"L3\"><a class=\"n\" href=\"#L3\">3</a>a&lt;b&gt; <mark class=\"k0\">tid</mark></div>\n"
+        );
+    }
+}
@@ -0,0 +1,459 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::{
+    context::AppContext, matcher::MatchArgs, record::RecordArgs, subcommand::CheckSummary,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_at      INTEGER NOT NULL,
+    command     TEXT NOT NULL,
+    filter_hash TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS results (
+    run_id   INTEGER NOT NULL REFERENCES runs(id),
+    path     TEXT NOT NULL,
+    matches  INTEGER NOT NULL,
+    errors   INTEGER NOT NULL,
+    cpu_peak REAL
+);
+CREATE INDEX IF NOT EXISTS results_run_path ON results (run_id, path);
+CREATE TABLE IF NOT EXISTS commands (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_at      INTEGER NOT NULL,
+    args        TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    result      TEXT NOT NULL
+);
+";
+
+#[derive(Parser)]
+pub struct TrendArgs {
+    /// 只比较该路径下的文件
+    #[arg(short, long)]
+    pub path: Option<PathBuf>,
+
+    /// 与之前多少次运行比较
+    #[arg(short = 'n', long, default_value_t = 5)]
+    pub runs: u32,
+
+    /// 只比较该命令的运行，默认为最近一次运行的命令
+    #[arg(long, value_parser = ["cl", "stats"])]
+    pub command: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct HistoryArgs {
+    /// 显示最近多少条命令
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: u32,
+}
+
+#[derive(Parser)]
+pub struct RerunArgs {
+    /// `lp history` 中的命令编号
+    pub id: i64,
+}
+
+fn open_history(ctx: &AppContext) -> Result<Connection> {
+    let conn = Connection::open(ctx.config_file("history.db")?)?;
+    conn.execute_batch(SCHEMA)?;
+
+    Ok(conn)
+}
+
+/// 关键字集合的稳定哈希 (FNV-1a)，用于区分不同过滤条件下的运行记录
+pub fn filter_hash(filters: &[String], matching: &MatchArgs, records: &RecordArgs) -> String {
+    let mut filters = filters.to_vec();
+    filters.sort();
+
+    // 表达式与记录边界都会改变命中数，同样计入
+    let options = format!(
+        "variants={} fuzzy={:?} regex={} expr={:?} record_separator={:?} entries={} level={:?} module={:?} exclude_module={:?} since={:?} until={:?}",
+        matching.variants,
+        matching.fuzzy,
+        matching.regex,
+        matching.expr,
+        records.record_separator,
+        records.entries,
+        matching.level,
+        matching.module,
+        matching.exclude_module,
+        matching.time_range.since,
+        matching.time_range.until
+    );
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in filters
+        .iter()
+        .flat_map(|s| s.as_bytes().iter().chain(b"\n"))
+        .chain(options.as_bytes())
+    {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    format!("{hash:016x}")
+}
+
+fn unix_now() -> Result<i64> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64)
+}
+
+pub fn record_command(
+    ctx: &AppContext,
+    args: &[String],
+    duration: Duration,
+    result: &Result<()>,
+) -> Result<()> {
+    let result = match result {
+        Ok(()) => "ok".to_string(),
+        Err(e) => format!("error: {e}"),
+    };
+
+    let conn = open_history(ctx)?;
+    conn.execute(
+        "INSERT INTO commands (run_at, args, duration_ms, result) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            unix_now()?,
+            serde_json::to_string(args)?,
+            duration.as_millis() as i64,
+            result
+        ],
+    )?;
+
+    Ok(())
+}
+
+pub fn command_args(ctx: &AppContext, id: i64) -> Result<Vec<String>> {
+    let conn = open_history(ctx)?;
+    let args = conn
+        .query_row(
+            "SELECT args FROM commands WHERE id = ?1",
+            params![id],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()?
+        .ok_or_else(|| anyhow!("❌ command #{id} not found in history"))?;
+
+    Ok(serde_json::from_str(&args)?)
+}
+
+pub fn process_history(ctx: &AppContext, args: HistoryArgs) -> Result<()> {
+    let conn = open_history(ctx)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, datetime(run_at, 'unixepoch', 'localtime'), args, duration_ms, result
+         FROM commands ORDER BY id DESC LIMIT ?1",
+    )?;
+    let commands = stmt
+        .query_map(params![args.limit], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    for (id, run_at, argv, duration_ms, result) in commands.into_iter().rev() {
+        let argv = serde_json::from_str::<Vec<String>>(&argv)?
+            .iter()
+            .map(|s| {
+                if s.contains(char::is_whitespace) {
+                    format!("{s:?}")
+                } else {
+                    s.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("#{id:<5} {run_at} {duration_ms:>7}ms  lp {argv}  [{result}]");
+    }
+
+    Ok(())
+}
+
+pub fn record_check_run(
+    ctx: &AppContext,
+    filter_hash: &str,
+    summaries: &[CheckSummary],
+) -> Result<()> {
+    let results = summaries
+        .iter()
+        .map(|summary| RunResult {
+            path: summary.path.display().to_string(),
+            matches: summary.matches as i64,
+            errors: summary.errors as i64,
+            cpu_peak: summary.cpu_peak,
+        })
+        .collect::<Vec<_>>();
+
+    record_run(ctx, "cl", filter_hash, &results)
+}
+
+/// 记录一次 stats 的结果，stats 没有关键字，命中行数记为 0，过滤条件的哈希为空
+pub fn record_stats_run(ctx: &AppContext, results: &[RunResult]) -> Result<()> {
+    record_run(ctx, "stats", "", results)
+}
+
+fn record_run(
+    ctx: &AppContext,
+    command: &str,
+    filter_hash: &str,
+    results: &[RunResult],
+) -> Result<()> {
+    let run_at = unix_now()?;
+
+    let mut conn = open_history(ctx)?;
+    let tx = conn.transaction()?;
+    tx.execute(
+        "INSERT INTO runs (run_at, command, filter_hash) VALUES (?1, ?2, ?3)",
+        params![run_at, command, filter_hash],
+    )?;
+    let run_id = tx.last_insert_rowid();
+
+    for result in results {
+        tx.execute(
+            "INSERT INTO results (run_id, path, matches, errors, cpu_peak) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                run_id,
+                result.path,
+                result.matches,
+                result.errors,
+                result.cpu_peak,
+            ],
+        )?;
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// 一次运行中单个文件的结果
+pub struct RunResult {
+    pub path: String,
+    pub matches: i64,
+    pub errors: i64,
+    pub cpu_peak: Option<f64>,
+}
+
+/// 记录的路径是否在 `dir` 下，按路径组成部分比较，`/var/log/app` 不包含 `/var/log/app2`
+fn is_under(path: &str, dir: Option<&Path>) -> bool {
+    dir.is_none_or(|dir| Path::new(path).starts_with(dir))
+}
+
+pub fn process_trend(ctx: &AppContext, args: TrendArgs) -> Result<()> {
+    let dir = args.path.map(|path| ctx.resolve_path(path)).transpose()?;
+
+    let conn = open_history(ctx)?;
+    let Some(latest) = latest_run(&conn, dir.as_deref(), args.command.as_deref())? else {
+        println!("no recorded runs");
+        return Ok(());
+    };
+    let LatestRun {
+        id: run_id,
+        command,
+        filter_hash,
+        run_at,
+    } = latest;
+
+    let mut stmt = conn.prepare(
+        "SELECT path, MAX(matches), MAX(errors), MAX(cpu_peak) FROM results
+         WHERE run_id IN (
+             SELECT id FROM runs WHERE command = ?4 AND filter_hash = ?1 AND id < ?2
+             ORDER BY id DESC LIMIT ?3
+         )
+         GROUP BY path",
+    )?;
+    let previous = stmt
+        .query_map(
+            params![filter_hash, run_id, args.runs, command],
+            read_result,
+        )?
+        .map(|r| r.map(|r| (r.path.clone(), r)))
+        .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+
+    if previous.is_empty() {
+        println!("{command} run #{run_id} ({run_at}) has no previous runs with the same filters");
+        return Ok(());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT path, matches, errors, cpu_peak FROM results
+         WHERE run_id = ?1
+         ORDER BY path",
+    )?;
+    let mut latest = stmt
+        .query_map(params![run_id], read_result)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    latest.retain(|result| is_under(&result.path, dir.as_deref()));
+
+    let mut regressions = 0;
+    for result in &latest {
+        let Some(prev) = previous.get(&result.path) else {
+            println!("🆕 {}: no history", result.path);
+            continue;
+        };
+
+        let mut changes = Vec::new();
+        if result.errors > prev.errors {
+            changes.push(format!("errors {} -> {}", prev.errors, result.errors));
+        }
+        if let (Some(cpu), Some(prev_cpu)) = (result.cpu_peak, prev.cpu_peak)
+            && cpu > prev_cpu
+        {
+            changes.push(format!("cpu peak {prev_cpu:.2}% -> {cpu:.2}%"));
+        }
+        if result.matches > prev.matches {
+            changes.push(format!(
+                "keyword lines {} -> {}",
+                prev.matches, result.matches
+            ));
+        }
+
+        if !changes.is_empty() {
+            regressions += 1;
+            println!("⚠️ {}: {}", result.path, changes.join(", "));
+        }
+    }
+
+    println!(
+        "{command} run #{run_id} ({run_at}) compared against up to {} previous runs, files: {}, regressed: {}",
+        args.runs,
+        latest.len(),
+        regressions
+    );
+
+    Ok(())
+}
+
+/// 最近一次包含 `dir` 下文件的运行
+struct LatestRun {
+    id: i64,
+    command: String,
+    filter_hash: String,
+    run_at: String,
+}
+
+/// 从新到旧逐条检查运行结果的路径，`command` 为 `None` 时不限命令
+fn latest_run(
+    conn: &Connection,
+    dir: Option<&Path>,
+    command: Option<&str>,
+) -> Result<Option<LatestRun>> {
+    let mut stmt = conn.prepare(
+        "SELECT runs.id, runs.command, runs.filter_hash,
+                datetime(runs.run_at, 'unixepoch', 'localtime'), results.path
+         FROM runs JOIN results ON results.run_id = runs.id
+         WHERE ?1 IS NULL OR runs.command = ?1
+         ORDER BY runs.id DESC",
+    )?;
+    for row in stmt.query_map(params![command], |row| {
+        Ok((
+            LatestRun {
+                id: row.get(0)?,
+                command: row.get(1)?,
+                filter_hash: row.get(2)?,
+                run_at: row.get(3)?,
+            },
+            row.get::<_, String>(4)?,
+        ))
+    })? {
+        let (run, path) = row?;
+        if is_under(&path, dir) {
+            return Ok(Some(run));
+        }
+    }
+
+    Ok(None)
+}
+
+fn read_result(row: &rusqlite::Row<'_>) -> rusqlite::Result<RunResult> {
+    Ok(RunResult {
+        path: row.get(0)?,
+        matches: row.get(1)?,
+        errors: row.get(2)?,
+        cpu_peak: row.get(3)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_under() {
+        let dir = Path::new("/var/log/app");
+        assert!(is_under("/var/log/app/a.log", Some(dir)));
+        assert!(is_under("/var/log/app", Some(dir)));
+        assert!(!is_under("/var/log/app2/a.log", Some(dir)));
+        assert!(!is_under("/var/log/application.log", Some(dir)));
+        assert!(is_under("/srv/a.log", None));
+    }
+
+    #[test]
+    fn test_filter_hash_options() {
+        use crate::subcommand::CheckLineArgs;
+
+        let hash = |argv: &[&str]| {
+            let args = CheckLineArgs::try_parse_from(argv).unwrap();
+            filter_hash(&[], &args.matching, &args.records)
+        };
+        let plain = hash(&["cl", "-p", "a.log"]);
+        assert_eq!(plain, hash(&["cl", "-p", "a.log"]));
+        // 表达式与记录边界不同的运行不互相比较
+        assert_ne!(plain, hash(&["cl", "-p", "a.log", "--expr", "pid:"]));
+        assert_ne!(plain, hash(&["cl", "-p", "a.log", "--entries"]));
+        assert_ne!(
+            plain,
+            hash(&["cl", "-p", "a.log", "--record-separator=---"])
+        );
+    }
+
+    #[test]
+    fn test_latest_run() {
+        let dir = std::env::temp_dir().join(format!("lp_history_test_{}", std::process::id()));
+        let ctx = AppContext::new(dir.join("config.json"));
+        let result = |path: &str, errors| RunResult {
+            path: path.to_string(),
+            matches: 0,
+            errors,
+            cpu_peak: None,
+        };
+        record_run(&ctx, "cl", "abc", &[result("/var/log/app/a.log", 1)]).unwrap();
+        record_stats_run(&ctx, &[result("/var/log/db/a.log", 2)]).unwrap();
+
+        let conn = open_history(&ctx).unwrap();
+        let latest = |dir: Option<&str>, command| {
+            latest_run(&conn, dir.map(Path::new), command)
+                .unwrap()
+                .map(|run| (run.command, run.filter_hash))
+        };
+        assert_eq!(
+            latest(None, None),
+            Some(("stats".to_string(), String::new()))
+        );
+        assert_eq!(
+            latest(None, Some("cl")),
+            Some(("cl".to_string(), "abc".to_string()))
+        );
+        assert_eq!(
+            latest(Some("/var/log/app"), None),
+            Some(("cl".to_string(), "abc".to_string()))
+        );
+        assert_eq!(latest(Some("/var/log/app"), Some("stats")), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
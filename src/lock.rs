@@ -0,0 +1,141 @@
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use clap::Args;
+
+/// 目标已被其他 lp 进程处理时的策略
+#[derive(Args, Clone, Copy)]
+pub struct LockArgs {
+    /// 目标正被其他 lp 进程处理时等待其完成 (默认)
+    #[arg(long, default_value_t = false, overrides_with = "no_wait")]
+    pub wait: bool,
+
+    /// 目标正被其他 lp 进程处理时立即退出
+    #[arg(long, default_value_t = false, overrides_with = "wait")]
+    pub no_wait: bool,
+}
+
+/// 锁文件的后缀，遍历文件夹时跳过这类文件
+pub const LOCK_SUFFIX: &str = ".lp.lock";
+
+/// 目标路径的锁文件，为同目录下的 `.<name>.lp.lock`，释放锁时删除；放在目标旁边而不是配置目录，
+/// 不同工作目录下运行的 lp 也能拿到同一把锁，rf 删除文件夹时也不会删到锁文件
+fn lock_path(target: &Path) -> PathBuf {
+    let target = fs::canonicalize(target).unwrap_or_else(|_| target.to_path_buf());
+    match target.file_name() {
+        Some(name) => target.with_file_name(format!(".{}{LOCK_SUFFIX}", name.display())),
+        None => target.join(LOCK_SUFFIX),
+    }
+}
+
+/// 目标路径的锁，drop 时删除锁文件并解锁
+pub struct TargetLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl Drop for TargetLock {
+    fn drop(&mut self) {
+        // 先在持有锁时删除，等待中的进程拿到锁后会发现文件已不在原处而重新创建，
+        // 不会出现两个进程分别锁住新旧两个文件的情况
+        let _ = fs::remove_file(&self.path);
+        let _ = self.file.unlock();
+    }
+}
+
+/// 拿到锁的文件是否仍是锁文件路径上的那个，持有者退出时可能已将其删除
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (file.metadata(), fs::metadata(path)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+/// 其他平台上打开中的文件无法删除，锁文件一直在原处
+#[cfg(not(unix))]
+fn is_current(_file: &File, path: &Path) -> bool {
+    path.exists()
+}
+
+/// 对目标路径加独占的 advisory 锁，返回的 [`TargetLock`] 被 drop 时释放
+pub fn lock_target(target: &Path, args: LockArgs) -> Result<TargetLock> {
+    let path = lock_path(target);
+    let mut waited = false;
+    let mut lock = loop {
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) if !args.no_wait => {
+                if !waited {
+                    println!(
+                        "⏳ {} is being processed by another lp, waiting",
+                        target.display()
+                    );
+                    waited = true;
+                }
+                lock.lock()?;
+            }
+            Err(TryLockError::WouldBlock) => {
+                bail!(
+                    "❌ {} is being processed by another lp, retry later or use --wait",
+                    target.display()
+                );
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+        if is_current(&lock, &path) {
+            break lock;
+        }
+    };
+
+    // 记录持有者，便于排查
+    lock.set_len(0)?;
+    writeln!(lock, "{} pid {}", target.display(), std::process::id())?;
+
+    Ok(TargetLock { file: lock, path })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_path() {
+        assert_eq!(
+            lock_path(Path::new("/nonexistent/app.log")),
+            PathBuf::from("/nonexistent/.app.log.lp.lock")
+        );
+    }
+
+    #[test]
+    fn test_lock_removed() {
+        let dir = std::env::temp_dir().join(format!("lp_lock_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("app.log");
+        fs::write(&target, "").unwrap();
+        let args = LockArgs {
+            wait: false,
+            no_wait: true,
+        };
+
+        let lock = lock_target(&target, args).unwrap();
+        assert!(lock_path(&target).exists());
+        assert!(lock_target(&target, args).is_err());
+        drop(lock);
+        assert!(!lock_path(&target).exists());
+        drop(lock_target(&target, args).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use serde_json::json;
+
+use crate::{
+    context::AppContext,
+    exit::Failures,
+    record::parse_line,
+    subcommand::get_entries,
+    time::{parse_timestamp, parse_utc_offset},
+};
+
+#[derive(Parser)]
+pub struct PushLokiArgs {
+    /// Loki 地址，如 http://loki:3100
+    #[arg(long)]
+    pub url: String,
+
+    /// 附加的标签，如 `job=field,host=dev1`
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_label)]
+    pub labels: Vec<(String, String)>,
+
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 日志时间所在时区，如 +08:00
+    #[arg(long, default_value = "+00:00", value_parser = parse_utc_offset, allow_hyphen_values = true)]
+    pub utc_offset: i64,
+
+    /// 每次请求最多推送的记录数
+    #[arg(long, default_value_t = 1000)]
+    pub batch: usize,
+}
+
+fn parse_label(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid label `{s}`, expected name=value"))?;
+    let name = name.trim();
+
+    let valid = name
+        .chars()
+        .enumerate()
+        .all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+    if name.is_empty() || !valid {
+        return Err(format!("invalid label name `{name}`"));
+    }
+
+    Ok((name.to_string(), value.trim().to_string()))
+}
+
+/// 一条待推送的日志记录，续行 (如堆栈) 合并到上一条
+struct Entry {
+    level: String,
+    time_ns: i64,
+    line: String,
+}
+
+fn parse_entries(content: &str, utc_offset: i64) -> (Vec<Entry>, usize) {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut skipped = 0;
+
+    for line in content.lines() {
+        let record =
+            parse_line(line).and_then(|record| Some((parse_timestamp(record.time)?, record.level)));
+        match (record, entries.last_mut()) {
+            (Some((ms, level)), _) => entries.push(Entry {
+                level: level.to_ascii_lowercase(),
+                time_ns: (ms - utc_offset) * 1_000_000,
+                line: line.to_string(),
+            }),
+            (None, Some(last)) => {
+                last.line.push('\n');
+                last.line.push_str(line);
+            }
+            (None, None) => skipped += 1,
+        }
+    }
+
+    (entries, skipped)
+}
+
+/// 按 level 分 stream 组装 push 请求体
+fn push_body(entries: &[Entry], labels: &BTreeMap<String, String>) -> serde_json::Value {
+    let mut streams: BTreeMap<&str, Vec<[String; 2]>> = BTreeMap::new();
+    for entry in entries {
+        streams
+            .entry(&entry.level)
+            .or_default()
+            .push([entry.time_ns.to_string(), entry.line.clone()]);
+    }
+
+    let streams = streams
+        .into_iter()
+        .map(|(level, values)| {
+            let mut stream = labels.clone();
+            stream.insert("level".to_string(), level.to_string());
+            json!({ "stream": stream, "values": values })
+        })
+        .collect::<Vec<_>>();
+
+    json!({ "streams": streams })
+}
+
+fn push_file(path: &Path, name: &str, args: &PushLokiArgs, endpoint: &str) -> Result<usize> {
+    let content = fs::read_to_string(path)?;
+    let (entries, skipped) = parse_entries(&content, args.utc_offset);
+    if skipped > 0 {
+        println!("⚠️ {name}: skipped {skipped} lines before the first timestamp");
+    }
+
+    let mut labels = args.labels.iter().cloned().collect::<BTreeMap<_, _>>();
+    labels
+        .entry("filename".to_string())
+        .or_insert_with(|| name.to_string());
+
+    for batch in entries.chunks(args.batch) {
+        let body = serde_json::to_string(&push_body(batch, &labels))?;
+        ureq::post(endpoint)
+            .header("Content-Type", "application/json")
+            .send(body)?;
+    }
+
+    Ok(entries.len())
+}
+
+pub fn process_push_loki(ctx: &AppContext, args: PushLokiArgs) -> Result<()> {
+    if args.batch == 0 {
+        bail!("❌ --batch should be greater than 0");
+    }
+
+    let path = ctx.resolve_path(args.path.clone())?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    let endpoint = format!("{}/loki/api/v1/push", args.url.trim_end_matches('/'));
+    let files = if path.is_dir() {
+        get_entries(&path)
+            .into_iter()
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        vec![path.clone()]
+    };
+
+    // 逐个文件顺序推送，避免同一 stream 的记录乱序
+    let mut total = 0;
+    let failures = Failures::new(ctx);
+    for file in &files {
+        if failures.should_stop() {
+            break;
+        }
+        let name = file.strip_prefix(&path).unwrap_or(file);
+        let name = if name.as_os_str().is_empty() {
+            file.file_name().unwrap_or_default().display().to_string()
+        } else {
+            name.to_string_lossy().replace('\\', "/")
+        };
+
+        match push_file(file, &name, &args, &endpoint) {
+            Ok(count) => {
+                total += count;
+                println!("push file, path: {:?}, records: {}", file.display(), count);
+            }
+            Err(e) => {
+                eprintln!("❌ push loki failed, path {:?}, reason: {}", file, e);
+                failures.record(file, &e);
+            }
+        }
+    }
+    println!("pushed records: {total} to {endpoint}");
+
+    failures.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_entries() {
+        let content = "\
+banner line
+[2026-01-06 10:29:10.765] [error] [Global]  exception callback: ERRCODE_MSOPTIMEOUT
+    at ModelServer::load
+[1970-01-01 08:00:01] [INFO] [Global]  started";
+        let (entries, skipped) = parse_entries(content, 8 * 3_600_000);
+        assert_eq!(skipped, 1);
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].line.ends_with("\n    at ModelServer::load"));
+        assert_eq!(entries[1].level, "info");
+        assert_eq!(entries[1].time_ns, 1_000_000_000);
+
+        let labels = BTreeMap::from([("job".to_string(), "field".to_string())]);
+        let body = push_body(&entries, &labels);
+        assert_eq!(body["streams"].as_array().unwrap().len(), 2);
+        assert_eq!(body["streams"][0]["stream"]["level"], "error");
+        assert_eq!(body["streams"][1]["stream"]["job"], "field");
+        assert_eq!(body["streams"][1]["values"][0][0], "1000000000");
+    }
+
+    #[test]
+    fn test_parse_label() {
+        assert_eq!(
+            parse_label("job=field"),
+            Ok(("job".to_string(), "field".to_string()))
+        );
+        assert!(parse_label("job").is_err());
+        assert!(parse_label("1job=x").is_err());
+    }
+}
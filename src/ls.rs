@@ -0,0 +1,184 @@
+use std::{cmp::Reverse, fs, path::PathBuf};
+
+use anyhow::{Ok, Result, bail};
+use clap::{Parser, ValueEnum};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    context::AppContext, exit::Failures, record::parse_line, subcommand::get_entries,
+    table::print_table, time::parse_timestamp, units::format_size,
+};
+
+/// 排序字段
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SortKey {
+    Name,
+    Size,
+    Lines,
+    First,
+    Last,
+}
+
+#[derive(Parser)]
+pub struct LsArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 排序字段，size/lines 从大到小，其余从小到大
+    #[arg(short, long, value_enum, default_value = "name")]
+    pub sort: SortKey,
+
+    /// 反向排序
+    #[arg(short, long)]
+    pub reverse: bool,
+
+    /// 以 json 格式输出
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Serialize)]
+struct LogInfo {
+    path: PathBuf,
+    size: u64,
+    lines: usize,
+    first: Option<String>,
+    last: Option<String>,
+    encoding: &'static str,
+}
+
+/// 根据 BOM 与内容判断文件编码
+fn detect_encoding(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        "utf-8-bom"
+    } else if bytes.starts_with(&[0xFF, 0xFE]) {
+        "utf-16le"
+    } else if bytes.starts_with(&[0xFE, 0xFF]) {
+        "utf-16be"
+    } else if bytes.is_ascii() {
+        "ascii"
+    } else if std::str::from_utf8(bytes).is_ok() {
+        "utf-8"
+    } else {
+        "unknown"
+    }
+}
+
+fn line_time(line: &str) -> Option<&str> {
+    let record = parse_line(line)?;
+    parse_timestamp(record.time).map(|_| record.time)
+}
+
+fn inspect(path: PathBuf) -> Result<LogInfo> {
+    let bytes = fs::read(&path)?;
+    let encoding = detect_encoding(&bytes);
+    let content = String::from_utf8_lossy(&bytes);
+
+    let first = content.lines().find_map(line_time);
+    let last = content.lines().rev().find_map(line_time);
+
+    Ok(LogInfo {
+        size: bytes.len() as u64,
+        lines: content.lines().count(),
+        first: first.map(str::to_string),
+        last: last.map(str::to_string),
+        encoding,
+        path,
+    })
+}
+
+pub fn process_ls(ctx: &AppContext, args: LsArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    let files = if path.is_dir() {
+        get_entries(&path)
+            .into_iter()
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        vec![path.clone()]
+    };
+
+    let failures = Failures::new(ctx);
+    let mut infos = files
+        .into_par_iter()
+        .filter_map(|file| {
+            if failures.should_stop() {
+                return None;
+            }
+            inspect(file.clone())
+                .inspect_err(|e| {
+                    eprintln!("❌ ls failed, path {:?}, reason: {}", file, e);
+                    failures.record(&file, e);
+                })
+                .ok()
+        })
+        .collect::<Vec<_>>();
+
+    match args.sort {
+        SortKey::Name => infos.sort_by(|a, b| a.path.cmp(&b.path)),
+        SortKey::Size => infos.sort_by_key(|info| Reverse(info.size)),
+        SortKey::Lines => infos.sort_by_key(|info| Reverse(info.lines)),
+        SortKey::First => infos.sort_by(|a, b| a.first.cmp(&b.first)),
+        SortKey::Last => infos.sort_by(|a, b| a.last.cmp(&b.last)),
+    }
+    if args.reverse {
+        infos.reverse();
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&infos)?);
+        return failures.finish();
+    }
+
+    let rows = infos
+        .iter()
+        .map(|info| {
+            let name = info.path.strip_prefix(&path).unwrap_or(&info.path);
+            let name = if name.as_os_str().is_empty() {
+                &info.path
+            } else {
+                name
+            };
+            vec![
+                name.display().to_string(),
+                format_size(info.size),
+                info.lines.to_string(),
+                info.first.clone().unwrap_or_default(),
+                info.last.clone().unwrap_or_default(),
+                info.encoding.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_table(
+        &["file", "size", "lines", "first", "last", "encoding"],
+        &rows,
+    );
+    println!(
+        "files: {}, total size: {}",
+        infos.len(),
+        format_size(infos.iter().map(|info| info.size).sum())
+    );
+
+    failures.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(detect_encoding(b"[info] ok"), "ascii");
+        assert_eq!(detect_encoding("模型加载".as_bytes()), "utf-8");
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBF[info]"), "utf-8-bom");
+        assert_eq!(detect_encoding(b"\xFF\xFE[\0"), "utf-16le");
+        // GBK 编码的 "模型"
+        assert_eq!(detect_encoding(b"\xC4\xA3\xD0\xCD"), "unknown");
+    }
+}
@@ -1,18 +1,172 @@
-use std::{fs, path::Path};
+use std::{
+    env, iter,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Instant,
+};
 
-use anyhow::{Ok, Result, anyhow};
+use anomalies::{AnomaliesArgs, process_anomalies};
+use anyhow::{Ok, Result, bail};
+use audit::{AuditArgs, process_audit};
+use bundle::{BundleArgs, process_bundle};
+use bursts::{BurstsArgs, process_bursts};
+use cancel::{install_interrupt_handler, is_cancelled};
 use clap::{Parser, Subcommand};
-use rust_xlsxwriter::workbook::Workbook;
+use clean::{CleanArgs, process_clean};
+use compare::{CompareArgs, process_compare};
+use config::{ConfigArgs, process_config};
+use context::{AppContext, env_base_dir};
+use cooccur::{CooccurArgs, process_cooccur};
+use dedup::{DedupArgs, process_dedup};
+use diff_dir::{DiffDirArgs, process_diff_dir};
+use exit::exit_code;
+use export::{ExportArgs, process_export};
+use filters::{FiltersArgs, process_filters};
+use follow::{FollowArgs, process_follow};
+use growth::{GrowthArgs, process_growth};
+use heartbeat::{HeartbeatArgs, process_heartbeat};
+use heatmap::{HeatmapArgs, process_heatmap};
+use highlight::{HighlightArgs, process_highlight};
+use history::{
+    HistoryArgs, RerunArgs, TrendArgs, command_args, process_history, process_trend, record_command,
+};
+use loki::{PushLokiArgs, process_push_loki};
+use ls::{LsArgs, process_ls};
+use merge::{MergeArgs, process_merge};
+use metrics::{MetricsArgs, process_metrics};
+use new_lines::{NewLinesArgs, process_new_lines};
+use occurrences::{OccurrencesArgs, process_occurrences};
+use output::OutputFormat;
+use preset::{PresetArgs, process_preset};
+use profile::{ProfileArgs, process_profile};
+use prom::{PromArgs, process_prom};
+use repro_slice::{ReproSliceArgs, process_repro_slice};
+use route::{RouteArgs, process_route};
+use sample::{SampleArgs, process_sample};
+use seek::{SeekArgs, process_seek};
+use selftest::{SelftestArgs, process_selftest};
+use shard::{MergeResultsArgs, ShardArgs, process_merge_results, process_shard};
+use split::{SplitArgs, process_split};
+use split_pid::{SplitPidArgs, process_split_pid};
+use stats::{StatsArgs, process_stats};
 use subcommand::{
     BaseDirArgs, CheckLineArgs, RemoveFileArgs, RemoveLineArgs, get_base_dir, process_check_line,
     process_remove_file, process_remove_line, set_base_dir,
 };
+use temp::rollback;
+use transform::{TransformArgs, process_transform};
+use undo::{UndoArgs, process_undo};
+use upload::{UploadArgs, process_upload};
+use watch::{WatchArgs, process_watch};
+use with::{ConfigOverlay, WithArgs};
 
+mod annotate;
+mod anomalies;
+mod audit;
+mod bundle;
+mod bursts;
+mod cancel;
+mod clean;
+mod compare;
+mod compress;
+mod config;
+mod context;
+mod cooccur;
+mod dedup;
+mod diff_dir;
+mod entries;
+mod estimate;
+mod exit;
+mod export;
+mod expr;
+mod extractor;
+mod filters;
+mod follow;
+mod glob;
+mod growth;
+mod heartbeat;
+mod heatmap;
+mod highlight;
+mod history;
+mod lock;
+mod loki;
+mod ls;
+mod mapping;
+mod matcher;
+mod merge;
+mod metrics;
+mod new_lines;
+mod occurrences;
+mod ordered;
+mod out_name;
+mod output;
+mod pipeline;
+mod preset;
+mod profile;
+mod progress;
+mod prom;
+mod record;
+mod repro_slice;
+mod route;
+mod sample;
+mod schedule;
+mod seek;
+mod selftest;
+mod shard;
+mod split;
+mod split_pid;
+mod stats;
 mod subcommand;
+mod table;
+mod temp;
+mod time;
+mod timeout;
+mod timestamp;
+mod transform;
+mod undo;
+mod units;
+mod upload;
+mod watch;
+mod with;
 
 #[derive(Parser)]
 #[command(name = "lp", version, about = "简单日志处理工具")]
 struct Cli {
+    /// 本次运行使用的根路径，覆盖环境变量 LP_BASE_DIR 与配置中的 base_dir
+    #[arg(long, global = true)]
+    base_dir: Option<PathBuf>,
+
+    /// 本次运行使用配置中该名称的根路径，覆盖环境变量 LP_BASE_DIR 与 `lp profile use` 的选择
+    #[arg(long, global = true, conflicts_with = "base_dir")]
+    profile: Option<String>,
+
+    /// 在 stderr 输出每行一个 JSON 的进度事件 (文件开始、进度百分比、文件结束、汇总)，供 GUI 使用
+    #[arg(long, global = true, default_value_t = false)]
+    progress_json: bool,
+
+    /// 不显示文件夹模式下的进度条 (文件数、已处理字节数与预计剩余时间)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = false,
+        conflicts_with = "progress_json"
+    )]
+    no_progress: bool,
+
+    /// 只读模式：拒绝执行修改或删除日志的命令 (rl --in-place、rf、clean)，dry run 不受影响；
+    /// 也可通过 `lp config set read-only true` 开启
+    #[arg(long, global = true, default_value_t = false)]
+    read_only: bool,
+
+    /// 文件夹模式下第一个文件失败后不再开始新的文件，已开始的文件照常完成
+    #[arg(long, global = true, default_value_t = false)]
+    fail_fast: bool,
+
+    /// 供脚本解析的输出格式，需写在子命令之前，如 `lp --format ndjson cl -p logs`；
+    /// 目前 cl、stats、export 支持，子命令自身指定了非文本格式时以子命令的为准
+    #[arg(long, value_enum, default_value = "text")]
+    format: OutputFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -20,11 +174,11 @@ struct Cli {
 /// 子命令集合
 #[derive(Subcommand)]
 enum Commands {
-    /// 设置要操作文件的根路径
+    /// 设置要操作文件的根路径，同 `lp config set base-dir`
     #[command(name = "sbd", alias = "set_bd")]
     SetBaseDir(BaseDirArgs),
 
-    /// 获取当前的根路径
+    /// 获取当前生效的根路径 (LP_BASE_DIR、--base-dir、--profile 优先于配置)，同 `lp config get base-dir`
     #[command(name = "gbd", alias = "get_bd")]
     GetBaseDir,
 
@@ -36,95 +190,382 @@ enum Commands {
     #[command(name = "rl", alias = "rm_ln")]
     RemoveLine(RemoveLineArgs),
 
-    /// 删除文件
+    /// 删除文件或文件夹，默认移到系统回收站
     #[command(name = "rf", alias = "rm_f")]
     RemoveFile(RemoveFileArgs),
-}
 
-fn main() -> Result<()> {
-    // // let path = "E:/project/select_direction/1234 - 副本.log";
-    // let path = "E:/project/select_direction/23.log";
-    // split_log_to_excel(path)?;
+    /// 对比最近一次 cl 或 stats 与历史记录，标出变差的文件
+    Trend(TrendArgs),
 
-    let args = Cli::parse();
-    match args.command {
-        Commands::SetBaseDir(args) => {
-            set_base_dir(args)?;
-        }
-        Commands::GetBaseDir => {
-            println!("{}", get_base_dir()?.path.display());
-        }
-        Commands::CheckLine(args) => {
-            process_check_line(args)?;
-        }
-        Commands::RemoveLine(args) => {
-            process_remove_line(args)?;
-        }
-        Commands::RemoveFile(args) => {
-            process_remove_file(args)?;
-        }
-    }
+    /// 查看执行过的命令
+    History(HistoryArgs),
 
-    Ok(())
-}
+    /// 重新执行历史记录中的命令
+    Rerun(RerunArgs),
 
-fn split_log_to_excel<P: AsRef<Path>>(path: P) -> Result<()> {
-    let line = fs::read_to_string(&path)?;
-    let mut east_str = String::new();
-    let mut west_str = String::new();
+    /// 对比两次 `cl --json` 或 `stats --json` 的结果，列出变差的文件
+    Compare(CompareArgs),
 
-    let mut east_data = Vec::new();
-    let mut west_data = Vec::new();
+    /// 统计两个关键字在时间窗口内先后出现的次数
+    Cooccur(CooccurArgs),
 
-    for line in line.lines() {
-        if line.contains("East") {
-            east_str.push_str(line);
-            east_str.push('\n');
+    /// 检测 cpu/内存指标中的异常点
+    Anomalies(AnomaliesArgs),
 
-            east_data.push(line);
-        } else if line.contains("West") {
-            west_str.push_str(line);
-            west_str.push('\n');
+    /// 按 pid 将多进程混写的日志拆分为多个文件
+    SplitPid(SplitPidArgs),
 
-            west_data.push(line);
-        }
-    }
+    /// 按保留策略清理根路径下的旧日志
+    Clean(CleanArgs),
 
-    fs::write("east.log", east_str)?;
-    fs::write("west.log", west_str)?;
+    /// 查询 rf/clean 等破坏性操作的审计记录
+    Audit(AuditArgs),
 
-    write_to_xlsx(&east_data, "east.xlsx")?;
-    write_to_xlsx(&west_data, "west.xlsx")?;
+    /// 按规则重映射日志级别，输出到 xxx_transformed 文件
+    Transform(TransformArgs),
 
-    Ok(())
+    /// 保留全部警告/错误，其余日志按比例抽样
+    Sample(SampleArgs),
+
+    /// 统计每个关键字在各文件中首次/末次出现的时间与次数
+    Occurrences(OccurrencesArgs),
+
+    /// 列出当前日志中基准日志里没有出现过的消息模板
+    NewLines(NewLinesArgs),
+
+    /// 列出日志的大小、行数、起止时间与编码
+    Ls(LsArgs),
+
+    /// 将文件夹中的文件按哈希分片，只检查其中一个分片，输出 `cl --json` 格式结果
+    Shard(ShardArgs),
+
+    /// 合并多个分片的检查结果
+    MergeResults(MergeResultsArgs),
+
+    /// 将关键字行数、错误数与最新 cpu/内存读数导出为 Prometheus textfile
+    Prom(PromArgs),
+
+    /// 将日志记录推送到 Grafana Loki
+    PushLoki(PushLokiArgs),
+
+    /// 按关键字将日志拆分为多个文件，可同时输出 xlsx
+    Split(SplitArgs),
+
+    /// 将日志打包为 zip，可用 AES 加密
+    Bundle(BundleArgs),
+
+    /// 将日志解析为 时间/级别/模块/消息 并导出为 xlsx、csv 或 json
+    Export(ExportArgs),
+
+    /// 在大文件中按时间二分定位，打印附近的日志
+    Seek(SeekArgs),
+
+    /// 去除重复行，可按时间容差将消息相同、时间相近的行视为重复
+    Dedup(DedupArgs),
+
+    /// 按文件 × 时间桶统计关键字命中数，输出热力图
+    Heatmap(HeatmapArgs),
+
+    /// 持续输出日志文件新写入的命中行 (类似 tail -F)，可先回填轮转前的文件
+    #[command(alias = "tail")]
+    Follow(FollowArgs),
+
+    /// 监听文件夹，对新增或修改的 .log 文件持续执行 cl 或 rl
+    Watch(WatchArgs),
+
+    /// 统计行数、各级别与模块的行数以及时间范围
+    Stats(StatsArgs),
+
+    /// 提取状态行中的 cpu/内存读数，导出为 csv 或 xlsx
+    Metrics(MetricsArgs),
+
+    /// 查看和修改配置
+    Config(ConfigArgs),
+
+    /// 检查关键字集合
+    Filters(FiltersArgs),
+
+    /// 按时间戳合并多个日志文件
+    Merge(MergeArgs),
+
+    /// 生成关键字高亮的 HTML 页面，附命中行索引
+    Highlight(HighlightArgs),
+
+    /// 管理配置中的命名关键字集合
+    Preset(PresetArgs),
+
+    /// 按相对路径配对两个日志目录的文件，对比行数、错误数与新出现的消息模板
+    DiffDir(DiffDirArgs),
+
+    /// 检查大量连续行共用同一毫秒时间戳的突发 (日志后端批量刷写的症状)
+    Bursts(BurstsArgs),
+
+    /// 管理多个命名的根路径，用于处理不同产品的日志
+    Profile(ProfileArgs),
+
+    /// 按天或周统计各组件产生的日志行数与字节数，用于容量规划
+    Growth(GrowthArgs),
+    /// 分块上传处理结果到制品服务，附带关键字、源路径与工具版本等元数据，中断后再次执行可续传
+    Upload(UploadArgs),
+    /// 用内置的样例日志运行各子命令，逐字节对比输出与期望结果
+    Selftest(SelftestArgs),
+    /// 以 `--base-dir` 指定的根路径与当前配置的临时副本执行一条子命令，不影响全局配置，
+    /// 如 `lp with --base-dir /tmp/case123 -- cl -p logs`
+    With(WithArgs),
+    /// 按关键字规则把每行写入一个或多个输出文件，只扫描一次，不匹配的行可写入默认输出
+    Route(RouteArgs),
+    /// 截取故障时间前后的一段日志并脱敏，文件名与校验和固定，用于附到上游的缺陷报告，
+    /// 如 `lp repro-slice -p app.log --around '2026-01-06 10:29:10' --window 2m`
+    ReproSlice(ReproSliceArgs),
+    /// 撤销最近的 `rl --in-place` 或 `rf`：改写前的原文件暂存在 `.lp_undo`，删除的文件从回收站恢复
+    Undo(UndoArgs),
+    /// 检查周期性状态行是否按预期间隔出现，列出每一段漏报，漏报通常意味着服务卡死
+    Heartbeat(HeartbeatArgs),
 }
 
-fn write_to_xlsx<P: AsRef<Path>>(lines: &[&str], path: P) -> Result<()> {
-    let mut wb = Workbook::new();
-    let ws = wb.add_worksheet();
+fn main() -> ExitCode {
+    let result = run_cli(Cli::parse());
+    exit_code(&result)
+}
 
-    for (row, &line) in lines.iter().enumerate() {
-        let mut parts = line.split(']');
-        let mut time = parts
-            .next()
-            .ok_or_else(|| anyhow!("line should contain time"))?;
-        if time.starts_with('[') {
-            time = time.strip_prefix('[').unwrap();
-        }
+/// 未指定全局参数时的上下文：根路径取自环境变量 LP_BASE_DIR (如有)，显示进度条
+fn base_context(config_path: &Path) -> AppContext {
+    let ctx = AppContext::new(config_path).with_progress_bar();
+    match env_base_dir() {
+        Some(base_dir) => ctx.with_base_dir(base_dir),
+        None => ctx,
+    }
+}
+
+/// 在 `ctx` 上应用命令行中的全局参数，未指定的沿用 `ctx` 的设置；
+/// `lp rerun` 与 `lp with` 重新解析的命令同样经过这里，记录中的全局参数不会丢失
+fn cli_context(args: &Cli, ctx: AppContext) -> AppContext {
+    let ctx = match (&args.base_dir, &args.profile) {
+        (Some(base_dir), _) => ctx.with_base_dir(base_dir),
+        (None, Some(profile)) => ctx.with_profile(profile.clone()),
+        (None, None) => ctx,
+    };
+    let ctx = if args.progress_json {
+        ctx.with_progress_json()
+    } else if args.no_progress {
+        ctx.without_progress()
+    } else {
+        ctx
+    };
+    let ctx = if args.read_only {
+        ctx.with_read_only()
+    } else {
+        ctx
+    };
+    let ctx = if args.fail_fast {
+        ctx.with_fail_fast()
+    } else {
+        ctx
+    };
+    if args.format == OutputFormat::Text {
+        ctx
+    } else {
+        ctx.with_output_format(args.format)
+    }
+}
+
+fn run_cli(args: Cli) -> Result<()> {
+    let record = !matches!(args.command, Commands::History(_));
+    let argv = env::args().skip(1).collect::<Vec<_>>();
 
-        let other = parts
-            .next()
-            .ok_or_else(|| anyhow!("line should contain other Lines"))?;
-        let other_parts = other.split_whitespace();
+    let ctx = cli_context(&args, base_context(AppContext::default().config_path()));
 
-        let row = row as u32;
-        ws.write_string(row, 0, time)?;
-        for (col, part) in other_parts.enumerate() {
-            ws.write_string(row, (col + 1) as u16, part)?;
+    if let Some(threads) = ctx.threads()? {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()?;
+    }
+    install_interrupt_handler()?;
+    let start = Instant::now();
+    let result = run(&ctx, args.command).and_then(|()| {
+        if is_cancelled() {
+            bail!("❌ interrupted, results are partial");
         }
+        Ok(())
+    });
+    // 超时的任务在后台线程中继续运行，进程退出前删除其未完成的输出
+    for path in rollback() {
+        eprintln!("↩ rolled back partial output, path: {:?}", path.display());
+    }
+    if record && let Err(e) = record_command(&ctx, &argv, start.elapsed(), &result) {
+        eprintln!("❌ record command failed, reason: {}", e);
     }
 
-    wb.save(path)?;
+    result
+}
+
+fn run(ctx: &AppContext, command: Commands) -> Result<()> {
+    match command {
+        Commands::SetBaseDir(args) => {
+            set_base_dir(ctx, args)?;
+        }
+        Commands::GetBaseDir => {
+            println!("{}", get_base_dir(ctx)?.path.display());
+        }
+        Commands::CheckLine(args) => {
+            process_check_line(ctx, args)?;
+        }
+        Commands::RemoveLine(args) => {
+            process_remove_line(ctx, args)?;
+        }
+        Commands::RemoveFile(args) => {
+            process_remove_file(ctx, args)?;
+        }
+        Commands::Trend(args) => {
+            process_trend(ctx, args)?;
+        }
+        Commands::History(args) => {
+            process_history(ctx, args)?;
+        }
+        Commands::Compare(args) => {
+            process_compare(args)?;
+        }
+        Commands::Cooccur(args) => {
+            process_cooccur(ctx, args)?;
+        }
+        Commands::Anomalies(args) => {
+            process_anomalies(ctx, args)?;
+        }
+        Commands::SplitPid(args) => {
+            process_split_pid(ctx, args)?;
+        }
+        Commands::Clean(args) => {
+            process_clean(ctx, args)?;
+        }
+        Commands::Audit(args) => {
+            process_audit(ctx, args)?;
+        }
+        Commands::Transform(args) => {
+            process_transform(ctx, args)?;
+        }
+        Commands::Sample(args) => {
+            process_sample(ctx, args)?;
+        }
+        Commands::Occurrences(args) => {
+            process_occurrences(ctx, args)?;
+        }
+        Commands::NewLines(args) => {
+            process_new_lines(ctx, args)?;
+        }
+        Commands::Ls(args) => {
+            process_ls(ctx, args)?;
+        }
+        Commands::Shard(args) => {
+            process_shard(ctx, args)?;
+        }
+        Commands::MergeResults(args) => {
+            process_merge_results(args)?;
+        }
+        Commands::Prom(args) => {
+            process_prom(ctx, args)?;
+        }
+        Commands::PushLoki(args) => {
+            process_push_loki(ctx, args)?;
+        }
+        Commands::Split(args) => {
+            process_split(ctx, args)?;
+        }
+        Commands::Bundle(args) => {
+            process_bundle(ctx, args)?;
+        }
+        Commands::Export(args) => {
+            process_export(ctx, args)?;
+        }
+        Commands::Seek(args) => {
+            process_seek(ctx, args)?;
+        }
+        Commands::Dedup(args) => {
+            process_dedup(ctx, args)?;
+        }
+        Commands::Heatmap(args) => {
+            process_heatmap(ctx, args)?;
+        }
+        Commands::Follow(args) => {
+            process_follow(ctx, args)?;
+        }
+        Commands::Watch(args) => {
+            process_watch(ctx, args)?;
+        }
+        Commands::Stats(args) => {
+            process_stats(ctx, args)?;
+        }
+        Commands::Metrics(args) => {
+            process_metrics(ctx, args)?;
+        }
+        Commands::Config(args) => {
+            process_config(ctx, args)?;
+        }
+        Commands::Filters(args) => {
+            process_filters(ctx, args)?;
+        }
+        Commands::Merge(args) => {
+            process_merge(ctx, args)?;
+        }
+        Commands::Highlight(args) => {
+            process_highlight(ctx, args)?;
+        }
+        Commands::Preset(args) => {
+            process_preset(ctx, args)?;
+        }
+        Commands::DiffDir(args) => {
+            process_diff_dir(ctx, args)?;
+        }
+        Commands::Bursts(args) => {
+            process_bursts(ctx, args)?;
+        }
+        Commands::Profile(args) => {
+            process_profile(ctx, args)?;
+        }
+        Commands::Growth(args) => {
+            process_growth(ctx, args)?;
+        }
+        Commands::Upload(args) => {
+            process_upload(ctx, args)?;
+        }
+        Commands::Selftest(args) => {
+            process_selftest(args)?;
+        }
+        Commands::With(args) => {
+            let cli = Cli::try_parse_from(iter::once("lp".to_string()).chain(args.command))?;
+            if matches!(cli.command, Commands::With(_) | Commands::Rerun(_)) {
+                bail!("❌ `lp with` can not run with or rerun");
+            }
+            let overlay = ConfigOverlay::new(ctx)?;
+            let nested = cli_context(&cli, ctx.clone());
+            run(&overlay.context(&nested)?, cli.command)?;
+        }
+        Commands::Route(args) => {
+            process_route(ctx, args)?;
+        }
+        Commands::ReproSlice(args) => {
+            process_repro_slice(ctx, args)?;
+        }
+        Commands::Undo(args) => {
+            process_undo(ctx, args)?;
+        }
+        Commands::Heartbeat(args) => {
+            process_heartbeat(ctx, args)?;
+        }
+        Commands::Rerun(args) => {
+            let argv = command_args(ctx, args.id)?;
+            println!("rerun: lp {}", argv.join(" "));
+
+            let cli = Cli::try_parse_from(iter::once("lp".to_string()).chain(argv))?;
+            if matches!(cli.command, Commands::History(_) | Commands::Rerun(_)) {
+                bail!("❌ command #{} can not be rerun", args.id);
+            }
+            // 与原命令使用相同的全局参数，而不是本次 `lp rerun` 的
+            run(
+                &cli_context(&cli, base_context(ctx.config_path())),
+                cli.command,
+            )?;
+        }
+    }
 
     Ok(())
 }
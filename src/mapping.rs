@@ -0,0 +1,353 @@
+use regex::Regex;
+
+/// 导出时对每条记录计算的一列，`--map NAME=TEMPLATE`；
+/// 模板中 `{field|filter|...}` 引用已有的列 (time、level、module、message 或前面定义的列)，
+/// 与已有列同名时替换该列
+#[derive(Clone)]
+pub struct ColumnMap {
+    pub name: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Clone)]
+enum Part {
+    Literal(String),
+    Field { field: String, filters: Vec<Filter> },
+}
+
+/// 模板中字段的处理函数
+#[derive(Clone)]
+enum Filter {
+    /// `trunc:N` 最多保留 N 个字符
+    Trunc(usize),
+    /// `extract:REGEX` 取第一个捕获组，没有捕获组时取整个匹配，不匹配时为空
+    Extract(Regex),
+    /// `num` 转为数字，不是数字时为空
+    Num,
+    /// `div:N` / `mul:N`
+    Div(f64),
+    Mul(f64),
+    /// `round:N` 保留 N 位小数
+    Round(usize),
+    Upper,
+    Lower,
+    /// `map:a=1,b=2` 按值查表，没有对应项时为空
+    Lookup(Vec<(String, String)>),
+    /// `default:X` 为空时取 X
+    Default(String),
+}
+
+/// 列的值；数字在 json 中按数字写出
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(f64),
+}
+
+impl Value {
+    pub fn text(&self) -> String {
+        match self {
+            Value::Text(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+        }
+    }
+
+    fn number(&self) -> Option<f64> {
+        match self {
+            Value::Text(s) => s.trim().parse().ok(),
+            Value::Number(n) => Some(*n),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        matches!(self, Value::Text(s) if s.is_empty())
+    }
+}
+
+fn empty() -> Value {
+    Value::Text(String::new())
+}
+
+impl Filter {
+    fn parse(s: &str) -> Result<Self, String> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg)),
+            None => (s.trim(), None),
+        };
+        let arg = |name: &str| arg.ok_or_else(|| format!("filter `{name}` needs an argument"));
+        let number = |name: &str| {
+            let arg = arg(name)?;
+            arg.trim()
+                .parse::<f64>()
+                .map_err(|_| format!("filter `{name}` expects a number, got `{arg}`"))
+        };
+        let count = |name: &str| {
+            let arg = arg(name)?;
+            arg.trim()
+                .parse::<usize>()
+                .map_err(|_| format!("filter `{name}` expects a count, got `{arg}`"))
+        };
+
+        Ok(match name {
+            "trunc" => Filter::Trunc(count(name)?),
+            "extract" => Filter::Extract(
+                Regex::new(arg(name)?).map_err(|e| format!("invalid extract pattern: {e}"))?,
+            ),
+            "num" => Filter::Num,
+            "div" => {
+                let n = number(name)?;
+                if n == 0.0 {
+                    return Err("filter `div` by zero".to_string());
+                }
+                Filter::Div(n)
+            }
+            "mul" => Filter::Mul(number(name)?),
+            "round" => Filter::Round(count(name)?),
+            "upper" => Filter::Upper,
+            "lower" => Filter::Lower,
+            "map" => Filter::Lookup(
+                arg(name)?
+                    .split(',')
+                    .map(|pair| {
+                        pair.split_once('=')
+                            .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                            .ok_or_else(|| format!("invalid map entry `{pair}`, expected `k=v`"))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            "default" => Filter::Default(arg(name)?.to_string()),
+            _ => return Err(format!("unknown filter `{name}`")),
+        })
+    }
+
+    fn apply(&self, value: Value) -> Value {
+        let numeric = |value: &Value, f: &dyn Fn(f64) -> f64| {
+            value.number().map_or_else(empty, |n| Value::Number(f(n)))
+        };
+        match self {
+            Filter::Trunc(n) => Value::Text(value.text().chars().take(*n).collect()),
+            Filter::Extract(re) => {
+                let text = value.text();
+                let matched = re
+                    .captures(&text)
+                    .and_then(|caps| caps.get(1).or_else(|| caps.get(0)))
+                    .map(|m| m.as_str().to_string());
+                Value::Text(matched.unwrap_or_default())
+            }
+            Filter::Num => numeric(&value, &|n| n),
+            Filter::Div(d) => numeric(&value, &|n| n / d),
+            Filter::Mul(m) => numeric(&value, &|n| n * m),
+            Filter::Round(digits) => {
+                let scale = 10f64.powi(*digits as i32);
+                numeric(&value, &|n| (n * scale).round() / scale)
+            }
+            Filter::Upper => Value::Text(value.text().to_uppercase()),
+            Filter::Lower => Value::Text(value.text().to_lowercase()),
+            Filter::Lookup(table) => {
+                let text = value.text();
+                match table.iter().find(|(k, _)| *k == text) {
+                    Some((_, v)) => v
+                        .parse()
+                        .map_or_else(|_| Value::Text(v.clone()), Value::Number),
+                    None => empty(),
+                }
+            }
+            Filter::Default(default) if value.is_empty() => Value::Text(default.clone()),
+            Filter::Default(_) => value,
+        }
+    }
+}
+
+/// 拆分 `{...}` 内的内容：按未转义的 `|` 分段，`\` 转义下一个字符
+fn split_field(s: &str) -> Vec<String> {
+    let mut segments = vec![String::new()];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => segments
+                .last_mut()
+                .unwrap()
+                .extend(chars.next().map(|next| match next {
+                    // 正则中的转义 (如 `\d`) 原样保留
+                    '|' | '}' | '{' => next.to_string(),
+                    _ => format!("\\{next}"),
+                })),
+            '|' => segments.push(String::new()),
+            _ => segments.last_mut().unwrap().push(c),
+        }
+    }
+    segments
+}
+
+/// 解析 `--map NAME=TEMPLATE`
+pub fn parse_column_map(s: &str) -> Result<ColumnMap, String> {
+    let (name, template) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid map `{s}`, expected `name=template`"))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("invalid map `{s}`, column name is empty"));
+    }
+
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => literal.extend(chars.next()),
+            '{' => {
+                let mut field = String::new();
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            field.push(c);
+                            field.extend(chars.next());
+                        }
+                        '}' => {
+                            closed = true;
+                            break;
+                        }
+                        _ => field.push(c),
+                    }
+                }
+                if !closed {
+                    return Err(format!("invalid map `{s}`, missing `}}`"));
+                }
+
+                let mut segments = split_field(&field).into_iter();
+                let field = segments.next().unwrap_or_default().trim().to_string();
+                if field.is_empty() {
+                    return Err(format!("invalid map `{s}`, empty field name"));
+                }
+                let filters = segments
+                    .map(|f| Filter::parse(&f))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !literal.is_empty() {
+                    parts.push(Part::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(Part::Field { field, filters });
+            }
+            _ => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(Part::Literal(literal));
+    }
+
+    Ok(ColumnMap {
+        name: name.to_string(),
+        parts,
+    })
+}
+
+impl ColumnMap {
+    /// 按 `columns` / `values` 中已有的列计算，引用不存在的列时报错
+    pub fn apply(&self, columns: &[String], values: &[Value]) -> Result<Value, String> {
+        let mut results = self.parts.iter().map(|part| match part {
+            Part::Literal(s) => Ok(Value::Text(s.clone())),
+            Part::Field { field, filters } => {
+                let i = columns
+                    .iter()
+                    .position(|c| c == field)
+                    .ok_or_else(|| format!("unknown column `{field}` in map `{}`", self.name))?;
+                Ok(filters
+                    .iter()
+                    .fold(values[i].clone(), |value, filter| filter.apply(value)))
+            }
+        });
+
+        // 只有一个字段时保留其类型，否则拼接为文本
+        if self.parts.len() == 1 {
+            return results.next().unwrap_or_else(|| Ok(empty()));
+        }
+        let mut text = String::new();
+        for value in results {
+            text.push_str(&value?.text());
+        }
+        Ok(Value::Text(text))
+    }
+}
+
+/// 导出的表头：基础列之后依次追加映射出的新列；映射引用了此前不存在的列时报错
+pub fn mapped_columns(base: &[&str], maps: &[ColumnMap]) -> Result<Vec<String>, String> {
+    let mut columns = base.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    for map in maps {
+        for part in &map.parts {
+            if let Part::Field { field, .. } = part
+                && !columns.contains(field)
+            {
+                return Err(format!("unknown column `{field}` in map `{}`", map.name));
+            }
+        }
+        if !columns.contains(&map.name) {
+            columns.push(map.name.clone());
+        }
+    }
+    Ok(columns)
+}
+
+/// 对一条记录应用所有映射，`values` 与 `base` 列一一对应，结果与 [`mapped_columns`] 一一对应
+pub fn apply_maps(
+    base: &[&str],
+    values: Vec<Value>,
+    maps: &[ColumnMap],
+) -> Result<Vec<Value>, String> {
+    let mut columns = base.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+    let mut values = values;
+    for map in maps {
+        let value = map.apply(&columns, &values)?;
+        match columns.iter().position(|c| *c == map.name) {
+            Some(i) => values[i] = value,
+            None => {
+                columns.push(map.name.clone());
+                values.push(value);
+            }
+        }
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_map() {
+        let base = ["level", "message"];
+        let maps = [
+            "mem_gb={message|extract:mem (\\d+)MB|div:1024|round:2}",
+            "message={message|trunc:8}",
+            "severity_num={level|map:info=2,error=4|default:0}",
+            "tag=[{level|upper}] {mem_gb}",
+        ]
+        .map(|s| parse_column_map(s).unwrap());
+        assert_eq!(
+            mapped_columns(&base, &maps).unwrap(),
+            ["level", "message", "mem_gb", "severity_num", "tag"]
+        );
+
+        let row = |level: &str, message: &str| {
+            apply_maps(
+                &base,
+                vec![Value::Text(level.into()), Value::Text(message.into())],
+                &maps,
+            )
+            .unwrap()
+        };
+        let values = row("error", "used mem 2048MB of 4096MB");
+        assert_eq!(values[1], Value::Text("used mem".into()));
+        assert_eq!(values[2], Value::Number(2.0));
+        assert_eq!(values[3], Value::Number(4.0));
+        assert_eq!(values[4], Value::Text("[ERROR] 2".into()));
+
+        let values = row("debug", "idle");
+        assert_eq!(values[2], Value::Text(String::new()));
+        assert_eq!(values[3], Value::Text("0".into()));
+
+        assert!(parse_column_map("x={message|nope}").is_err());
+        assert!(parse_column_map("x={message").is_err());
+        let unknown = parse_column_map("x={missing}").unwrap();
+        assert!(mapped_columns(&base, &[unknown]).is_err());
+    }
+}
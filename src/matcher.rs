@@ -0,0 +1,880 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+};
+
+use aho_corasick::AhoCorasick;
+use anyhow::{Ok, Result, anyhow};
+use clap::Args;
+use regex::RegexSet;
+
+use crate::{
+    context::AppContext,
+    expr::Expr,
+    record::parse_line,
+    time::{TimeRange, parse_timestamp},
+};
+
+/// 常见的繁体 -> 简体字对照，用于 `--variants` 模糊匹配
+const TRAD_TO_SIMP: &[(char, char)] = &[
+    ('錯', '错'),
+    ('誤', '误'),
+    ('連', '连'),
+    ('線', '线'),
+    ('網', '网'),
+    ('絡', '络'),
+    ('時', '时'),
+    ('間', '间'),
+    ('記', '记'),
+    ('錄', '录'),
+    ('憶', '忆'),
+    ('體', '体'),
+    ('發', '发'),
+    ('請', '请'),
+    ('務', '务'),
+    ('異', '异'),
+    ('啟', '启'),
+    ('動', '动'),
+    ('斷', '断'),
+    ('開', '开'),
+    ('關', '关'),
+    ('閉', '闭'),
+    ('檔', '档'),
+    ('實', '实'),
+    ('據', '据'),
+    ('數', '数'),
+    ('庫', '库'),
+    ('載', '载'),
+    ('敗', '败'),
+    ('處', '处'),
+    ('報', '报'),
+    ('讀', '读'),
+    ('寫', '写'),
+    ('執', '执'),
+    ('應', '应'),
+    ('響', '响'),
+    ('機', '机'),
+    ('設', '设'),
+    ('備', '备'),
+    ('統', '统'),
+    ('計', '计'),
+    ('態', '态'),
+    ('狀', '状'),
+    ('離', '离'),
+    ('復', '复'),
+    ('創', '创'),
+    ('認', '认'),
+    ('證', '证'),
+    ('權', '权'),
+    ('資', '资'),
+    ('測', '测'),
+    ('試', '试'),
+    ('驗', '验'),
+    ('傳', '传'),
+    ('輸', '输'),
+    ('協', '协'),
+    ('議', '议'),
+    ('點', '点'),
+    ('擊', '击'),
+    ('鍵', '键'),
+    ('碼', '码'),
+    ('號', '号'),
+    ('類', '类'),
+    ('參', '参'),
+    ('當', '当'),
+    ('標', '标'),
+    ('準', '准'),
+    ('確', '确'),
+    ('與', '与'),
+    ('為', '为'),
+    ('無', '无'),
+    ('個', '个'),
+    ('們', '们'),
+    ('來', '来'),
+    ('後', '后'),
+    ('會', '会'),
+    ('還', '还'),
+    ('過', '过'),
+    ('進', '进'),
+    ('運', '运'),
+    ('續', '续'),
+    ('結', '结'),
+    ('節', '节'),
+    ('單', '单'),
+    ('頁', '页'),
+    ('圖', '图'),
+    ('檢', '检'),
+    ('內', '内'),
+    ('說', '说'),
+    ('變', '变'),
+    ('換', '换'),
+    ('導', '导'),
+    ('長', '长'),
+    ('調', '调'),
+    ('產', '产'),
+    ('現', '现'),
+    ('轉', '转'),
+    ('區', '区'),
+    ('塊', '块'),
+    ('隊', '队'),
+    ('緩', '缓'),
+    ('衝', '冲'),
+    ('盤', '盘'),
+    ('儲', '储'),
+    ('獲', '获'),
+    ('鏈', '链'),
+    ('層', '层'),
+    ('級', '级'),
+    ('屬', '属'),
+    ('電', '电'),
+    ('腦', '脑'),
+];
+
+static VARIANTS: LazyLock<HashMap<char, char>> =
+    LazyLock::new(|| TRAD_TO_SIMP.iter().copied().collect());
+
+/// cl/rl 共用的匹配参数
+#[derive(Args)]
+pub struct MatchArgs {
+    /// 需要过滤的关键字
+    #[arg(short, long)]
+    pub filters: Option<Vec<String>>,
+
+    /// 使用配置中的命名关键字集合，可指定多个，与 `-f` 一起使用时合并
+    #[arg(long, conflicts_with = "expr")]
+    pub preset: Vec<String>,
+
+    /// 匹配常见的简繁体变体
+    #[arg(long, default_value_t = false)]
+    pub variants: bool,
+
+    /// 近似匹配关键字，允许的最大编辑距离 (缺省为 1)
+    #[arg(long, value_name = "MAX_EDITS", num_args = 0..=1, default_missing_value = "1")]
+    pub fuzzy: Option<usize>,
+
+    /// 关键字按正则表达式匹配，如 `tid: \d{4,}`
+    #[arg(long, default_value_t = false, conflicts_with = "fuzzy")]
+    pub regex: bool,
+
+    /// 关键字布尔表达式，代替 `-f`，如 `(error AND ModelServer) OR "cpu usage"`
+    #[arg(long, conflicts_with_all = ["filters", "fuzzy", "regex"])]
+    pub expr: Option<String>,
+
+    /// 只匹配这些级别的行，如 info,warn,error；未指定关键字时只按级别匹配
+    #[arg(long, value_delimiter = ',')]
+    pub level: Vec<String>,
+
+    /// 只匹配这些模块的行，按 `[module]` 字段精确匹配，不会命中消息中的同名文字
+    #[arg(long, value_delimiter = ',')]
+    pub module: Vec<String>,
+
+    /// 排除这些模块的行，按 `[module]` 字段精确匹配
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_module: Vec<String>,
+
+    #[command(flatten)]
+    pub time_range: TimeRange,
+}
+
+impl MatchArgs {
+    /// 要匹配的关键字：指定了 `-f` 时使用之，指定了 `--preset` 时为各集合与 `-f` 去重合并，
+    /// 指定了 `--expr` 时为该表达式本身，
+    /// 只按级别等条件匹配时为空，否则为 `command` 的默认关键字
+    pub fn keywords(&self, ctx: &AppContext, command: &str) -> Result<Vec<String>> {
+        if let Some(expr) = &self.expr {
+            return Ok(vec![expr.clone()]);
+        }
+        if !self.preset.is_empty() {
+            let mut keywords = Vec::new();
+            for name in &self.preset {
+                keywords.extend(ctx.preset(name)?);
+            }
+            keywords.extend(self.filters.iter().flatten().cloned());
+            let mut seen = HashSet::new();
+            keywords.retain(|k| seen.insert(k.clone()));
+            return Ok(keywords);
+        }
+
+        match &self.filters {
+            Some(filters) => Ok(filters.clone()),
+            None if self.has_fields() => Ok(Vec::new()),
+            None => ctx.default_filters(command),
+        }
+    }
+
+    /// 是否指定了级别、模块、时间等结构化条件
+    fn has_fields(&self) -> bool {
+        !(self.level.is_empty()
+            && self.module.is_empty()
+            && self.exclude_module.is_empty()
+            && self.time_range.is_unbounded())
+    }
+
+    pub fn matcher(&self, filters: &[String]) -> Result<Matcher> {
+        let matcher = if let Some(expr) = &self.expr {
+            Matcher::expr(expr, self.variants)?
+        } else if self.regex {
+            Matcher::regex(filters, self.variants)?
+        } else {
+            match self.fuzzy {
+                Some(max_edits) => Matcher::fuzzy(filters, max_edits, self.variants),
+                None => Matcher::new(filters, self.variants)?,
+            }
+        };
+
+        Ok(matcher
+            .with_levels(&self.level)
+            .with_modules(&self.module, &self.exclude_module)
+            .with_time_range(self.time_range))
+    }
+}
+
+/// 关键字匹配器
+pub enum KeywordMatcher {
+    /// 逐个关键字 `contains`，适用于纯 ASCII 关键字
+    Plain(Vec<String>),
+
+    /// 基于 UTF-8 字节的多模式匹配，可选简繁体归一
+    MultiPattern {
+        ac: AhoCorasick,
+        filters: Vec<String>,
+        variants: bool,
+    },
+
+    /// 基于编辑距离的近似匹配，容忍拼写错误、截断等细微差异
+    Fuzzy {
+        filters: Vec<String>,
+        patterns: Vec<Vec<char>>,
+        max_edits: usize,
+        variants: bool,
+    },
+
+    /// 正则表达式匹配
+    Regex {
+        set: RegexSet,
+        filters: Vec<String>,
+        variants: bool,
+    },
+
+    /// 关键字布尔表达式，整个表达式算作一个关键字
+    Expr {
+        expr: Expr,
+        filters: Vec<String>,
+        variants: bool,
+    },
+}
+
+impl KeywordMatcher {
+    pub fn new(filters: &[String], variants: bool) -> Result<Self> {
+        if !variants && filters.iter().all(|s| s.is_ascii()) {
+            return Ok(KeywordMatcher::Plain(filters.to_vec()));
+        }
+
+        let patterns = filters.iter().map(|s| {
+            if variants {
+                fold_variants(s)
+            } else {
+                s.clone()
+            }
+        });
+        let ac = AhoCorasick::new(patterns)?;
+
+        Ok(KeywordMatcher::MultiPattern {
+            ac,
+            filters: filters.to_vec(),
+            variants,
+        })
+    }
+
+    pub fn fuzzy(filters: &[String], max_edits: usize, variants: bool) -> Self {
+        let patterns = filters
+            .iter()
+            .map(|s| {
+                if variants {
+                    fold_variants(s).chars().collect()
+                } else {
+                    s.chars().collect()
+                }
+            })
+            .collect();
+
+        KeywordMatcher::Fuzzy {
+            filters: filters.to_vec(),
+            patterns,
+            max_edits,
+            variants,
+        }
+    }
+
+    pub fn regex(filters: &[String], variants: bool) -> Result<Self> {
+        let patterns = filters.iter().map(|s| {
+            if variants {
+                fold_variants(s)
+            } else {
+                s.clone()
+            }
+        });
+        let set = RegexSet::new(patterns).map_err(|e| anyhow!("❌ invalid regex filter: {e}"))?;
+
+        Ok(KeywordMatcher::Regex {
+            set,
+            filters: filters.to_vec(),
+            variants,
+        })
+    }
+
+    pub fn expr(source: &str, variants: bool) -> Result<Self> {
+        let mut expr = Expr::parse(source)?;
+        if variants {
+            expr = expr.map_terms(&|term| fold_variants(&term));
+        }
+
+        Ok(KeywordMatcher::Expr {
+            expr,
+            filters: vec![source.to_string()],
+            variants,
+        })
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        match self {
+            KeywordMatcher::Plain(filters) => contains_keyword(line, filters),
+            KeywordMatcher::MultiPattern { ac, variants, .. } => {
+                if *variants && !line.is_ascii() {
+                    ac.is_match(fold_variants(line).as_bytes())
+                } else {
+                    ac.is_match(line.as_bytes())
+                }
+            }
+            KeywordMatcher::Fuzzy {
+                patterns,
+                max_edits,
+                variants,
+                ..
+            } => {
+                let line = fuzzy_haystack(line, *variants);
+                patterns
+                    .iter()
+                    .any(|p| fuzzy_contains(&line, p, *max_edits))
+            }
+            KeywordMatcher::Regex { set, variants, .. } => {
+                if *variants && !line.is_ascii() {
+                    set.is_match(&fold_variants(line))
+                } else {
+                    set.is_match(line)
+                }
+            }
+            KeywordMatcher::Expr { expr, variants, .. } => {
+                if *variants && !line.is_ascii() {
+                    let line = fold_variants(line);
+                    expr.eval(&|term| line.contains(term))
+                } else {
+                    expr.eval(&|term| line.contains(term))
+                }
+            }
+        }
+    }
+
+    pub fn filters(&self) -> &[String] {
+        match self {
+            KeywordMatcher::Plain(filters)
+            | KeywordMatcher::MultiPattern { filters, .. }
+            | KeywordMatcher::Fuzzy { filters, .. }
+            | KeywordMatcher::Regex { filters, .. }
+            | KeywordMatcher::Expr { filters, .. } => filters,
+        }
+    }
+
+    /// 返回该行命中的关键字下标，每个关键字最多出现一次
+    pub fn matched_filters(&self, line: &str) -> Vec<usize> {
+        match self {
+            KeywordMatcher::Plain(filters) => filters
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| line.contains(s.as_str()))
+                .map(|(i, _)| i)
+                .collect(),
+            KeywordMatcher::MultiPattern { ac, variants, .. } => {
+                let folded;
+                let haystack = if *variants && !line.is_ascii() {
+                    folded = fold_variants(line);
+                    folded.as_bytes()
+                } else {
+                    line.as_bytes()
+                };
+
+                let mut ids = ac
+                    .find_overlapping_iter(haystack)
+                    .map(|m| m.pattern().as_usize())
+                    .collect::<Vec<_>>();
+                ids.sort_unstable();
+                ids.dedup();
+                ids
+            }
+            KeywordMatcher::Fuzzy {
+                patterns,
+                max_edits,
+                variants,
+                ..
+            } => {
+                let line = fuzzy_haystack(line, *variants);
+                patterns
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| fuzzy_contains(&line, p, *max_edits))
+                    .map(|(i, _)| i)
+                    .collect()
+            }
+            KeywordMatcher::Regex { set, variants, .. } => {
+                if *variants && !line.is_ascii() {
+                    set.matches(&fold_variants(line)).into_iter().collect()
+                } else {
+                    set.matches(line).into_iter().collect()
+                }
+            }
+            KeywordMatcher::Expr { .. } => {
+                if self.is_match(line) {
+                    vec![0]
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+}
+
+/// 行匹配器：关键字与级别等结构化条件同时满足才算命中
+pub struct Matcher {
+    keywords: Option<KeywordMatcher>,
+    levels: Vec<String>,
+    modules: Vec<String>,
+    exclude_modules: Vec<String>,
+    time_range: TimeRange,
+}
+
+impl Matcher {
+    fn with_keywords(keywords: Option<KeywordMatcher>) -> Self {
+        Matcher {
+            keywords,
+            levels: Vec::new(),
+            modules: Vec::new(),
+            exclude_modules: Vec::new(),
+            time_range: TimeRange::default(),
+        }
+    }
+
+    /// 关键字为空时不按关键字过滤
+    fn from_keywords(
+        filters: &[String],
+        keywords: impl FnOnce() -> Result<KeywordMatcher>,
+    ) -> Result<Self> {
+        let keywords = if filters.is_empty() {
+            None
+        } else {
+            Some(keywords()?)
+        };
+
+        Ok(Matcher::with_keywords(keywords))
+    }
+
+    pub fn new(filters: &[String], variants: bool) -> Result<Self> {
+        Self::from_keywords(filters, || KeywordMatcher::new(filters, variants))
+    }
+
+    pub fn fuzzy(filters: &[String], max_edits: usize, variants: bool) -> Self {
+        Matcher::with_keywords(
+            (!filters.is_empty()).then(|| KeywordMatcher::fuzzy(filters, max_edits, variants)),
+        )
+    }
+
+    pub fn regex(filters: &[String], variants: bool) -> Result<Self> {
+        Self::from_keywords(filters, || KeywordMatcher::regex(filters, variants))
+    }
+
+    pub fn expr(source: &str, variants: bool) -> Result<Self> {
+        Ok(Matcher::with_keywords(Some(KeywordMatcher::expr(
+            source, variants,
+        )?)))
+    }
+
+    /// 只匹配这些级别 (不区分大小写) 的行
+    pub fn with_levels(mut self, levels: &[String]) -> Self {
+        self.levels = levels.to_vec();
+        self
+    }
+
+    /// 只匹配 `modules` 中的模块，排除 `exclude_modules` 中的模块 (区分大小写)
+    pub fn with_modules(mut self, modules: &[String], exclude_modules: &[String]) -> Self {
+        self.modules = modules.to_vec();
+        self.exclude_modules = exclude_modules.to_vec();
+        self
+    }
+
+    /// 只匹配行首时间戳在 `time_range` 内的行
+    pub fn with_time_range(mut self, time_range: TimeRange) -> Self {
+        self.time_range = time_range;
+        self
+    }
+
+    /// 没有任何关键字与结构化条件，匹配所有行
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_none() && !self.has_fields()
+    }
+
+    fn has_fields(&self) -> bool {
+        !(self.levels.is_empty()
+            && self.modules.is_empty()
+            && self.exclude_modules.is_empty()
+            && self.time_range.is_unbounded())
+    }
+
+    /// 级别、模块、时间等结构化条件是否满足；无法解析为标准日志行时，
+    /// 只有排除条件的情况下视为满足，否则视为不满足
+    fn fields_match(&self, line: &str) -> bool {
+        if !self.has_fields() {
+            return true;
+        }
+
+        let Some(record) = parse_line(line) else {
+            return self.levels.is_empty()
+                && self.modules.is_empty()
+                && self.time_range.is_unbounded();
+        };
+
+        (self.time_range.is_unbounded()
+            || parse_timestamp(record.time).is_some_and(|ms| self.time_range.contains(ms)))
+            && (self.levels.is_empty()
+                || self
+                    .levels
+                    .iter()
+                    .any(|level| level.eq_ignore_ascii_case(record.level)))
+            && (self.modules.is_empty() || self.modules.iter().any(|m| m == record.module))
+            && !self.exclude_modules.iter().any(|m| m == record.module)
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        self.fields_match(line)
+            && self
+                .keywords
+                .as_ref()
+                .is_none_or(|keywords| keywords.is_match(line))
+    }
+
+    pub fn filters(&self) -> &[String] {
+        self.keywords
+            .as_ref()
+            .map_or(&[], |keywords| keywords.filters())
+    }
+
+    /// 返回该行命中的关键字下标，每个关键字最多出现一次
+    pub fn matched_filters(&self, line: &str) -> Vec<usize> {
+        match &self.keywords {
+            Some(keywords) if self.fields_match(line) => keywords.matched_filters(line),
+            _ => Vec::new(),
+        }
+    }
+
+    /// 按 `keep` 判断该行是否需要保留
+    pub fn keep_line(&self, line: &str, keep: bool) -> bool {
+        match &self.keywords {
+            Some(KeywordMatcher::Plain(filters)) if !keep && !self.has_fields() => {
+                filter_keyword(line, filters)
+            }
+            _ => self.is_match(line) == keep,
+        }
+    }
+}
+
+pub fn fold_variants(s: &str) -> String {
+    s.chars()
+        .map(|c| VARIANTS.get(&c).copied().unwrap_or(c))
+        .collect()
+}
+
+fn fuzzy_haystack(line: &str, variants: bool) -> Vec<char> {
+    if variants {
+        fold_variants(line).chars().collect()
+    } else {
+        line.chars().collect()
+    }
+}
+
+/// 判断 `line` 中是否存在与 `pattern` 编辑距离不超过 `max_edits` 的子串 (Sellers 算法)，
+/// 编辑距离至多取关键字长度的一半，避免短关键字匹配任意内容
+fn fuzzy_contains(line: &[char], pattern: &[char], max_edits: usize) -> bool {
+    let m = pattern.len();
+    if m == 0 {
+        return true;
+    }
+    let max_edits = max_edits.min((m - 1) / 2);
+
+    let mut prev = (0..=m).collect::<Vec<_>>();
+    let mut cur = vec![0; m + 1];
+    for &c in line {
+        for j in 1..=m {
+            let cost = usize::from(pattern[j - 1] != c);
+            cur[j] = (prev[j - 1] + cost).min(prev[j] + 1).min(cur[j - 1] + 1);
+        }
+        if cur[m] <= max_edits {
+            return true;
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    false
+}
+
+pub fn contains_keyword(line: &str, filters: &[String]) -> bool {
+    filters.iter().any(|s| line.contains(s))
+}
+
+pub fn filter_keyword(line: &str, filters: &[String]) -> bool {
+    filters.iter().all(|s| !line.contains(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn filters(keywords: &[&str]) -> Vec<String> {
+        keywords.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_multi_pattern_chinese() {
+        let filters = filters(&["连接超时", "内存不足", "tid:"]);
+        let matcher = Matcher::new(&filters, false).unwrap();
+        assert!(matches!(
+            matcher.keywords,
+            Some(KeywordMatcher::MultiPattern { .. })
+        ));
+
+        assert!(matcher.is_match("[2026-01-06 10:29:10.765] [error] [Global]  数据库连接超时"));
+        assert!(matcher.is_match("[2026-01-06 10:22:50.306] [info] [Global]  tid: 17916"));
+        assert!(!matcher.is_match("[2026-01-06 10:29:10.765] [error] [Global]  資料庫連接超時"));
+        assert!(!matcher.is_match("[2026-01-06 10:29:09.814] [info] [ModelServer]  模型加载完成"));
+    }
+
+    #[test]
+    fn test_variants() {
+        let filters = filters(&["连接超时", "錯誤"]);
+        let matcher = Matcher::new(&filters, true).unwrap();
+
+        assert!(matcher.is_match("[2026-01-06 10:29:10.765] [error] [Global]  資料庫連接超時"));
+        assert!(matcher.is_match("[2026-01-06 10:29:10.765] [error] [Global]  数据库连接超时"));
+        assert!(matcher.is_match("[2026-01-06 10:29:10.765] [error] [Global]  发生错误"));
+        assert!(!matcher.keep_line(
+            "[2026-01-06 10:29:10.765] [error] [Global]  發生錯誤",
+            false
+        ));
+        assert!(matcher.keep_line(
+            "[2026-01-06 10:29:09.814] [info] [ModelServer]  模型加載完成",
+            false
+        ));
+    }
+
+    #[test]
+    fn test_regex() {
+        let patterns = filters(&[r"tid: \d{4,}", r"^\[[^\]]+\] \[error\]"]);
+        let matcher = Matcher::regex(&patterns, false).unwrap();
+
+        assert!(matcher.is_match("[2026-01-06 10:22:50.306] [info] [Global]  tid: 17916, start"));
+        assert!(!matcher.is_match("[2026-01-06 10:22:50.306] [info] [Global]  tid: 179, start"));
+        assert!(!matcher.is_match("[2026-01-06 10:22:50.306] [info] [Global]  pid: 17916"));
+        assert_eq!(
+            matcher.matched_filters("[2026-01-06 10:29:10.765] [error] [Global]  tid: 12992"),
+            vec![0, 1]
+        );
+        assert!(!matcher.is_match("    at [error] handler"));
+
+        assert!(Matcher::regex(&filters(&["tid: ("]), false).is_err());
+    }
+
+    #[test]
+    fn test_plain_equals_multi_pattern() {
+        let filters = filters(&["tid:", "pid:", "cpu usage"]);
+        let plain = Matcher::new(&filters, false).unwrap();
+        let multi = Matcher::new(&filters, true).unwrap();
+        assert!(matches!(plain.keywords, Some(KeywordMatcher::Plain(_))));
+
+        let lines = [
+            "[2026-01-06 10:22:50.306] [info] [Global]  tid: 17916, start: 0x7ff93b051b70",
+            "[2026-01-06 10:29:10.765] [info] [Global]  cpu usage: 5.83%, memory usage: 0.35%",
+            "[2026-01-06 10:29:09.814] [info] [ModelServer]  generateAllGltfModel called",
+        ];
+        for line in lines {
+            assert_eq!(plain.is_match(line), multi.is_match(line));
+            assert_eq!(plain.keep_line(line, false), multi.keep_line(line, false));
+        }
+    }
+
+    #[test]
+    fn test_matched_filters() {
+        let filters = filters(&["tid:", "连接超时", "超时"]);
+        let line = "[2026-01-06 10:29:10.765] [error] [Global]  tid: 12, 連接超時";
+        let matcher = Matcher::new(&filters, true).unwrap();
+        assert_eq!(matcher.matched_filters(line), vec![0, 1, 2]);
+
+        let matcher = Matcher::new(&filters, false).unwrap();
+        assert_eq!(matcher.matched_filters(line), vec![0]);
+    }
+
+    #[test]
+    fn test_fuzzy() {
+        let keywords = filters(&["generateAllGltfModel", "连接超时"]);
+        let matcher = Matcher::fuzzy(&keywords, 2, false);
+
+        assert!(matcher.is_match("[info] [ModelServer]  generateAllGltfModel called"));
+        assert!(matcher.is_match("[info] [ModelServer]  generateAlGltfModel called"));
+        assert!(matcher.is_match("[info] [ModelServer]  generateAllGltfModels called"));
+        assert!(matcher.is_match("[info] [ModelServer]  generateAllGltfModle called"));
+        assert!(!matcher.is_match("[info] [ModelServer]  generateAllObjModel called"));
+        assert!(matcher.is_match("[error] [Global]  数据库连接超時"));
+        assert_eq!(matcher.matched_filters("[error] 连接超"), vec![1]);
+
+        let matcher = Matcher::fuzzy(&filters(&["pid:"]), 10, false);
+        assert!(matcher.is_match("pid: 12992"));
+        assert!(!matcher.is_match("cpu usage: 5.83%"));
+    }
+
+    #[test]
+    fn test_levels() {
+        let info = "[2026-01-06 10:29:09.814] [info] [ModelServer]  timeout retry";
+        let error = "[2026-01-06 10:29:10.765] [ERROR] [Global]  exception callback: timeout";
+        let message = "[2026-01-06 10:29:10.765] [info] [Global]  level [error] in message";
+
+        let matcher = Matcher::new(&[], false)
+            .unwrap()
+            .with_levels(&filters(&["warn", "error"]));
+        assert!(!matcher.is_match(info));
+        assert!(matcher.is_match(error));
+        assert!(!matcher.is_match(message));
+        assert!(!matcher.is_match("    at ModelServer::load"));
+        assert!(matcher.keep_line(info, false));
+        assert!(matcher.filters().is_empty());
+
+        let matcher = Matcher::new(&filters(&["timeout"]), false)
+            .unwrap()
+            .with_levels(&filters(&["info"]));
+        assert!(matcher.is_match(info));
+        assert!(!matcher.is_match(error));
+        assert!(matcher.matched_filters(error).is_empty());
+        assert!(!matcher.keep_line(info, false));
+        assert!(matcher.keep_line(error, false));
+    }
+
+    #[test]
+    fn test_modules() {
+        let model = "[2026-01-06 10:29:09.814] [info] [ModelServer]  load done";
+        let global = "[2026-01-06 10:29:10.765] [info] [Global]  ModelServer timeout";
+        let trace = "    at ModelServer::load (model.cpp:42)";
+
+        let matcher = Matcher::new(&[], false)
+            .unwrap()
+            .with_modules(&filters(&["ModelServer"]), &[]);
+        assert!(matcher.is_match(model));
+        assert!(!matcher.is_match(global));
+        assert!(!matcher.is_match(trace));
+
+        let matcher = Matcher::new(&filters(&["timeout"]), false)
+            .unwrap()
+            .with_modules(&[], &filters(&["Global"]));
+        assert!(!matcher.is_match(global));
+        assert!(matcher.keep_line(global, false));
+        assert!(!matcher.keep_line("[2026-01-06 10:29:11.000] [warn] [Net]  timeout", false));
+        assert!(matcher.keep_line(trace, false));
+
+        let matcher = Matcher::new(&[], false)
+            .unwrap()
+            .with_levels(&filters(&["info"]))
+            .with_modules(
+                &filters(&["Global", "ModelServer"]),
+                &filters(&["ModelServer"]),
+            );
+        assert!(!matcher.is_match(model));
+        assert!(matcher.is_match(global));
+    }
+
+    #[test]
+    fn test_time_range() {
+        let before = "[2026-01-06 09:59:59.999] [info] [Global]  tid: 1";
+        let inside = "[2026-01-06 10:29:09.814] [info] [Global]  tid: 2";
+        let after = "[2026-01-06 11:00:00.000] [info] [Global]  tid: 3";
+
+        let matcher = Matcher::new(&filters(&["tid:"]), false)
+            .unwrap()
+            .with_time_range(TimeRange {
+                since: parse_timestamp("2026-01-06 10:00"),
+                until: parse_timestamp("2026-01-06 11:00"),
+            });
+        assert!(!matcher.is_match(before));
+        assert!(matcher.is_match(inside));
+        assert!(!matcher.is_match(after));
+        assert!(!matcher.is_match("    tid: 4"));
+        assert!(matcher.keep_line(before, false));
+        assert!(!matcher.keep_line(inside, false));
+    }
+
+    #[test]
+    fn test_expr() {
+        let matcher = Matcher::expr(r#"(error AND ModelServer) OR "cpu usage""#, false).unwrap();
+        let error = "[2026-01-06 10:29:10.765] [error] [ModelServer]  load failed";
+        let cpu = "[2026-01-06 10:29:10.765] [info] [Global]  cpu usage: 5.83%";
+        let other = "[2026-01-06 10:29:10.765] [error] [Global]  timeout";
+        assert!(matcher.is_match(error));
+        assert!(matcher.is_match(cpu));
+        assert!(!matcher.is_match(other));
+        assert_eq!(matcher.matched_filters(cpu), vec![0]);
+        assert!(matcher.matched_filters(other).is_empty());
+        assert!(matcher.keep_line(other, false));
+
+        let matcher = Matcher::expr("連接 AND NOT 超时", true).unwrap();
+        assert!(matcher.is_match("[error] [Global]  数据库连接失败"));
+        assert!(!matcher.is_match("[error] [Global]  資料庫連接超時"));
+    }
+
+    /// cargo test --release bench_chinese_filters -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_chinese_filters() {
+        let filters = filters(&[
+            "连接超时",
+            "内存不足",
+            "模型加载失败",
+            "线程阻塞",
+            "磁盘空间不足",
+            "请求被拒绝",
+            "心跳丢失",
+            "证书过期",
+        ]);
+        let lines = (0..200_000)
+            .map(|i| match i % 4 {
+                0 => format!("[2026-01-06 10:29:10.765] [info] [ModelServer]  第{i}个模型加载完成"),
+                1 => {
+                    format!("[2026-01-06 10:29:10.765] [info] [Global]  cpu usage: 5.83%, id: {i}")
+                }
+                2 => format!("[2026-01-06 10:29:10.765] [warn] [Network]  请求{i}处理中，等待响应"),
+                _ => format!("[2026-01-06 10:29:10.765] [error] [Global]  数据库连接超时: {i}"),
+            })
+            .collect::<Vec<_>>();
+
+        let start = Instant::now();
+        let naive = lines
+            .iter()
+            .filter(|s| contains_keyword(s, &filters))
+            .count();
+        let naive_elapsed = start.elapsed();
+
+        for variants in [false, true] {
+            let matcher = Matcher::new(&filters, variants).unwrap();
+            let start = Instant::now();
+            let count = lines.iter().filter(|s| matcher.is_match(s)).count();
+            let elapsed = start.elapsed();
+            assert_eq!(count, naive);
+            println!("multi pattern (variants: {variants}): {elapsed:?}");
+        }
+
+        println!("contains loop: {naive_elapsed:?}, matches: {naive}");
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::File,
+    io::{self, BufRead, BufWriter, Lines, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+
+use crate::{
+    compress::open_log,
+    context::AppContext,
+    temp::InFlight,
+    timestamp::{TimestampFormat, Timestamps},
+};
+
+#[derive(Parser)]
+pub struct MergeArgs {
+    /// 要合并的日志文件，支持 `.gz`
+    #[arg(required = true, num_args = 2..)]
+    pub paths: Vec<PathBuf>,
+
+    /// 合并后的输出文件
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+/// 一条日志：带时间戳的行及其后没有时间戳的续行 (如堆栈)
+struct Entry {
+    time: i64,
+    text: String,
+}
+
+/// 按条读取日志，文件开头没有时间戳的行归为时间最早的一条，保证先输出
+struct Entries<R> {
+    lines: Lines<R>,
+    format: TimestampFormat,
+    pending: Option<Entry>,
+}
+
+impl<R: BufRead> Entries<R> {
+    fn new(reader: R, format: TimestampFormat) -> Self {
+        Entries {
+            lines: reader.lines(),
+            format,
+            pending: None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Entries<R> {
+    type Item = io::Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => return Some(Err(e)),
+                None => return self.pending.take().map(Ok),
+            };
+
+            match (self.format.parse(&line), &mut self.pending) {
+                (Some(time), _) => {
+                    let entry = self.pending.replace(Entry { time, text: line });
+                    if let Some(entry) = entry {
+                        return Some(Ok(entry));
+                    }
+                }
+                (None, Some(entry)) => {
+                    entry.text.push('\n');
+                    entry.text.push_str(&line);
+                }
+                (None, None) => {
+                    self.pending = Some(Entry {
+                        time: i64::MIN,
+                        text: line,
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// k 路归并：每次输出各文件当前最早的一条，时间相同时按文件顺序输出；
+/// 单个文件内部的顺序保持不变；各文件按自己的时间戳格式解析
+fn merge_entries<R: BufRead, W: Write>(
+    readers: Vec<(R, TimestampFormat)>,
+    output: &mut W,
+) -> Result<usize> {
+    let mut sources = readers
+        .into_iter()
+        .map(|(reader, format)| Entries::new(reader, format))
+        .collect::<Vec<_>>();
+    let mut heads = Vec::with_capacity(sources.len());
+    let mut heap = BinaryHeap::new();
+    for (i, source) in sources.iter_mut().enumerate() {
+        let head = source.next().transpose()?;
+        if let Some(entry) = &head {
+            heap.push(Reverse((entry.time, i)));
+        }
+        heads.push(head);
+    }
+
+    let mut count = 0;
+    while let Some(Reverse((_, i))) = heap.pop() {
+        let entry = heads[i].take().unwrap();
+        output.write_all(entry.text.as_bytes())?;
+        output.write_all(b"\n")?;
+        count += 1;
+
+        heads[i] = sources[i].next().transpose()?;
+        if let Some(next) = &heads[i] {
+            heap.push(Reverse((next.time, i)));
+        }
+    }
+
+    Ok(count)
+}
+
+pub fn process_merge(ctx: &AppContext, args: MergeArgs) -> Result<()> {
+    let paths = args
+        .paths
+        .into_iter()
+        .map(|path| ctx.resolve_path(path))
+        .collect::<Result<Vec<_>>>()?;
+    let output_path = ctx.resolve_path(args.output)?;
+    for path in &paths {
+        if !path.is_file() {
+            bail!("❌ {} is not a file", path.display());
+        }
+        if *path == output_path {
+            bail!("❌ output would overwrite input {}", path.display());
+        }
+    }
+
+    let timestamps = Timestamps::load(ctx)?;
+    let readers = paths
+        .iter()
+        .map(|path| Ok((open_log(path)?, timestamps.format_for(path))))
+        .collect::<io::Result<Vec<_>>>()?;
+    let partial = InFlight::register(&output_path);
+    let mut output = BufWriter::new(File::create(&output_path)?);
+    let count = merge_entries(readers, &mut output)?;
+    output.flush()?;
+    partial.commit();
+
+    println!(
+        "write merged file, path: {:?}, entries: {}",
+        output_path.display(),
+        count
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_entries() {
+        let a = "\
+[2026-01-06 10:00:00.000] [info] [A]  a1
+[2026-01-06 10:00:02.000] [error] [A]  a2
+    at a2
+";
+        let b = "\
+banner
+[2026-01-06 10:00:01.000] [info] [B]  b1
+[2026-01-06 10:00:02.000] [info] [B]  b2
+";
+        let mut output = Vec::new();
+        let readers = vec![
+            (a.as_bytes(), TimestampFormat::Bracket),
+            (b.as_bytes(), TimestampFormat::Bracket),
+        ];
+        let count = merge_entries(readers, &mut output).unwrap();
+        assert_eq!(count, 5);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\
+banner
+[2026-01-06 10:00:00.000] [info] [A]  a1
+[2026-01-06 10:00:01.000] [info] [B]  b1
+[2026-01-06 10:00:02.000] [error] [A]  a2
+    at a2
+[2026-01-06 10:00:02.000] [info] [B]  b2
+"
+        );
+    }
+}
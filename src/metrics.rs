@@ -0,0 +1,248 @@
+use std::{
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Ok, Result, bail};
+use clap::{Parser, ValueEnum};
+use rayon::prelude::*;
+
+use crate::{
+    compress::{open_log, plain_path},
+    context::AppContext,
+    exit::Failures,
+    extractor::{Extractor, metric_extractors},
+    out_name::OverwriteArgs,
+    record::{Metric, parse_line, parse_percent, parse_value},
+    subcommand::get_entries,
+    table::TableWriter,
+    time::{TimeRange, parse_timestamp},
+};
+
+/// 固定输出的列，百分比与 MB 均为数值，`--metric` 指定的指标依次追加在后面
+const HEADERS: [&str; 5] = ["time", "cpu_percent", "mem_percent", "total_mb", "used_mb"];
+
+/// 指标表格的格式
+#[derive(Clone, Copy, ValueEnum)]
+pub enum MetricsFormat {
+    Csv,
+    Xlsx,
+}
+
+impl MetricsFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            MetricsFormat::Csv => "csv",
+            MetricsFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+#[derive(Parser)]
+pub struct MetricsArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 输出格式，输出到源文件旁的 xxx_metrics.csv / xxx_metrics.xlsx
+    #[arg(short, long, value_enum, default_value = "csv")]
+    pub format: MetricsFormat,
+
+    #[command(flatten)]
+    pub time_range: TimeRange,
+
+    /// 额外生成一个 cpu/内存随时间变化的折线图工作表，仅支持 xlsx
+    #[arg(long, default_value_t = false)]
+    pub chart: bool,
+
+    /// 追加输出配置中的自定义指标，也会画入折线图
+    #[arg(short, long)]
+    pub metric: Vec<String>,
+
+    #[command(flatten)]
+    pub overwrite: OverwriteArgs,
+}
+
+/// 一行状态日志中的资源读数，如
+/// `cpu usage: 5.83%, memory usage: 0.35%, total: 65301.08MB, used: 230.32MB`
+struct MetricRow {
+    time: String,
+    cpu: Option<f64>,
+    mem: Option<f64>,
+    total_mb: Option<f64>,
+    used_mb: Option<f64>,
+    custom: Vec<Option<f64>>,
+}
+
+impl MetricRow {
+    fn cells(&self) -> Vec<String> {
+        let cell = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+        let mut cells = vec![
+            self.time.clone(),
+            cell(self.cpu),
+            cell(self.mem),
+            cell(self.total_mb),
+            cell(self.used_mb),
+        ];
+        cells.extend(self.custom.iter().map(|&v| cell(v)));
+        cells
+    }
+}
+
+/// 解析状态行，cpu、内存与自定义指标都没有读数时返回 `None`
+fn parse_metrics(line: &str, extractors: &[Extractor]) -> Option<MetricRow> {
+    let record = parse_line(line)?;
+    let cpu = parse_percent(record.message, Metric::Cpu.key());
+    let mem = parse_percent(record.message, Metric::Mem.key());
+    let custom = extractors
+        .iter()
+        .map(|e| e.extract(record.message))
+        .collect::<Vec<_>>();
+    if cpu.is_none() && mem.is_none() && custom.iter().all(Option::is_none) {
+        return None;
+    }
+
+    Some(MetricRow {
+        time: record.time.to_string(),
+        cpu,
+        mem,
+        total_mb: parse_value(record.message, "total", "MB"),
+        used_mb: parse_value(record.message, "used", "MB"),
+        custom,
+    })
+}
+
+fn metrics_path(path: &Path, format: MetricsFormat) -> PathBuf {
+    let path = plain_path(path);
+    let stem = path.file_stem().unwrap_or_default();
+    path.with_file_name(format!("{}_metrics.{}", stem.display(), format.extension()))
+}
+
+/// 边读边写，内存占用与文件大小无关
+fn extract_file(path: &Path, args: &MetricsArgs, extractors: &[Extractor]) -> Result<()> {
+    let new_path = args.overwrite.claim(&metrics_path(path, args.format))?;
+    let headers = HEADERS
+        .into_iter()
+        .map(str::to_string)
+        .chain(extractors.iter().map(|e| match e.unit() {
+            "" => e.name().to_string(),
+            unit => format!("{}_{}", e.name(), unit),
+        }))
+        .collect::<Vec<_>>();
+    let headers = headers.iter().map(String::as_str).collect::<Vec<_>>();
+    let mut writer = TableWriter::create(&new_path, &headers)?;
+    if args.chart {
+        let columns = [1, 2]
+            .into_iter()
+            .chain((HEADERS.len()..headers.len()).map(|i| i as u16))
+            .collect::<Vec<_>>();
+        let title = if extractors.is_empty() {
+            "cpu / memory usage (%)"
+        } else {
+            "metrics"
+        };
+        writer = writer.with_line_chart(title, &columns);
+    }
+    let mut rows = 0;
+    for line in open_log(path)?.lines() {
+        let Some(row) = parse_metrics(&line?, extractors) else {
+            continue;
+        };
+        if !args.time_range.is_unbounded()
+            && parse_timestamp(&row.time).is_none_or(|time| !args.time_range.contains(time))
+        {
+            continue;
+        }
+        writer.write_row(&row.cells())?;
+        rows += 1;
+    }
+    writer.finish()?;
+    println!(
+        "write metrics file, path: {:?}, rows: {}",
+        new_path.display(),
+        rows
+    );
+
+    Ok(())
+}
+
+pub fn process_metrics(ctx: &AppContext, args: MetricsArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path.clone())?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+    if args.chart && !matches!(args.format, MetricsFormat::Xlsx) {
+        bail!("❌ --chart requires --format xlsx");
+    }
+    let extractors = metric_extractors(ctx, &args.metric)?;
+
+    if path.is_dir() {
+        let failures = Failures::new(ctx);
+        get_entries(&path)
+            .par_iter()
+            .filter(|e| {
+                e.path()
+                    .extension()
+                    .is_none_or(|ext| ext != "csv" && ext != "xlsx")
+            })
+            .for_each(|e| {
+                if failures.should_stop() {
+                    return;
+                }
+                let file_path = e.path();
+                if let Err(e) = extract_file(file_path, &args, &extractors) {
+                    eprintln!("❌ metrics failed, path {:?}, reason: {}", file_path, e);
+                    failures.record(file_path, &e);
+                }
+            });
+        failures.finish()?;
+    } else {
+        extract_file(&path, &args, &extractors)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metrics() {
+        let row = parse_metrics(
+            "[2026-01-06 10:29:10.765] [info] [Global]  cpu usage: 5.83%, memory usage: 0.35%, total: 65301.08MB, used: 230.32MB",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            row.cells(),
+            [
+                "2026-01-06 10:29:10.765",
+                "5.83",
+                "0.35",
+                "65301.08",
+                "230.32"
+            ]
+        );
+
+        let row = parse_metrics(
+            "[2026-01-06 10:29:10.765] [info] [Global]  cpu usage: 12%",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(row.cells(), ["2026-01-06 10:29:10.765", "12", "", "", ""]);
+
+        assert!(
+            parse_metrics(
+                "[2026-01-06 10:29:10.765] [info] [Global]  model loaded",
+                &[]
+            )
+            .is_none()
+        );
+        assert!(parse_metrics("cpu usage: 5.83%", &[]).is_none());
+        assert_eq!(
+            metrics_path(Path::new("/var/log/app.log.gz"), MetricsFormat::Xlsx),
+            PathBuf::from("/var/log/app_metrics.xlsx")
+        );
+    }
+}
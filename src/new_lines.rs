@@ -0,0 +1,123 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Ok, Result, bail};
+use clap::Parser;
+
+use crate::{context::AppContext, record::strip_timestamp};
+
+#[derive(Parser)]
+pub struct NewLinesArgs {
+    /// 当前日志
+    pub path: PathBuf,
+
+    /// 作为基准的正常日志
+    #[arg(short, long)]
+    pub baseline: PathBuf,
+}
+
+/// 将行归一化为模板：去掉时间戳，含数字的片段 (id、耗时、地址等) 替换为 `<*>`
+pub fn template(line: &str) -> String {
+    let line = strip_timestamp(line);
+    let mut out = String::with_capacity(line.len());
+    let mut token = String::new();
+
+    let flush = |token: &mut String, out: &mut String| {
+        if token.chars().any(|c| c.is_ascii_digit()) {
+            out.push_str("<*>");
+        } else {
+            out.push_str(token);
+        }
+        token.clear();
+    };
+
+    for c in line.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            token.push(c);
+        } else {
+            flush(&mut token, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut token, &mut out);
+
+    out.trim_end().to_string()
+}
+
+fn read_log(ctx: &AppContext, path: PathBuf) -> Result<(PathBuf, String)> {
+    let path = ctx.resolve_path(path)?;
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("❌ failed to read {}", path.display()))?;
+
+    Ok((path, content))
+}
+
+pub fn process_new_lines(ctx: &AppContext, args: NewLinesArgs) -> Result<()> {
+    let (_, baseline) = read_log(ctx, args.baseline)?;
+    let (path, current) = read_log(ctx, args.path)?;
+
+    let known = baseline.lines().map(template).collect::<HashSet<_>>();
+
+    // 按首次出现的顺序输出，同时统计出现次数
+    let mut new_templates: Vec<(String, usize, usize)> = Vec::new();
+    let mut index = HashMap::<String, usize>::new();
+    for (line_no, line) in current.lines().enumerate() {
+        let template = template(line);
+        if template.is_empty() || known.contains(&template) {
+            continue;
+        }
+        match index.get(&template) {
+            Some(&i) => new_templates[i].1 += 1,
+            None => {
+                index.insert(template.clone(), new_templates.len());
+                new_templates.push((template, 1, line_no + 1));
+            }
+        }
+    }
+
+    for (template, count, line_no) in &new_templates {
+        println!("{count:>6}  L{line_no:<7} {template}");
+    }
+    println!(
+        "file: {}, new templates: {}",
+        path.display(),
+        new_templates.len()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template() {
+        assert_eq!(
+            template("[2026-01-06 10:29:10.765] [info] [Global]  cpu usage: 5.83%, used: 230.32MB"),
+            "[info] [Global]  cpu usage: <*>.<*>%, used: <*>.<*>"
+        );
+        assert_eq!(
+            template(
+                "[2026-01-06 10:29:10.765] [error] [Global]  exception callback: ERRCODE_MSOPTIMEOUT"
+            ),
+            template(
+                "[2026-01-07 08:00:00.001] [error] [Global]  exception callback: ERRCODE_MSOPTIMEOUT"
+            )
+        );
+        assert_eq!(
+            template("[info] [Worker] task 0x7ffe12 done by pid1234"),
+            "[info] [Worker] task <*> done by <*>"
+        );
+        assert_ne!(
+            template("[error] [Global]  exception callback: ERRCODE_MSOPTIMEOUT"),
+            template("[error] [Global]  exception callback: ERRCODE_NOTFOUND")
+        );
+    }
+}
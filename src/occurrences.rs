@@ -0,0 +1,158 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+
+use crate::{
+    context::AppContext,
+    exit::Failures,
+    matcher::{MatchArgs, Matcher},
+    out_name::OverwriteArgs,
+    record::parse_line,
+    subcommand::get_entries,
+    table::{print_table, write_table},
+    time::parse_timestamp,
+};
+
+#[derive(Parser)]
+pub struct OccurrencesArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub matching: MatchArgs,
+
+    /// 导出表格 (.xlsx 或 .csv)，默认打印到终端
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub overwrite: OverwriteArgs,
+}
+
+const HEADERS: [&str; 5] = ["file", "keyword", "count", "first", "last"];
+
+struct Occurrence<'a> {
+    count: usize,
+    first: Option<(i64, &'a str)>,
+    last: Option<(i64, &'a str)>,
+}
+
+pub fn process_occurrences(ctx: &AppContext, args: OccurrencesArgs) -> Result<()> {
+    let Some(filters) = args.matching.filters.clone() else {
+        bail!("❌ no keyword given, e.g. -f ERRCODE_MSOPTIMEOUT");
+    };
+    let matcher = args.matching.matcher(&filters)?;
+
+    let path = ctx.resolve_path(args.path)?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    let files = if path.is_dir() {
+        get_entries(&path)
+            .into_iter()
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        vec![path.clone()]
+    };
+
+    let failures = Failures::new(ctx);
+    let rows = files
+        .par_iter()
+        .filter_map(|file| {
+            if failures.should_stop() {
+                return None;
+            }
+            file_rows(file, &path, &filters, &matcher)
+                .inspect_err(|e| {
+                    eprintln!("❌ occurrences failed, path {:?}, reason: {}", file, e);
+                    failures.record(file, e);
+                })
+                .ok()
+        })
+        .flatten()
+        .collect::<Vec<_>>();
+
+    match args.output {
+        Some(output) => {
+            let output = args.overwrite.claim(&output)?;
+            write_table(&output, &HEADERS, &rows)?;
+            println!(
+                "write occurrences, path: {:?}, rows: {}",
+                output.display(),
+                rows.len()
+            );
+        }
+        None => print_table(&HEADERS, &rows),
+    }
+
+    failures.finish()
+}
+
+/// 一个文件中各关键字的命中数与首末时间，每个命中的关键字一行
+fn file_rows(
+    file: &Path,
+    root: &Path,
+    filters: &[String],
+    matcher: &Matcher,
+) -> Result<Vec<Vec<String>>> {
+    let content = fs::read_to_string(file)?;
+    let mut occurrences = filters
+        .iter()
+        .map(|_| Occurrence {
+            count: 0,
+            first: None,
+            last: None,
+        })
+        .collect::<Vec<_>>();
+
+    for line in content.lines() {
+        let matched = matcher.matched_filters(line);
+        if matched.is_empty() {
+            continue;
+        }
+
+        let time =
+            parse_line(line).and_then(|record| Some((parse_timestamp(record.time)?, record.time)));
+        for i in matched {
+            let occurrence = &mut occurrences[i];
+            occurrence.count += 1;
+            if let Some(time) = time {
+                if occurrence.first.is_none_or(|first| time.0 < first.0) {
+                    occurrence.first = Some(time);
+                }
+                if occurrence.last.is_none_or(|last| time.0 >= last.0) {
+                    occurrence.last = Some(time);
+                }
+            }
+        }
+    }
+
+    let display = file.strip_prefix(root).unwrap_or(file);
+    let display = if display.as_os_str().is_empty() {
+        file.display().to_string()
+    } else {
+        display.display().to_string()
+    };
+    Ok(filters
+        .iter()
+        .zip(occurrences)
+        .filter(|(_, o)| o.count > 0)
+        .map(|(filter, o)| {
+            vec![
+                display.clone(),
+                filter.clone(),
+                o.count.to_string(),
+                o.first.map_or_else(String::new, |(_, s)| s.to_string()),
+                o.last.map_or_else(String::new, |(_, s)| s.to_string()),
+            ]
+        })
+        .collect())
+}
@@ -0,0 +1,112 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    sync::Mutex,
+};
+
+struct State<W> {
+    out: W,
+    next: usize,
+    pending: BTreeMap<usize, Vec<u8>>,
+}
+
+/// 按序号顺序输出的写入器：并行处理的分块可以任意顺序提交，
+/// 只有序号连续的分块才会写出，保证输出顺序与输入一致
+pub struct OrderedWriter<W: Write> {
+    state: Mutex<State<W>>,
+}
+
+impl<W: Write> OrderedWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            state: Mutex::new(State {
+                out,
+                next: 0,
+                pending: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// 提交第 `seq` 块 (从 0 开始)，并写出所有已就绪的连续分块
+    pub fn write_chunk(&self, seq: usize, chunk: Vec<u8>) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if seq < state.next || state.pending.contains_key(&seq) {
+            return Err(io::Error::other(format!("chunk {seq} submitted twice")));
+        }
+        state.pending.insert(seq, chunk);
+
+        loop {
+            let next = state.next;
+            let Some(chunk) = state.pending.remove(&next) else {
+                break;
+            };
+            state.out.write_all(&chunk)?;
+            state.next += 1;
+        }
+
+        Ok(())
+    }
+
+    /// 结束写入，仍有未写出的分块 (序号不连续) 时返回错误
+    pub fn finish(self) -> io::Result<W> {
+        let mut state = self.state.into_inner().unwrap();
+        if let Some(seq) = state.pending.keys().next() {
+            return Err(io::Error::other(format!(
+                "chunk {} missing before chunk {seq}",
+                state.next
+            )));
+        }
+        state.out.flush()?;
+
+        Ok(state.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_ordered_writer() {
+        let writer = OrderedWriter::new(Vec::new());
+        for seq in [2, 0, 3, 1] {
+            writer
+                .write_chunk(seq, format!("chunk {seq}\n").into_bytes())
+                .unwrap();
+        }
+        assert!(writer.write_chunk(1, Vec::new()).is_err());
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            "chunk 0\nchunk 1\nchunk 2\nchunk 3\n"
+        );
+
+        let writer = OrderedWriter::new(Vec::new());
+        writer.write_chunk(1, b"late".to_vec()).unwrap();
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn test_ordered_writer_parallel() {
+        let lines = (0..1000).map(|i| format!("line {i}")).collect::<Vec<_>>();
+        let expected = lines.iter().map(|s| format!("{s}\n")).collect::<String>();
+
+        // 块大小不整除行数，最后一块只有部分行
+        for chunk_size in [1, 7, 64, 999, 1000, 4096] {
+            let writer = OrderedWriter::new(Vec::new());
+            lines
+                .par_chunks(chunk_size)
+                .enumerate()
+                .try_for_each(|(seq, chunk)| {
+                    let data = chunk.iter().map(|s| format!("{s}\n")).collect::<String>();
+                    writer.write_chunk(seq, data.into_bytes())
+                })
+                .unwrap();
+            assert_eq!(
+                String::from_utf8(writer.finish().unwrap()).unwrap(),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, bail};
+use clap::Args;
+use globset::escape;
+
+use crate::{context::AppContext, time::format_timestamp};
+
+/// 输出文件名模板支持的变量
+const VARIABLES: [&str; 5] = ["stem", "ext", "date", "preset", "profile"];
+
+/// 校验 `--out-name` 模板，如 `{stem}.{date}.clean.{ext}`、`{stem}.{preset}.{ext}`
+pub fn parse_out_name(s: &str) -> Result<String, String> {
+    if s.contains(['/', '\\']) {
+        return Err(format!("`{s}` should be a file name, not a path"));
+    }
+
+    let mut rest = s;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            return Err(format!("unclosed `{{` in `{s}`"));
+        };
+        let variable = &rest[start + 1..start + len];
+        if !VARIABLES.contains(&variable) {
+            let supported = VARIABLES.map(|v| format!("{{{v}}}")).join(", ");
+            return Err(format!(
+                "unknown variable `{{{variable}}}` in `{s}`, supported: {supported}"
+            ));
+        }
+        rest = &rest[start + len + 1..];
+    }
+
+    Ok(s.to_string())
+}
+
+/// `--out-name` 模板及其中与文件无关的变量：`{preset}` 为 `--preset` 指定的集合 (多个时以 `+` 连接)，
+/// `{profile}` 为当前使用的 profile，未指定时均为空
+pub struct OutName {
+    template: String,
+    preset: String,
+    profile: String,
+}
+
+impl OutName {
+    pub fn new(ctx: &AppContext, template: String, presets: &[String]) -> Result<Self> {
+        Ok(OutName {
+            template,
+            preset: file_safe(&presets.join("+")),
+            profile: file_safe(&ctx.profile_name()?.unwrap_or_default()),
+        })
+    }
+
+    /// 模板生成的文件名对应的 glob：`{stem}`、`{ext}` 匹配任意值 (另含没有扩展名的情况)，`{date}` 匹配任意日期，
+    /// 文件夹模式下据此跳过之前按同一模板生成的结果
+    pub fn output_globs(&self) -> Vec<String> {
+        ["\0ext", ""]
+            .map(|ext| {
+                let name = render(
+                    &self.template,
+                    &[
+                        ("stem", "\0stem"),
+                        ("ext", ext),
+                        ("date", "\0date"),
+                        ("preset", &self.preset),
+                        ("profile", &self.profile),
+                    ],
+                );
+                escape(&name)
+                    .replace("\0stem", "*")
+                    .replace("\0ext", "*")
+                    .replace("\0date", "[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]")
+            })
+            .to_vec()
+    }
+}
+
+/// 变量的值不能带入路径分隔符
+fn file_safe(value: &str) -> String {
+    value.replace(['/', '\\'], "_")
+}
+
+/// 按模板生成文件名；为空的变量连同与之相邻的一个 `.` 一起去掉，如没有扩展名时不留下末尾的 `.`
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut name = template.to_string();
+    for (variable, value) in values {
+        let placeholder = format!("{{{variable}}}");
+        if value.is_empty() {
+            name = name
+                .replace(&format!(".{placeholder}"), "")
+                .replace(&format!("{placeholder}."), "");
+        }
+        name = name.replace(&placeholder, value);
+    }
+    name
+}
+
+/// 输出文件已存在时的处理方式，默认拒绝覆盖
+#[derive(Args, Clone, Copy, Default)]
+pub struct OverwriteArgs {
+    /// 覆盖已存在的输出文件
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+
+    /// 输出文件已存在时写到带编号的新文件，如 `app_filtered_2.log`
+    #[arg(long, default_value_t = false, conflicts_with = "force")]
+    pub no_clobber: bool,
+}
+
+impl OverwriteArgs {
+    /// 实际写入的路径：`path` 不存在或指定了 `--force` 时为 `path`，`--no-clobber` 时为第一个不存在的
+    /// 带编号路径，否则报错
+    pub fn claim(&self, path: &Path) -> Result<PathBuf> {
+        if self.force || !path.exists() {
+            return Ok(path.to_path_buf());
+        }
+        if !self.no_clobber {
+            bail!(
+                "❌ {} already exists, pass --force to overwrite it or --no-clobber to write a numbered copy",
+                path.display()
+            );
+        }
+
+        Ok((2..)
+            .map(|n| numbered_path(path, n))
+            .find(|path| !path.exists())
+            .unwrap())
+    }
+}
+
+/// 在扩展名前插入编号，`.gz` 前的扩展名一并保留，如 `app_filtered.log.gz` -> `app_filtered_2.log.gz`
+fn numbered_path(path: &Path, n: usize) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let (name, gz) = match name.strip_suffix(".gz") {
+        Some(name) => (name, ".gz"),
+        None => (name.as_ref(), ""),
+    };
+    let name = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{stem}_{n}.{ext}{gz}"),
+        _ => format!("{name}_{n}{gz}"),
+    };
+
+    path.with_file_name(name)
+}
+
+/// 在扩展名前加上各命令的后缀，如 rl 的 `xxx_filtered.ext`，不同命令的结果互不覆盖
+fn suffixed_output(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default();
+    let ext = path.extension().unwrap_or_default();
+    path.with_file_name(format!(
+        "{}{suffix}{}{}",
+        stem.display(),
+        if ext.is_empty() { "" } else { "." },
+        ext.display(),
+    ))
+}
+
+/// 处理结果的输出路径：未指定模板时为源文件旁加上 `suffix` 的 `xxx{suffix}.ext`，否则按模板命名
+pub fn output_path(path: &Path, out_name: Option<&OutName>, suffix: &str) -> Result<PathBuf> {
+    let Some(out_name) = out_name else {
+        return Ok(suffixed_output(path, suffix));
+    };
+
+    let new_path = path.with_file_name(template_name(out_name, path)?);
+    if new_path == path {
+        bail!(
+            "❌ output name `{}` would overwrite {}",
+            out_name.template,
+            path.display()
+        );
+    }
+
+    Ok(new_path)
+}
+
+/// 按模板生成 `path` 对应的文件名，`{date}` 为当天日期 (UTC)
+pub fn template_name(out_name: &OutName, path: &Path) -> Result<String> {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+    let date = &format_timestamp(now)[..10];
+
+    Ok(render(
+        &out_name.template,
+        &[
+            ("stem", &stem),
+            ("ext", &ext),
+            ("date", date),
+            ("preset", &out_name.preset),
+            ("profile", &out_name.profile),
+        ],
+    ))
+}
+
+/// 把 `root` 下的输出路径放到 `dir` 下相同的相对位置，处理文件夹时保留其目录结构；
+/// 不在 `root` 下时 (如模板生成了绝对路径) 只保留文件名
+pub fn mirror_path(path: &Path, root: &Path, dir: &Path) -> PathBuf {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    if relative.is_absolute() {
+        dir.join(relative.file_name().unwrap_or_default())
+    } else {
+        dir.join(relative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_out_name() {
+        let values = [
+            ("stem", "app"),
+            ("ext", "log"),
+            ("date", "2026-01-06"),
+            ("preset", "noise+network"),
+            ("profile", ""),
+        ];
+        assert_eq!(
+            render("{stem}.{date}.clean.{ext}", &values),
+            "app.2026-01-06.clean.log"
+        );
+        assert_eq!(
+            render("{stem}.{preset}.{profile}.{ext}", &values),
+            "app.noise+network.log"
+        );
+        assert_eq!(
+            render("{profile}.{stem}.clean.{ext}", &values),
+            "app.clean.log"
+        );
+        assert_eq!(
+            render("{stem}.clean.{ext}", &[("stem", "app"), ("ext", "")]),
+            "app.clean"
+        );
+        assert_eq!(file_safe("a/b\\c"), "a_b_c");
+
+        assert!(parse_out_name("{stem}.{date}.clean.{ext}").is_ok());
+        assert!(parse_out_name("{stem}.{preset}.{profile}.{ext}").is_ok());
+        assert!(parse_out_name("{stem}.{filter}.{ext}").is_err());
+        assert!(parse_out_name("{stem.log").is_err());
+        assert!(parse_out_name("out/{stem}.log").is_err());
+
+        let path = Path::new("/var/log/app.log");
+        assert_eq!(
+            output_path(path, None, "_filtered").unwrap(),
+            PathBuf::from("/var/log/app_filtered.log")
+        );
+        let out_name = OutName {
+            template: "{stem}.{ext}".to_string(),
+            preset: String::new(),
+            profile: String::new(),
+        };
+        assert!(output_path(path, Some(&out_name), "_filtered").is_err());
+        let out_name = OutName {
+            template: "{stem}.{date}.[{preset}].{ext}".to_string(),
+            preset: "noise".to_string(),
+            profile: String::new(),
+        };
+        assert_eq!(
+            out_name.output_globs(),
+            [
+                "*.[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9].[[]noise[]].*",
+                "*.[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9].[[]noise[]]",
+            ]
+        );
+        assert_eq!(
+            numbered_path(Path::new("logs/app_filtered.log.gz"), 2),
+            PathBuf::from("logs/app_filtered_2.log.gz")
+        );
+        assert_eq!(
+            numbered_path(Path::new("logs/app.2026-01-06.csv"), 3),
+            PathBuf::from("logs/app.2026-01-06_3.csv")
+        );
+        assert_eq!(
+            numbered_path(Path::new("logs/app"), 2),
+            PathBuf::from("logs/app_2")
+        );
+
+        assert_eq!(
+            mirror_path(
+                Path::new("/var/log/a/app_filtered.log"),
+                Path::new("/var/log"),
+                Path::new("/out")
+            ),
+            PathBuf::from("/out/a/app_filtered.log")
+        );
+        assert_eq!(
+            mirror_path(path, Path::new("/srv"), Path::new("/out")),
+            PathBuf::from("/out/app.log")
+        );
+    }
+}
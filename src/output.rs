@@ -0,0 +1,37 @@
+use std::io::{self, Write};
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// `lp --format`：供脚本 (如 jq) 解析的输出格式
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// 各命令默认的文本输出
+    #[default]
+    Text,
+    /// 一个 JSON 数组
+    Json,
+    /// 每行一个 JSON 对象
+    Ndjson,
+}
+
+/// 按 `format` 输出一组记录：ndjson 时每行一个对象，否则为格式化的 JSON 数组
+pub fn print_records<T: Serialize>(format: OutputFormat, records: &[T]) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        OutputFormat::Ndjson => {
+            for record in records {
+                serde_json::to_writer(&mut stdout, record)?;
+                stdout.write_all(b"\n")?;
+            }
+        }
+        OutputFormat::Json | OutputFormat::Text => {
+            serde_json::to_writer_pretty(&mut stdout, records)?;
+            stdout.write_all(b"\n")?;
+        }
+    }
+    stdout.flush()?;
+
+    Ok(())
+}
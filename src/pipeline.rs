@@ -0,0 +1,146 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Result;
+
+use crate::{
+    matcher::Matcher,
+    record::{Boundary, read_records},
+};
+
+/// 一个处理步骤，按加入的顺序作用于每条记录
+enum Stage<'a> {
+    /// 返回 false 的记录被丢弃，不再经过后面的步骤
+    Filter(Box<dyn FnMut(&str) -> bool + 'a>),
+    Transform(Box<dyn FnMut(String) -> String + 'a>),
+}
+
+/// 一次运行中读入与写出的记录数和字节数，每条记录按以换行结尾计算
+#[derive(Debug, Default, PartialEq)]
+pub struct PipelineStats {
+    pub records_in: u64,
+    pub bytes_in: u64,
+    pub records_out: u64,
+    pub bytes_out: u64,
+}
+
+/// 不经过文件系统的流式处理，读入任意 `BufRead`，逐条过滤、转换后立即写出到 `sink`：
+///
+/// `Pipeline::new().filter(&matcher, false).transform(f).sink(writer).run(reader)`
+///
+/// 未指定 `sink` 时丢弃输出，只统计
+pub struct Pipeline<'a, W> {
+    boundary: Boundary,
+    stages: Vec<Stage<'a>>,
+    sink: W,
+}
+
+impl Pipeline<'_, io::Sink> {
+    pub fn new() -> Self {
+        Pipeline {
+            boundary: Boundary::default(),
+            stages: Vec::new(),
+            sink: io::sink(),
+        }
+    }
+}
+
+impl Default for Pipeline<'_, io::Sink> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, W: Write> Pipeline<'a, W> {
+    /// 记录的边界，默认每行一条
+    pub fn boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// 与 `rl` 一致：`keep` 为 false 时去掉匹配的记录，为 true 时只保留匹配的记录
+    pub fn filter(self, matcher: &'a Matcher, keep: bool) -> Self {
+        self.filter_with(move |record| matcher.keep_line(record, keep))
+    }
+
+    pub fn filter_with(mut self, f: impl FnMut(&str) -> bool + 'a) -> Self {
+        self.stages.push(Stage::Filter(Box::new(f)));
+        self
+    }
+
+    pub fn transform(mut self, f: impl FnMut(String) -> String + 'a) -> Self {
+        self.stages.push(Stage::Transform(Box::new(f)));
+        self
+    }
+
+    pub fn sink<S: Write>(self, sink: S) -> Pipeline<'a, S> {
+        Pipeline {
+            boundary: self.boundary,
+            stages: self.stages,
+            sink,
+        }
+    }
+
+    /// 处理 `reader` 直到结束；`sink` 被关闭 (如下游 `| head` 退出) 时视为正常结束
+    pub fn run<R: BufRead>(mut self, reader: R) -> Result<PipelineStats> {
+        let mut stats = PipelineStats::default();
+        'records: for record in read_records(reader, &self.boundary) {
+            let mut record = record?;
+            stats.records_in += 1;
+            stats.bytes_in += record.len() as u64 + 1;
+            for stage in &mut self.stages {
+                match stage {
+                    Stage::Filter(f) => {
+                        if !f(&record) {
+                            continue 'records;
+                        }
+                    }
+                    Stage::Transform(f) => record = f(record),
+                }
+            }
+            match writeln!(self.sink, "{record}") {
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => break,
+                result => result?,
+            }
+            stats.records_out += 1;
+            stats.bytes_out += record.len() as u64 + 1;
+        }
+        match self.sink.flush() {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e)?,
+            _ => {}
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline() {
+        let input = "a 1\nb 2\na 3\nc 4\n";
+        let mut out = Vec::new();
+        let stats = Pipeline::new()
+            .filter_with(|record| !record.starts_with('b'))
+            .transform(|record| record.to_uppercase())
+            .filter_with(|record| !record.starts_with('C'))
+            .sink(&mut out)
+            .run(input.as_bytes())
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&out), "A 1\nA 3\n");
+        assert_eq!(
+            stats,
+            PipelineStats {
+                records_in: 4,
+                bytes_in: 16,
+                records_out: 2,
+                bytes_out: 8,
+            }
+        );
+
+        // 没有 sink 时只统计
+        let stats = Pipeline::new().run(input.as_bytes()).unwrap();
+        assert_eq!(stats.records_out, 4);
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand};
+
+use crate::{context::AppContext, table::print_table};
+
+#[derive(Parser)]
+pub struct PresetArgs {
+    #[command(subcommand)]
+    pub command: PresetCommand,
+}
+
+#[derive(Subcommand)]
+pub enum PresetCommand {
+    /// 新增关键字集合，同名时替换
+    Add {
+        /// 集合名称，如 noise、network
+        name: String,
+
+        /// 集合中的关键字
+        #[arg(short, long, required = true)]
+        filters: Vec<String>,
+    },
+
+    /// 列出所有关键字集合
+    List,
+
+    /// 删除关键字集合
+    Remove {
+        /// 集合名称
+        name: String,
+    },
+}
+
+pub fn process_preset(ctx: &AppContext, args: PresetArgs) -> Result<()> {
+    match args.command {
+        PresetCommand::Add { name, filters } => {
+            if name.trim().is_empty() {
+                bail!("❌ preset name is empty");
+            }
+
+            let mut replaced = false;
+            ctx.update_config(|config| {
+                replaced = config.presets.insert(name.clone(), filters).is_some();
+            })?;
+            let action = if replaced { "replace" } else { "add" };
+            println!("{action} preset: {name}");
+        }
+        PresetCommand::List => {
+            let rows = ctx
+                .presets()?
+                .into_iter()
+                .map(|(name, filters)| vec![name, filters.join(", ")])
+                .collect::<Vec<_>>();
+            print_table(&["preset", "filters"], &rows);
+        }
+        PresetCommand::Remove { name } => {
+            if !ctx.presets()?.contains_key(&name) {
+                bail!("❌ preset `{name}` not found");
+            }
+
+            ctx.update_config(|config| {
+                config.presets.remove(&name);
+            })?;
+            println!("remove preset: {name}");
+        }
+    }
+
+    Ok(())
+}
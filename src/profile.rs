@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::{Parser, Subcommand};
+
+use crate::{context::AppContext, table::print_table};
+
+#[derive(Parser)]
+pub struct ProfileArgs {
+    #[command(subcommand)]
+    pub command: ProfileCommand,
+}
+
+#[derive(Subcommand)]
+pub enum ProfileCommand {
+    /// 新增命名的根路径，同名时替换
+    Add {
+        /// 名称，如产品名
+        name: String,
+
+        /// 该产品日志的根路径
+        path: PathBuf,
+    },
+
+    /// 选择默认使用的根路径，省略名称时取消选择，恢复使用 base_dir
+    Use {
+        /// 名称
+        name: Option<String>,
+    },
+
+    /// 列出所有根路径，`*` 为当前选择的
+    List,
+
+    /// 删除命名的根路径
+    Remove {
+        /// 名称
+        name: String,
+    },
+}
+
+pub fn process_profile(ctx: &AppContext, args: ProfileArgs) -> Result<()> {
+    match args.command {
+        ProfileCommand::Add { name, path } => {
+            if name.trim().is_empty() {
+                bail!("❌ profile name is empty");
+            }
+            if !path.is_dir() {
+                bail!("❌ {} is not a directory", path.display());
+            }
+
+            let mut replaced = false;
+            ctx.update_config(|config| {
+                replaced = config.profiles.insert(name.clone(), path.clone()).is_some();
+            })?;
+            let action = if replaced { "replace" } else { "add" };
+            println!("{action} profile: {name} -> {}", path.display());
+        }
+        ProfileCommand::Use { name } => {
+            let config = ctx.config_or_default()?;
+            if let Some(name) = &name
+                && !config.profiles.contains_key(name)
+            {
+                bail!("❌ profile `{name}` not found");
+            }
+
+            ctx.update_config(|config| config.profile = name.clone())?;
+            match name {
+                Some(name) => println!("use profile: {name}"),
+                None if config.base_dir.as_os_str().is_empty() => {
+                    println!("no profile selected, base dir is not set")
+                }
+                None => println!("use base dir: {}", config.base_dir.display()),
+            }
+        }
+        ProfileCommand::List => {
+            let config = ctx.config_or_default()?;
+            let rows = config
+                .profiles
+                .iter()
+                .map(|(name, path)| {
+                    let current = config.profile.as_ref() == Some(name);
+                    vec![
+                        if current { "*" } else { "" }.to_string(),
+                        name.clone(),
+                        path.display().to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            print_table(&["", "profile", "path"], &rows);
+        }
+        ProfileCommand::Remove { name } => {
+            if !ctx.config_or_default()?.profiles.contains_key(&name) {
+                bail!("❌ profile `{name}` not found");
+            }
+
+            ctx.update_config(|config| {
+                config.profiles.remove(&name);
+                if config.profile.as_ref() == Some(&name) {
+                    config.profile = None;
+                }
+            })?;
+            println!("remove profile: {name}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+    sync::{
+        OnceLock,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::Instant,
+};
+
+use anyhow::Result;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+
+/// 进度的输出方式
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProgressMode {
+    Off,
+    /// 处理多个文件时在 stderr 显示进度条，stderr 不是终端时不显示
+    Bar,
+    /// `--progress-json`
+    Json,
+}
+
+/// `--progress-json` 输出到 stderr 的事件，每行一个 JSON
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    Started {
+        files: usize,
+        bytes: u64,
+    },
+    FileStarted {
+        path: &'a Path,
+        bytes: u64,
+    },
+    FileFinished {
+        path: &'a Path,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    Progress {
+        percent: f64,
+        done_files: usize,
+        done_bytes: u64,
+        total_bytes: u64,
+    },
+    Finished {
+        files: usize,
+        failed: usize,
+        elapsed_ms: u128,
+    },
+}
+
+/// 文件夹模式下的处理进度：终端中的进度条，或供 GUI 包装程序使用的结构化事件；
+/// 关闭时所有方法都不输出
+pub struct Progress {
+    mode: ProgressMode,
+    bar: OnceLock<ProgressBar>,
+    start: Instant,
+    files: AtomicUsize,
+    total_bytes: AtomicU64,
+    done_files: AtomicUsize,
+    done_bytes: AtomicU64,
+    failed: AtomicUsize,
+}
+
+impl Progress {
+    pub fn new(mode: ProgressMode) -> Self {
+        Progress {
+            mode,
+            bar: OnceLock::new(),
+            start: Instant::now(),
+            files: AtomicUsize::new(0),
+            total_bytes: AtomicU64::new(0),
+            done_files: AtomicUsize::new(0),
+            done_bytes: AtomicU64::new(0),
+            failed: AtomicUsize::new(0),
+        }
+    }
+
+    fn emit(&self, event: &Event) {
+        if let Ok(line) = serde_json::to_string(event) {
+            // 整行一次写入，并行处理时各事件不会交错
+            let _ = writeln!(io::stderr().lock(), "{line}");
+        }
+    }
+
+    /// 开始处理一批文件
+    pub fn started<'a>(&self, files: impl IntoIterator<Item = &'a Path>) {
+        if self.mode == ProgressMode::Off {
+            return;
+        }
+
+        let (count, bytes) = files.into_iter().fold((0, 0), |(count, bytes), path| {
+            (count + 1, bytes + file_size(path))
+        });
+        self.files.store(count, Ordering::Relaxed);
+        self.total_bytes.store(bytes, Ordering::Relaxed);
+        match self.mode {
+            ProgressMode::Json => self.emit(&Event::Started {
+                files: count,
+                bytes,
+            }),
+            // 单个文件不显示进度条
+            ProgressMode::Bar if count > 1 => {
+                let style = ProgressStyle::with_template(
+                    "{bar:30} {binary_bytes}/{binary_total_bytes} {msg} ETA {eta}",
+                )
+                .expect("progress template is valid")
+                .progress_chars("=> ");
+                let bar = ProgressBar::new(bytes).with_style(style);
+                bar.set_message(format!("0/{count} files"));
+                let _ = self.bar.set(bar);
+            }
+            _ => {}
+        }
+    }
+
+    /// 处理单个文件，前后输出 file_started / file_finished 与总体进度
+    pub fn file<T>(&self, path: &Path, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        if self.mode == ProgressMode::Off {
+            return f();
+        }
+
+        let bytes = file_size(path);
+        let json = self.mode == ProgressMode::Json;
+        if json {
+            self.emit(&Event::FileStarted { path, bytes });
+        }
+        let result = f();
+        if result.is_err() {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+        let done_files = self.done_files.fetch_add(1, Ordering::Relaxed) + 1;
+        let done_bytes = self.done_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        if let Some(bar) = self.bar.get() {
+            bar.inc(bytes);
+            bar.set_message(format!(
+                "{done_files}/{} files",
+                self.files.load(Ordering::Relaxed)
+            ));
+        }
+        if !json {
+            return result;
+        }
+
+        self.emit(&Event::FileFinished {
+            path,
+            ok: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+
+        let total_bytes = self.total_bytes.load(Ordering::Relaxed);
+        let percent = if total_bytes == 0 {
+            done_files as f64 * 100.0 / self.files.load(Ordering::Relaxed).max(1) as f64
+        } else {
+            done_bytes as f64 * 100.0 / total_bytes as f64
+        };
+        self.emit(&Event::Progress {
+            percent: (percent * 10.0).round() / 10.0,
+            done_files,
+            done_bytes,
+            total_bytes,
+        });
+
+        result
+    }
+
+    /// 整批处理结束
+    pub fn finished(&self) {
+        if let Some(bar) = self.bar.get() {
+            bar.finish_and_clear();
+        }
+        if self.mode != ProgressMode::Json {
+            return;
+        }
+
+        self.emit(&Event::Finished {
+            files: self.done_files.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            elapsed_ms: self.start.elapsed().as_millis(),
+        });
+    }
+}
+
+fn file_size(path: &Path) -> u64 {
+    path.metadata().map_or(0, |m| m.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json() {
+        let line = serde_json::to_string(&Event::FileFinished {
+            path: Path::new("/var/log/app.log"),
+            ok: true,
+            error: None,
+        })
+        .unwrap();
+        assert_eq!(
+            line,
+            r#"{"event":"file_finished","path":"/var/log/app.log","ok":true}"#
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+
+use crate::{
+    context::AppContext,
+    exit::Failures,
+    matcher::{MatchArgs, Matcher},
+    record::{Metric, parse_line, parse_percent},
+    subcommand::get_entries,
+    temp::InFlight,
+};
+
+#[derive(Parser)]
+pub struct PromArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub matching: MatchArgs,
+
+    /// 输出的 textfile (通常放在 node_exporter 的 textfile 目录下，以 .prom 结尾)
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+/// 单个文件的指标
+struct FileMetrics {
+    file: String,
+    matches: usize,
+    errors: usize,
+    cpu: Option<f64>,
+    mem: Option<f64>,
+}
+
+fn collect_metrics(path: &Path, file: String, matcher: &Matcher) -> Result<FileMetrics> {
+    let content = fs::read_to_string(path)?;
+
+    let mut metrics = FileMetrics {
+        file,
+        matches: 0,
+        errors: 0,
+        cpu: None,
+        mem: None,
+    };
+    for line in content.lines() {
+        if matcher.is_match(line) {
+            metrics.matches += 1;
+        }
+        let Some(record) = parse_line(line) else {
+            continue;
+        };
+        if record.level.eq_ignore_ascii_case("error") {
+            metrics.errors += 1;
+        }
+        // 保留最后一次出现的读数
+        if let Some(cpu) = parse_percent(record.message, Metric::Cpu.key()) {
+            metrics.cpu = Some(cpu);
+        }
+        if let Some(mem) = parse_percent(record.message, Metric::Mem.key()) {
+            metrics.mem = Some(mem);
+        }
+    }
+
+    Ok(metrics)
+}
+
+/// 转义 label 值中的 `\`、`"` 与换行
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn render(metrics: &[FileMetrics]) -> String {
+    type Getter = fn(&FileMetrics) -> Option<f64>;
+    let families: [(&str, &str, Getter); 4] = [
+        ("lp_log_keyword_lines", "Lines matching the filters.", |m| {
+            Some(m.matches as f64)
+        }),
+        ("lp_log_error_lines", "Lines logged at error level.", |m| {
+            Some(m.errors as f64)
+        }),
+        (
+            "lp_log_cpu_usage_percent",
+            "Latest cpu usage reading in the log.",
+            |m| m.cpu,
+        ),
+        (
+            "lp_log_memory_usage_percent",
+            "Latest memory usage reading in the log.",
+            |m| m.mem,
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, help, value) in families {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} gauge");
+        for m in metrics {
+            if let Some(value) = value(m) {
+                let _ = writeln!(out, "{name}{{file=\"{}\"}} {value}", escape_label(&m.file));
+            }
+        }
+    }
+
+    out
+}
+
+pub fn process_prom(ctx: &AppContext, args: PromArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    let filters = args.matching.keywords(ctx, "cl")?;
+    let matcher = args.matching.matcher(&filters)?;
+
+    let files = if path.is_dir() {
+        get_entries(&path)
+            .into_iter()
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        vec![path.clone()]
+    };
+
+    let failures = Failures::new(ctx);
+    let mut metrics = files
+        .par_iter()
+        .filter_map(|file| {
+            if failures.should_stop() {
+                return None;
+            }
+            let name = file.strip_prefix(&path).unwrap_or(file);
+            let name = if name.as_os_str().is_empty() {
+                file.file_name().unwrap_or_default().display().to_string()
+            } else {
+                name.to_string_lossy().replace('\\', "/")
+            };
+            collect_metrics(file, name, &matcher)
+                .inspect_err(|e| {
+                    eprintln!("❌ prom failed, path {:?}, reason: {}", file, e);
+                    failures.record(file, e);
+                })
+                .ok()
+        })
+        .collect::<Vec<_>>();
+    metrics.sort_by(|a, b| a.file.cmp(&b.file));
+
+    // node_exporter 可能随时读取，先写临时文件再重命名
+    let tmp = InFlight::register(args.output.with_extension("prom.tmp"));
+    fs::write(tmp.path(), render(&metrics))?;
+    fs::rename(tmp.path(), &args.output)?;
+    tmp.commit();
+    println!(
+        "write metrics, path: {:?}, files: {}",
+        args.output.display(),
+        metrics.len()
+    );
+
+    failures.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = [FileMetrics {
+            file: "node\"1\"/app.log".to_string(),
+            matches: 3,
+            errors: 1,
+            cpu: Some(5.83),
+            mem: None,
+        }];
+        let out = render(&metrics);
+        assert!(out.contains("# TYPE lp_log_keyword_lines gauge\n"));
+        assert!(out.contains("lp_log_keyword_lines{file=\"node\\\"1\\\"/app.log\"} 3\n"));
+        assert!(out.contains("lp_log_cpu_usage_percent{file=\"node\\\"1\\\"/app.log\"} 5.83\n"));
+        assert!(!out.contains("lp_log_memory_usage_percent{"));
+    }
+
+    #[test]
+    fn test_collect_metrics_level_case() {
+        let path = std::env::temp_dir().join(format!("lp_prom_test_{}.log", std::process::id()));
+        fs::write(
+            &path,
+            "[2026-01-06 10:00:00.000] [ERROR] [A]  boom\n[2026-01-06 10:00:01.000] [error] [A]  boom\n[2026-01-06 10:00:02.000] [info] [A]  ok\n",
+        )
+        .unwrap();
+        let matcher = Matcher::new(&["boom".to_string()], false).unwrap();
+        let metrics = collect_metrics(&path, "app.log".to_string(), &matcher).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // 级别大小写不同也算错误
+        assert_eq!(metrics.errors, 2);
+        assert_eq!(metrics.matches, 2);
+    }
+}
@@ -0,0 +1,402 @@
+use std::{
+    io::{self, BufRead},
+    iter,
+};
+
+use clap::{Args, ValueEnum};
+
+use crate::time::{TimeRange, parse_timestamp};
+
+/// 状态行中的资源指标
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Metric {
+    Cpu,
+    Mem,
+}
+
+impl Metric {
+    /// 状态行中对应的字段名
+    pub fn key(self) -> &'static str {
+        match self {
+            Metric::Cpu => "cpu usage",
+            Metric::Mem => "memory usage",
+        }
+    }
+}
+
+/// 一行形如 `[2026-01-06 10:29:10.765] [info] [Global]  message` 的日志
+pub struct LogLine<'a> {
+    pub time: &'a str,
+    pub level: &'a str,
+    pub module: &'a str,
+    pub message: &'a str,
+}
+
+/// 解析日志行，不符合 `[time] [level] [module] message` 结构时返回 `None`
+pub fn parse_line(line: &str) -> Option<LogLine<'_>> {
+    let (time, rest) = take_bracket(line)?;
+    let (level, rest) = take_bracket(rest)?;
+    let (module, rest) = take_bracket(rest)?;
+
+    Some(LogLine {
+        time,
+        level,
+        module,
+        message: rest.trim_start(),
+    })
+}
+
+/// 将行内 `[level]` 替换为 `level`，不是标准日志行时返回 `None`
+pub fn replace_level(line: &str, level: &str) -> Option<String> {
+    let record = parse_line(line)?;
+    let start = record.level.as_ptr() as usize - line.as_ptr() as usize;
+    let end = start + record.level.len();
+
+    Some(format!("{}{}{}", &line[..start], level, &line[end..]))
+}
+
+fn take_bracket(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start().strip_prefix('[')?;
+    let end = s.find(']')?;
+
+    Some((&s[..end], &s[end + 1..]))
+}
+
+/// 解析行首 `[time]` 中的时间为毫秒时间戳
+pub fn line_timestamp(line: &str) -> Option<i64> {
+    let (time, _) = take_bracket(line)?;
+    parse_timestamp(time)
+}
+
+/// 去掉行首的 `[time]`，没有时间戳时原样返回
+pub fn strip_timestamp(line: &str) -> &str {
+    match take_bracket(line) {
+        Some((time, rest)) if parse_timestamp(time).is_some() => rest.trim_start(),
+        _ => line,
+    }
+}
+
+/// 记录的切分方式，cl / rl / shard 共用的参数
+#[derive(Args)]
+pub struct RecordArgs {
+    /// 记录分隔符，指定后按分隔符切分的整条记录而不是单行进行匹配
+    #[arg(long, value_name = "PATTERN")]
+    pub record_separator: Option<String>,
+
+    /// 按条目匹配：不以 `[时间戳]` 开头的行 (如堆栈) 归属于前面的条目，整条命中或过滤
+    #[arg(long, default_value_t = false, conflicts_with = "record_separator")]
+    pub entries: bool,
+}
+
+impl RecordArgs {
+    pub fn boundary(&self) -> Boundary {
+        match &self.record_separator {
+            Some(separator) => Boundary::Separator(separator.clone()),
+            None if self.entries => Boundary::Entry,
+            None => Boundary::Line,
+        }
+    }
+}
+
+/// 一条记录在哪里结束
+#[derive(Clone, Default)]
+pub enum Boundary {
+    /// 每行一条
+    #[default]
+    Line,
+    /// 以包含分隔符的行结束一条记录，分隔符行归属于它前面的记录
+    Separator(String),
+    /// 以 `[时间戳]` 开头的行开始新的一条，文件开头没有时间戳的行合为一条
+    Entry,
+}
+
+/// 一行在源文件中的位置：行号从 1 开始，偏移为行首的字节位置 (`.gz` 为解压后的位置)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub offset: u64,
+}
+
+/// 同 [`BufRead::lines`]，同时给出每行的位置
+pub struct PositionedLines<R> {
+    reader: R,
+    next: Position,
+}
+
+pub fn positioned_lines<R: BufRead>(reader: R) -> PositionedLines<R> {
+    PositionedLines {
+        reader,
+        next: Position { line: 1, offset: 0 },
+    }
+}
+
+impl<R: BufRead> Iterator for PositionedLines<R> {
+    type Item = io::Result<(Position, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = String::new();
+        let read = match self.reader.read_line(&mut line) {
+            Ok(0) => return None,
+            Ok(read) => read,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let position = self.next;
+        self.next = Position {
+            line: position.line + 1,
+            offset: position.offset + read as u64,
+        };
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Some(Ok((position, line)))
+    }
+}
+
+/// 从 `reader` 中按 `boundary` 逐条读取的记录
+pub struct Records<R> {
+    lines: PositionedLines<R>,
+    boundary: Boundary,
+    pending: Option<(Position, String)>,
+}
+
+pub fn read_records<R: BufRead>(reader: R, boundary: &Boundary) -> Records<R> {
+    Records {
+        lines: positioned_lines(reader),
+        boundary: boundary.clone(),
+        pending: None,
+    }
+}
+
+/// 读到包含分隔符的行为止
+fn next_separated<R: BufRead>(
+    lines: &mut PositionedLines<R>,
+    separator: &str,
+) -> Option<io::Result<(Position, String)>> {
+    let mut record: Option<(Position, String)> = None;
+    loop {
+        match lines.next() {
+            Some(Ok((position, line))) => {
+                let end = line.contains(separator);
+                match &mut record {
+                    Some((_, record)) => {
+                        record.push('\n');
+                        record.push_str(&line);
+                    }
+                    None => record = Some((position, line)),
+                }
+                if end {
+                    return record.map(Ok);
+                }
+            }
+            Some(Err(e)) => return Some(Err(e)),
+            None => return record.map(Ok),
+        }
+    }
+}
+
+impl<R: BufRead> Records<R> {
+    /// 读到下一条以时间戳开头的行为止，该行留给下一条
+    fn next_entry(&mut self) -> Option<io::Result<(Position, String)>> {
+        loop {
+            let (position, line) = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => return Some(Err(e)),
+                None => return self.pending.take().map(Ok),
+            };
+
+            let starts_entry = line_timestamp(&line).is_some();
+            match &mut self.pending {
+                Some((_, entry)) if !starts_entry => {
+                    entry.push('\n');
+                    entry.push_str(&line);
+                }
+                _ => {
+                    if let Some(entry) = self.pending.replace((position, line)) {
+                        return Some(Ok(entry));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 下一条记录及其首行的位置
+    fn next_record(&mut self) -> Option<io::Result<(Position, String)>> {
+        match &self.boundary {
+            Boundary::Line => self.lines.next(),
+            Boundary::Separator(separator) => next_separated(&mut self.lines, separator),
+            Boundary::Entry => self.next_entry(),
+        }
+    }
+
+    /// 逐条读取记录及其首行的位置
+    pub fn positioned(mut self) -> impl Iterator<Item = io::Result<(Position, String)>> {
+        iter::from_fn(move || self.next_record())
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record()
+            .map(|record| record.map(|(_, record)| record))
+    }
+}
+
+/// 按时间范围逐条筛选记录，与 export 一致：没有时间戳的记录 (如堆栈续行) 跟随上一条，
+/// 第一条带时间戳的记录之前的视为范围外
+pub struct TimeWindow {
+    range: TimeRange,
+    in_range: bool,
+}
+
+impl TimeWindow {
+    pub fn new(range: TimeRange) -> Self {
+        TimeWindow {
+            range,
+            in_range: range.is_unbounded(),
+        }
+    }
+
+    /// 须按文件中的顺序依次调用
+    pub fn contains(&mut self, record: &str) -> bool {
+        if self.range.is_unbounded() {
+            return true;
+        }
+        if let Some(ms) = parse_line(record).and_then(|record| parse_timestamp(record.time)) {
+            self.in_range = self.range.contains(ms);
+        }
+
+        self.in_range
+    }
+}
+
+/// 提取 `key` 后面紧跟的百分比数值，如 `cpu usage: 5.83%`
+pub fn parse_percent(message: &str, key: &str) -> Option<f64> {
+    parse_value(message, key, "%")
+}
+
+/// 提取 `key` 后面紧跟的以 `unit` 结尾的数值，如 `used: 230.32MB`
+pub fn parse_value(message: &str, key: &str, unit: &str) -> Option<f64> {
+    let rest = &message[message.find(key)? + key.len()..];
+    let rest = rest
+        .trim_start()
+        .strip_prefix(':')
+        .unwrap_or(rest)
+        .trim_start();
+    let end = rest.find(unit)?;
+
+    rest[..end].trim().parse().ok()
+}
+
+/// 提取消息中的错误码，如 `ERRCODE_MSOPTIMEOUT`
+pub fn parse_error_codes(message: &str) -> impl Iterator<Item = &str> {
+    message
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .filter(|s| s.starts_with("ERRCODE_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        let line = "[2026-01-06 10:29:10.765] [info] [Global]  cpu usage: 5.83%, memory usage: 0.35%, total: 65301.08MB, used: 230.32MB";
+        let record = parse_line(line).unwrap();
+        assert_eq!(
+            line_timestamp(line),
+            parse_timestamp("2026-01-06 10:29:10.765")
+        );
+        assert_eq!(record.time, "2026-01-06 10:29:10.765");
+        assert_eq!(record.level, "info");
+        assert_eq!(record.module, "Global");
+        assert!(record.message.starts_with("cpu usage"));
+
+        assert_eq!(parse_percent(record.message, "cpu usage"), Some(5.83));
+        assert_eq!(parse_percent(record.message, "memory usage"), Some(0.35));
+        assert_eq!(parse_percent(record.message, "disk usage"), None);
+        assert_eq!(parse_value(record.message, "total", "MB"), Some(65301.08));
+        assert_eq!(parse_value(record.message, "used", "MB"), Some(230.32));
+
+        let line =
+            "[2026-01-06 10:29:10.765] [error] [Global]  exception callback: ERRCODE_MSOPTIMEOUT";
+        let record = parse_line(line).unwrap();
+        assert_eq!(record.level, "error");
+        assert_eq!(
+            parse_error_codes(record.message).collect::<Vec<_>>(),
+            vec!["ERRCODE_MSOPTIMEOUT"]
+        );
+
+        assert_eq!(
+            replace_level(line, "warn").as_deref(),
+            Some(
+                "[2026-01-06 10:29:10.765] [warn] [Global]  exception callback: ERRCODE_MSOPTIMEOUT"
+            )
+        );
+
+        assert!(parse_line("    at ModelServer::load (model.cpp:42)").is_none());
+        assert!(parse_line("[2026-01-06 10:29:10.765] [info]").is_none());
+    }
+
+    #[test]
+    fn test_read_records() {
+        let records = |content: &str, separator: Option<&str>| {
+            let boundary = separator.map_or(Boundary::Line, |s| Boundary::Separator(s.to_string()));
+            read_records(content.as_bytes(), &boundary)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap()
+        };
+
+        let content = "a1\na2\n----8<----\nb1\n----8<----\nc1\nc2\n";
+        assert_eq!(
+            records(content, Some("----8<----")),
+            vec!["a1\na2\n----8<----", "b1\n----8<----", "c1\nc2"]
+        );
+        assert_eq!(records(content, None).len(), 7);
+        assert_eq!(
+            records("x\r\n----8<----\r\n", Some("----8<----")),
+            vec!["x\n----8<----"]
+        );
+        assert_eq!(
+            records("\nx\n----8<----", Some("----8<----")),
+            vec!["\nx\n----8<----"]
+        );
+
+        let content = "banner\n[2026-01-06 10:00:00.000] [error] [A]  boom\n    at a\n    at b\n[2026-01-06 10:00:01.000] [info] [A]  ok\n";
+        assert_eq!(
+            read_records(content.as_bytes(), &Boundary::Entry)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap(),
+            vec![
+                "banner",
+                "[2026-01-06 10:00:00.000] [error] [A]  boom\n    at a\n    at b",
+                "[2026-01-06 10:00:01.000] [info] [A]  ok"
+            ]
+        );
+
+        let positions = read_records(content.as_bytes(), &Boundary::Entry)
+            .positioned()
+            .map(|record| record.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            [(1, 0), (2, 7), (5, 69)].map(|(line, offset)| Position { line, offset })
+        );
+        let positions = positioned_lines("a\r\nbb\nc".as_bytes())
+            .map(|line| line.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions[1],
+            (Position { line: 2, offset: 3 }, "bb".to_string())
+        );
+        assert_eq!(
+            positions[2],
+            (Position { line: 3, offset: 6 }, "c".to_string())
+        );
+    }
+}
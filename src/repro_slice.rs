@@ -0,0 +1,311 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufRead, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Result, bail};
+use clap::{Parser, ValueEnum};
+use regex::{Captures, Regex};
+
+use crate::{
+    audit::sha256_file,
+    compress::{open_log, plain_path},
+    context::AppContext,
+    temp::InFlight,
+    time::{format_timestamp, parse_duration, parse_time_arg},
+    timestamp::{TimestampFormat, Timestamps},
+};
+
+#[derive(Parser)]
+pub struct ReproSliceArgs {
+    /// 文件路径，支持 `.gz`
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 故障发生的时间，如 `2026-01-06 10:29:10`
+    #[arg(long, value_name = "TIME", value_parser = parse_time_arg)]
+    pub around: i64,
+
+    /// 截取 `--around` 前后各这么长时间的行，如 `2m`
+    #[arg(long, value_name = "DURATION", default_value = "2m", value_parser = parse_duration)]
+    pub window: Duration,
+
+    /// 脱敏规则，default 替换邮箱、IP、UUID、长十六进制串与密码/令牌等字段的值
+    #[arg(long, value_enum, default_value = "default")]
+    pub redact: Redact,
+
+    /// 输出文件，默认为输出目录 (未配置时为源文件旁) 下的
+    /// `{stem}.repro-{时间}-{窗口}.log`，同一输入总是得到相同的文件名
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Redact {
+    Default,
+    None,
+}
+
+/// 脱敏：同一个值在切片中总是替换为同一个占位符 (如 `<ip-1>`)，保留不同行之间的关联
+struct Redactor {
+    rules: Vec<(&'static str, Regex)>,
+    secret: Regex,
+    seen: HashMap<(&'static str, String), usize>,
+    counts: HashMap<&'static str, usize>,
+    redacted: usize,
+}
+
+impl Redactor {
+    fn new() -> Self {
+        let rules = [
+            ("email", r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"),
+            (
+                "uuid",
+                r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b",
+            ),
+            ("ip", r"\b(?:\d{1,3}\.){3}\d{1,3}\b"),
+            ("hex", r"\b[0-9a-fA-F]{32,}\b"),
+        ];
+
+        Redactor {
+            rules: rules
+                .into_iter()
+                .map(|(kind, re)| (kind, Regex::new(re).unwrap()))
+                .collect(),
+            secret: Regex::new(
+                r#"(?i)\b(password|passwd|pwd|token|secret|api[_-]?key|authorization)(\s*[=:]\s*)("[^"]*"|\S+)"#,
+            )
+            .unwrap(),
+            seen: HashMap::new(),
+            counts: HashMap::new(),
+            redacted: 0,
+        }
+    }
+
+    fn redact(&mut self, line: &str) -> String {
+        let mut redacted = 0;
+        let mut line = self
+            .secret
+            .replace_all(line, |caps: &Captures| {
+                redacted += 1;
+                format!("{}{}<redacted>", &caps[1], &caps[2])
+            })
+            .into_owned();
+
+        for (kind, re) in &self.rules {
+            let seen = &mut self.seen;
+            let counts = &mut self.counts;
+            line = re
+                .replace_all(&line, |caps: &Captures| {
+                    redacted += 1;
+                    let id = *seen.entry((*kind, caps[0].to_string())).or_insert_with(|| {
+                        let count = counts.entry(kind).or_default();
+                        *count += 1;
+                        *count
+                    });
+                    format!("<{kind}-{id}>")
+                })
+                .into_owned();
+        }
+        self.redacted += redacted;
+
+        line
+    }
+}
+
+/// 切片中的行数与脱敏的值的个数
+#[derive(Debug, Default, PartialEq)]
+struct SliceCounts {
+    lines: usize,
+    redacted: usize,
+}
+
+/// 写出时间戳在 `[start, end]` 内的行，没有时间戳的续行跟随上一行；要求文件内时间基本有序，
+/// 读到晚于 `end` 的行即停止
+fn write_slice<R: BufRead, W: Write>(
+    reader: R,
+    format: TimestampFormat,
+    (start, end): (i64, i64),
+    mut redactor: Option<&mut Redactor>,
+    writer: &mut W,
+) -> Result<SliceCounts> {
+    let mut counts = SliceCounts::default();
+    let mut in_range = false;
+    for line in reader.lines() {
+        let line = line?;
+        if let Some(time) = format.parse(&line) {
+            if time > end {
+                break;
+            }
+            in_range = time >= start;
+        }
+        if !in_range {
+            continue;
+        }
+
+        match redactor.as_deref_mut() {
+            Some(redactor) => writeln!(writer, "{}", redactor.redact(&line))?,
+            None => writeln!(writer, "{line}")?,
+        }
+        counts.lines += 1;
+    }
+    counts.redacted = redactor.map_or(0, |r| r.redacted);
+
+    Ok(counts)
+}
+
+/// 窗口写成最大的整单位，如 `2m`、`90s`
+fn format_window(window: Duration) -> String {
+    let secs = window.as_secs();
+    if secs > 0 && secs.is_multiple_of(3600) {
+        format!("{}h", secs / 3600)
+    } else if secs > 0 && secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// 默认输出文件名，如 `app.repro-20260106-102910-2m.log`
+fn slice_name(path: &Path, around: i64, window: Duration) -> String {
+    let plain = plain_path(path);
+    let stem = plain.file_stem().unwrap_or_default().to_string_lossy();
+    let time = format_timestamp(around)[..19]
+        .replace(['-', ':'], "")
+        .replace(' ', "-");
+
+    format!("{stem}.repro-{time}-{}.log", format_window(window))
+}
+
+pub fn process_repro_slice(ctx: &AppContext, args: ReproSliceArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+
+    let output = match args.output {
+        Some(output) => ctx.resolve_path(output)?,
+        None => {
+            let dir = match ctx.output_dir()? {
+                Some(dir) => dir,
+                None => path.parent().unwrap_or(Path::new("")).to_path_buf(),
+            };
+            dir.join(slice_name(&path, args.around, args.window))
+        }
+    };
+    if output == path {
+        bail!("❌ output would overwrite input {}", path.display());
+    }
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let window = args.window.as_millis() as i64;
+    let range = (args.around - window, args.around + window);
+    let format = Timestamps::load(ctx)?.format_for(&path);
+    let mut redactor = (args.redact == Redact::Default).then(Redactor::new);
+
+    let partial = InFlight::register(&output);
+    let mut writer = BufWriter::new(File::create(&output)?);
+    let counts = write_slice(
+        open_log(&path)?,
+        format,
+        range,
+        redactor.as_mut(),
+        &mut writer,
+    )?;
+    writer.flush()?;
+    drop(writer);
+    if counts.lines == 0 {
+        bail!(
+            "❌ no lines between {} and {} in {}",
+            format_timestamp(range.0),
+            format_timestamp(range.1),
+            path.display()
+        );
+    }
+
+    // 与 `sha256sum` 的输出一致，可用 `sha256sum -c` 校验
+    let sha256 = sha256_file(&output)?;
+    let checksum = output.with_file_name(format!(
+        "{}.sha256",
+        output.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    fs::write(
+        &checksum,
+        format!(
+            "{sha256}  {}\n",
+            output.file_name().unwrap_or_default().to_string_lossy()
+        ),
+    )?;
+    partial.commit();
+
+    println!(
+        "write repro slice, path: {:?}, lines: {}, redacted values: {}, sha256: {sha256}",
+        output.display(),
+        counts.lines,
+        counts.redacted
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::parse_timestamp;
+
+    #[test]
+    fn test_write_slice() {
+        let content = "\
+[2026-01-06 10:26:00.000] [info] start
+[2026-01-06 10:28:00.000] [info] login user=bob@example.com from 10.0.0.12
+[2026-01-06 10:29:10.765] [error] request 10.0.0.12 failed token=abc123
+    at net::send
+[2026-01-06 10:30:00.000] [info] retry from 10.0.0.7
+[2026-01-06 10:32:00.000] [info] done
+";
+        let around = parse_timestamp("2026-01-06 10:29:10").unwrap();
+        let window = Duration::from_secs(120).as_millis() as i64;
+        let mut redactor = Redactor::new();
+        let mut out = Vec::new();
+        let counts = write_slice(
+            content.as_bytes(),
+            TimestampFormat::Bracket,
+            (around - window, around + window),
+            Some(&mut redactor),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            counts,
+            SliceCounts {
+                lines: 4,
+                redacted: 5
+            }
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&out),
+            "\
+[2026-01-06 10:28:00.000] [info] login user=<email-1> from <ip-1>
+[2026-01-06 10:29:10.765] [error] request <ip-1> failed token=<redacted>
+    at net::send
+[2026-01-06 10:30:00.000] [info] retry from <ip-2>
+"
+        );
+
+        assert_eq!(
+            slice_name(
+                Path::new("logs/app.log.gz"),
+                around,
+                Duration::from_secs(120)
+            ),
+            "app.repro-20260106-102910-2m.log"
+        );
+        assert_eq!(format_window(Duration::from_secs(90)), "90s");
+        assert_eq!(format_window(Duration::from_secs(7200)), "2h");
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+
+use crate::{compress::open_log, context::AppContext, temp::InFlight};
+
+#[derive(Parser)]
+pub struct RouteArgs {
+    /// 文件路径，支持 `.gz`
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 路由规则 `关键字=>输出文件`，如 `error=>errors.log`；一行写入所有匹配规则的输出，
+    /// 多条规则可指向同一文件，同一行只写入一次
+    #[arg(short, long = "rule", required = true, value_parser = parse_route_rule)]
+    pub rules: Vec<RouteRule>,
+
+    /// 不匹配任何规则的行写入该文件，不指定时丢弃
+    #[arg(long)]
+    pub default: Option<PathBuf>,
+}
+
+#[derive(Clone)]
+pub struct RouteRule {
+    keyword: String,
+    output: PathBuf,
+}
+
+/// 以最后一个 `=>` 分隔，关键字中可以包含 `=>`
+fn parse_route_rule(s: &str) -> Result<RouteRule, String> {
+    let (keyword, output) = s
+        .rsplit_once("=>")
+        .ok_or_else(|| format!("invalid route rule `{s}`, expected `keyword=>output`"))?;
+    if keyword.is_empty() || output.trim().is_empty() {
+        return Err(format!(
+            "invalid route rule `{s}`, keyword and output should not be empty"
+        ));
+    }
+
+    Ok(RouteRule {
+        keyword: keyword.to_string(),
+        output: PathBuf::from(output.trim()),
+    })
+}
+
+/// 按规则把每行写入对应的输出，`targets[i]` 为第 i 条规则的输出下标；返回每个输出写入的行数
+fn route_lines<R: BufRead, W: Write>(
+    reader: R,
+    keywords: &[&str],
+    targets: &[usize],
+    default: Option<usize>,
+    writers: &mut [W],
+) -> Result<Vec<usize>> {
+    let mut counts = vec![0; writers.len()];
+    let mut matched = vec![false; writers.len()];
+    for line in reader.lines() {
+        let line = line?;
+        matched.fill(false);
+        for (keyword, &target) in keywords.iter().zip(targets) {
+            if !matched[target] && line.contains(keyword) {
+                matched[target] = true;
+            }
+        }
+        if let Some(default) = default
+            && !matched.contains(&true)
+        {
+            matched[default] = true;
+        }
+
+        for (i, _) in matched.iter().enumerate().filter(|(_, m)| **m) {
+            writeln!(writers[i], "{line}")?;
+            counts[i] += 1;
+        }
+    }
+
+    Ok(counts)
+}
+
+pub fn process_route(ctx: &AppContext, args: RouteArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+
+    // 同一输出文件只打开一次
+    let mut outputs = Vec::<PathBuf>::new();
+    let mut target = |output: PathBuf| -> Result<usize> {
+        let output = ctx.resolve_path(output)?;
+        if output == path {
+            bail!("❌ output would overwrite input {}", path.display());
+        }
+        Ok(match outputs.iter().position(|o| *o == output) {
+            Some(i) => i,
+            None => {
+                outputs.push(output);
+                outputs.len() - 1
+            }
+        })
+    };
+    let targets = args
+        .rules
+        .iter()
+        .map(|rule| target(rule.output.clone()))
+        .collect::<Result<Vec<_>>>()?;
+    let default = args.default.map(&mut target).transpose()?;
+    if let Some(default) = default
+        && targets.contains(&default)
+    {
+        bail!(
+            "❌ --default {} is also the output of a rule",
+            outputs[default].display()
+        );
+    }
+
+    let partials = outputs.iter().map(InFlight::register).collect::<Vec<_>>();
+    let mut writers = outputs
+        .iter()
+        .map(|output| {
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            Ok(BufWriter::new(File::create(output)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let keywords = args
+        .rules
+        .iter()
+        .map(|rule| rule.keyword.as_str())
+        .collect::<Vec<_>>();
+    let counts = route_lines(open_log(&path)?, &keywords, &targets, default, &mut writers)?;
+
+    for writer in &mut writers {
+        writer.flush()?;
+    }
+    for partial in partials {
+        partial.commit();
+    }
+    for (output, count) in outputs.iter().zip(counts) {
+        println!(
+            "write route file, path: {:?}, lines: {count}",
+            output.display()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_lines() {
+        let rule = parse_route_rule("GET:=>api.log").unwrap();
+        assert_eq!(rule.keyword, "GET:");
+        assert_eq!(rule.output, PathBuf::from("api.log"));
+        assert_eq!(parse_route_rule("a=>b=> out.log").unwrap().keyword, "a=>b");
+        assert!(parse_route_rule("error").is_err());
+        assert!(parse_route_rule("=>errors.log").is_err());
+
+        let content = "\
+[info] GET: /a
+[error] GET: /b failed
+[error] fatal disk
+[info] idle
+";
+        // error、fatal 写入同一输出，GET: 单独输出，其余写入默认输出
+        let mut writers = vec![Vec::new(); 3];
+        let counts = route_lines(
+            content.as_bytes(),
+            &["error", "fatal", "GET:"],
+            &[0, 0, 1],
+            Some(2),
+            &mut writers,
+        )
+        .unwrap();
+        assert_eq!(counts, [2, 2, 1]);
+        assert_eq!(
+            String::from_utf8_lossy(&writers[0]),
+            "[error] GET: /b failed\n[error] fatal disk\n"
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&writers[1]),
+            "[info] GET: /a\n[error] GET: /b failed\n"
+        );
+        assert_eq!(String::from_utf8_lossy(&writers[2]), "[info] idle\n");
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+
+use crate::{
+    context::AppContext,
+    entries::EntryFilter,
+    exit::Failures,
+    out_name::{OutName, OverwriteArgs, output_path, parse_out_name},
+    pipeline::Pipeline,
+    record::parse_line,
+    subcommand::filtered_entries,
+    temp::InFlight,
+};
+
+#[derive(Parser)]
+pub struct SampleArgs {
+    /// 文件路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 全部保留的级别
+    #[arg(short, long, value_delimiter = ',', default_value = "warn,error")]
+    pub keep_level: Vec<String>,
+
+    /// 其余日志每多少条保留一条
+    #[arg(short, long, default_value_t = 1000)]
+    pub every: usize,
+
+    /// 输出文件名模板，支持 {stem}、{ext}、{date}、{preset}、{profile}，如 `{stem}.{date}.clean.{ext}`；
+    /// 默认为 `{stem}_sample.{ext}`
+    #[arg(long, value_name = "TEMPLATE", value_parser = parse_out_name)]
+    pub out_name: Option<String>,
+
+    #[command(flatten)]
+    pub overwrite: OverwriteArgs,
+}
+
+pub fn process_sample(ctx: &AppContext, args: SampleArgs) -> Result<()> {
+    if args.every == 0 {
+        bail!("❌ --every should be greater than 0");
+    }
+
+    let path = ctx.resolve_path(args.path.clone())?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+    let out_name = args
+        .out_name
+        .clone()
+        .map(|template| OutName::new(ctx, template, &[]))
+        .transpose()?;
+
+    if path.is_dir() {
+        let failures = Failures::new(ctx);
+        let entries = EntryFilter::default().skip_outputs(out_name.as_ref())?;
+        filtered_entries(&path, &entries).par_iter().for_each(|e| {
+            if failures.should_stop() {
+                return;
+            }
+            let file_path = e.path();
+            if let Err(e) = sample_file(file_path, &args, out_name.as_ref()) {
+                eprintln!("❌ sample failed, path {:?}, reason: {}", file_path, e);
+                failures.record(file_path, &e);
+            }
+        });
+        failures.finish()?;
+    } else {
+        sample_file(&path, &args, out_name.as_ref())?;
+    }
+
+    Ok(())
+}
+
+fn sample_file(path: &Path, args: &SampleArgs, out_name: Option<&OutName>) -> Result<()> {
+    let new_path = args
+        .overwrite
+        .claim(&output_path(path, out_name, "_sample")?)?;
+    let partial = InFlight::register(&new_path);
+    let mut sampler = Sampler::new(&args.keep_level, args.every);
+    let stats = Pipeline::new()
+        .filter_with(|line| sampler.keep(line))
+        .sink(BufWriter::new(File::create(&new_path)?))
+        .run(BufReader::new(File::open(path)?))?;
+    partial.commit();
+    println!(
+        "write file after sample, path: {:?}, lines: {} -> {}",
+        new_path.display(),
+        stats.records_in,
+        stats.records_out
+    );
+
+    Ok(())
+}
+
+/// 保留指定级别的所有行，其余行每 `every` 条取一条；无法解析的续行跟随上一行
+struct Sampler<'a> {
+    keep_levels: &'a [String],
+    every: usize,
+    counter: usize,
+    keep: bool,
+}
+
+impl<'a> Sampler<'a> {
+    fn new(keep_levels: &'a [String], every: usize) -> Self {
+        Sampler {
+            keep_levels,
+            every,
+            counter: 0,
+            keep: true,
+        }
+    }
+
+    fn keep(&mut self, line: &str) -> bool {
+        if let Some(record) = parse_line(line) {
+            self.keep = if self
+                .keep_levels
+                .iter()
+                .any(|level| level.eq_ignore_ascii_case(record.level))
+            {
+                true
+            } else {
+                self.counter += 1;
+                (self.counter - 1).is_multiple_of(self.every)
+            };
+        }
+        self.keep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_lines<'a>(content: &'a str, keep_levels: &[String], every: usize) -> Vec<&'a str> {
+        let mut sampler = Sampler::new(keep_levels, every);
+        content.lines().filter(|line| sampler.keep(line)).collect()
+    }
+
+    #[test]
+    fn test_sample_lines() {
+        let mut content = String::new();
+        for i in 0..10 {
+            content.push_str(&format!(
+                "[2026-01-06 10:29:10.765] [info] [Global]  noise {i}\n"
+            ));
+        }
+        content.push_str("[2026-01-06 10:29:10.765] [error] [Global]  exception callback\n");
+        content.push_str("    at ModelServer::load\n");
+        content.push_str("[2026-01-06 10:29:10.765] [info] [Global]  noise 10\n");
+        content.push_str("    continuation of noise 10\n");
+
+        let levels = ["warn".to_string(), "error".to_string()];
+        let lines = sample_lines(&content, &levels, 5);
+        assert_eq!(
+            lines,
+            vec![
+                "[2026-01-06 10:29:10.765] [info] [Global]  noise 0",
+                "[2026-01-06 10:29:10.765] [info] [Global]  noise 5",
+                "[2026-01-06 10:29:10.765] [error] [Global]  exception callback",
+                "    at ModelServer::load",
+                "[2026-01-06 10:29:10.765] [info] [Global]  noise 10",
+                "    continuation of noise 10",
+            ]
+        );
+
+        assert_eq!(sample_lines(&content, &levels, 1).len(), 14);
+    }
+
+    #[test]
+    fn test_sample_file_overwrite() {
+        let dir = std::env::temp_dir().join(format!("lp_sample_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        std::fs::write(&path, "[2026-01-06 10:29:10.765] [info] [Global]  noise\n").unwrap();
+        let sample = |extra: &[&str]| {
+            let argv = ["sample", "-p", path.to_str().unwrap()];
+            let args = SampleArgs::try_parse_from(argv.iter().chain(extra)).unwrap();
+            sample_file(&path, &args, None)
+        };
+
+        // 已有的结果不被覆盖，除非指定 --force
+        sample(&[]).unwrap();
+        std::fs::write(dir.join("app_sample.log"), "kept\n").unwrap();
+        assert!(sample(&[]).is_err());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("app_sample.log")).unwrap(),
+            "kept\n"
+        );
+        sample(&["--force"]).unwrap();
+        assert_ne!(
+            std::fs::read_to_string(dir.join("app_sample.log")).unwrap(),
+            "kept\n"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
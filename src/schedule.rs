@@ -0,0 +1,119 @@
+use std::{
+    cmp::Reverse,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use clap::ValueEnum;
+use rayon::prelude::*;
+use walkdir::DirEntry;
+
+use crate::cancel::{graceful, is_cancelled, print_cancelled};
+
+/// 文件夹中文件的处理顺序
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum Schedule {
+    /// 先处理大文件，避免最后只剩一个核在处理大文件
+    #[default]
+    Size,
+    /// 按路径排序
+    Name,
+    /// 按修改时间从旧到新
+    Mtime,
+}
+
+/// 用于排序的文件信息，读取失败时按 0 处理
+struct FileInfo<'a> {
+    path: &'a Path,
+    size: u64,
+    modified: SystemTime,
+}
+
+impl<'a> FileInfo<'a> {
+    fn new(entry: &'a DirEntry) -> Self {
+        let meta = entry.metadata().ok();
+        FileInfo {
+            path: entry.path(),
+            size: meta.as_ref().map_or(0, |m| m.len()),
+            modified: meta.and_then(|m| m.modified().ok()).unwrap_or(UNIX_EPOCH),
+        }
+    }
+}
+
+/// 按调度策略排列的下标
+fn schedule_order(files: &[FileInfo], schedule: Schedule) -> Vec<usize> {
+    let mut order = (0..files.len()).collect::<Vec<_>>();
+    match schedule {
+        Schedule::Size => order.sort_by_key(|&i| (Reverse(files[i].size), files[i].path)),
+        Schedule::Name => order.sort_by_key(|&i| files[i].path),
+        Schedule::Mtime => order.sort_by_key(|&i| (files[i].modified, files[i].path)),
+    }
+
+    order
+}
+
+/// 按调度顺序把文件交给 rayon，空闲线程依次取下一个文件，排在前面的先开始；
+/// 返回的结果与 `entries` 的顺序一致。Ctrl-C 后不再开始新的文件，只返回已处理文件的结果，
+/// 并按 `command` 提示如何处理剩下的文件
+pub fn par_map_scheduled<T, F>(
+    command: &str,
+    entries: &[DirEntry],
+    schedule: Schedule,
+    f: F,
+) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&DirEntry) -> T + Sync + Send,
+{
+    let files = entries.iter().map(FileInfo::new).collect::<Vec<_>>();
+    let order = schedule_order(&files, schedule);
+
+    let _graceful = graceful();
+    let mut results = order
+        .into_iter()
+        .par_bridge()
+        .filter(|_| !is_cancelled())
+        .map(|i| (i, f(&entries[i])))
+        .collect::<Vec<_>>();
+    results.sort_by_key(|(i, _)| *i);
+
+    if is_cancelled() {
+        let mut started = vec![false; entries.len()];
+        for (i, _) in &results {
+            started[*i] = true;
+        }
+        let not_started = files
+            .iter()
+            .zip(started)
+            .filter(|(_, started)| !started)
+            .map(|(file, _)| file.path)
+            .collect::<Vec<_>>();
+        print_cancelled(command, entries.len(), &not_started);
+    }
+
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_schedule_order() {
+        let file = |path, size, secs| FileInfo {
+            path: Path::new(path),
+            size,
+            modified: UNIX_EPOCH + Duration::from_secs(secs),
+        };
+        let files = [
+            file("/logs/b.log", 10, 300),
+            file("/logs/a.log", 10_000, 200),
+            file("/logs/c.log", 500, 100),
+        ];
+
+        assert_eq!(schedule_order(&files, Schedule::Size), vec![1, 2, 0]);
+        assert_eq!(schedule_order(&files, Schedule::Name), vec![1, 0, 2]);
+        assert_eq!(schedule_order(&files, Schedule::Mtime), vec![2, 1, 0]);
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    path::PathBuf,
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+
+use crate::{context::AppContext, record::line_timestamp, time::parse_time_arg};
+
+/// 从某个偏移开始寻找带时间戳的行时最多读取的字节数，超过后视为没有
+const MAX_PROBE: u64 = 1024 * 1024;
+
+#[derive(Parser)]
+pub struct SeekArgs {
+    /// 文件路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 要定位的时间，如 `2026-01-06 10:29`
+    #[arg(long, value_parser = parse_time_arg)]
+    pub at: i64,
+
+    /// 同时打印之前的行数
+    #[arg(short = 'B', long, default_value_t = 5)]
+    pub before: usize,
+
+    /// 打印之后的行数 (含定位到的行)
+    #[arg(short = 'A', long, default_value_t = 20)]
+    pub after: usize,
+}
+
+/// 返回偏移 `offset` 处或之后第一条带时间戳的行的 (起始偏移, 时间戳)
+fn probe<R: BufRead + Seek>(reader: &mut R, offset: u64) -> io::Result<Option<(u64, i64)>> {
+    let mut pos = if offset == 0 {
+        reader.seek(SeekFrom::Start(0))?
+    } else {
+        // 从前一个字节开始跳过当前行的剩余部分，得到 offset 处或之后的行首
+        reader.seek(SeekFrom::Start(offset - 1))?;
+        let mut skipped = Vec::new();
+        offset - 1 + reader.read_until(b'\n', &mut skipped)? as u64
+    };
+
+    let start = pos;
+    let mut line = Vec::new();
+    while pos - start < MAX_PROBE {
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line)?;
+        if n == 0 {
+            break;
+        }
+        if let Some(time) = line_timestamp(&String::from_utf8_lossy(&line)) {
+            return Ok(Some((pos, time)));
+        }
+        pos += n as u64;
+    }
+
+    Ok(None)
+}
+
+/// 二分查找第一条时间不早于 `target` 的行的起始偏移，要求文件内时间基本有序
+fn seek_timestamp<R: BufRead + Seek>(
+    reader: &mut R,
+    len: u64,
+    target: i64,
+) -> io::Result<Option<u64>> {
+    let (mut lo, mut hi) = (0, len);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        match probe(reader, mid)? {
+            Some((_, time)) if time < target => lo = mid + 1,
+            _ => hi = mid,
+        }
+    }
+
+    Ok(probe(reader, lo)?.map(|(pos, _)| pos))
+}
+
+/// 读取 `pos` 之前最多 `count` 行
+fn lines_before<R: Read + Seek>(reader: &mut R, pos: u64, count: usize) -> io::Result<Vec<String>> {
+    let mut window = 4096_u64.max(count as u64 * 512);
+    loop {
+        let start = pos.saturating_sub(window);
+        reader.seek(SeekFrom::Start(start))?;
+        let mut buf = vec![0; (pos - start) as usize];
+        reader.read_exact(&mut buf)?;
+
+        let text = String::from_utf8_lossy(&buf);
+        let mut lines = text.lines().collect::<Vec<_>>();
+        // 窗口起点不在行首时第一行不完整
+        if start > 0 && !lines.is_empty() {
+            lines.remove(0);
+        }
+        if lines.len() >= count || start == 0 || window >= MAX_PROBE {
+            let skip = lines.len().saturating_sub(count);
+            return Ok(lines[skip..].iter().map(|s| s.to_string()).collect());
+        }
+        window *= 4;
+    }
+}
+
+pub fn process_seek(ctx: &AppContext, args: SeekArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+
+    let file = File::open(&path)?;
+    let len = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let Some(pos) = seek_timestamp(&mut reader, len, args.at)? else {
+        println!("no line at or after the given time in {}", path.display());
+        return Ok(());
+    };
+    println!("offset: {pos}");
+
+    for line in lines_before(&mut reader, pos, args.before)? {
+        println!("  {line}");
+    }
+
+    reader.seek(SeekFrom::Start(pos))?;
+    for (i, line) in reader.lines().take(args.after).enumerate() {
+        let line = line?;
+        println!("{} {line}", if i == 0 { '>' } else { ' ' });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::time::parse_timestamp;
+
+    #[test]
+    fn test_seek_timestamp() {
+        let mut content = String::from("banner without time\n");
+        for minute in 0..60 {
+            content.push_str(&format!(
+                "[2026-01-06 10:{minute:02}:00.000] [info] [Global]  tick {minute}\n"
+            ));
+            content.push_str("    continuation\n");
+        }
+        let len = content.len() as u64;
+        let mut reader = Cursor::new(content.as_bytes());
+
+        let at = |reader: &mut Cursor<&[u8]>, time: &str| {
+            seek_timestamp(reader, len, parse_timestamp(time).unwrap())
+                .unwrap()
+                .map(|pos| {
+                    let rest = &content[pos as usize..];
+                    rest[..rest.find('\n').unwrap()].to_string()
+                })
+        };
+
+        assert!(
+            at(&mut reader, "2026-01-06 10:29")
+                .unwrap()
+                .ends_with("tick 29")
+        );
+        assert!(
+            at(&mut reader, "2026-01-06 10:29:30")
+                .unwrap()
+                .ends_with("tick 30")
+        );
+        assert!(at(&mut reader, "2026-01-01").unwrap().ends_with("tick 0"));
+        assert!(at(&mut reader, "2026-01-07").is_none());
+
+        let pos = seek_timestamp(
+            &mut reader,
+            len,
+            parse_timestamp("2026-01-06 10:02").unwrap(),
+        )
+        .unwrap()
+        .unwrap();
+        let before = lines_before(&mut reader, pos, 3).unwrap();
+        assert_eq!(before.len(), 3);
+        assert!(before[0].ends_with("continuation"));
+        assert!(before[1].ends_with("tick 1"));
+        assert_eq!(lines_before(&mut reader, pos, 100).unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,440 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+
+/// 随程序打包的用例，源文件在 `tests/fixtures/`，`cargo test` 时也会逐个运行
+const BUILTIN_CASES: &[(&str, &str)] = &[
+    ("cl", include_str!("../tests/fixtures/cl.case")),
+    ("cl_json", include_str!("../tests/fixtures/cl_json.case")),
+    (
+        "cl_no_match",
+        include_str!("../tests/fixtures/cl_no_match.case"),
+    ),
+    (
+        "cl_porcelain",
+        include_str!("../tests/fixtures/cl_porcelain.case"),
+    ),
+    ("dedup", include_str!("../tests/fixtures/dedup.case")),
+    ("export", include_str!("../tests/fixtures/export.case")),
+    (
+        "heartbeat",
+        include_str!("../tests/fixtures/heartbeat.case"),
+    ),
+    ("merge", include_str!("../tests/fixtures/merge.case")),
+    ("rl", include_str!("../tests/fixtures/rl.case")),
+    (
+        "rl_stdout",
+        include_str!("../tests/fixtures/rl_stdout.case"),
+    ),
+    (
+        "rl_time_range",
+        include_str!("../tests/fixtures/rl_time_range.case"),
+    ),
+    (
+        "repro_slice",
+        include_str!("../tests/fixtures/repro_slice.case"),
+    ),
+    ("route", include_str!("../tests/fixtures/route.case")),
+    ("sample", include_str!("../tests/fixtures/sample.case")),
+    ("stats", include_str!("../tests/fixtures/stats.case")),
+    (
+        "transform",
+        include_str!("../tests/fixtures/transform.case"),
+    ),
+];
+
+/// 输出中的临时根路径替换为该占位符，用例与运行位置无关
+const ROOT_PLACEHOLDER: &str = "$ROOT";
+
+const SECTION_PREFIX: &str = "--- ";
+
+#[derive(Parser)]
+pub struct SelftestArgs {
+    /// 只运行名称包含该字符串的用例
+    pub name: Option<String>,
+
+    /// 读取目录下的 `*.case` 用例，代替内置的用例
+    #[arg(long)]
+    pub fixtures: Option<PathBuf>,
+
+    /// 用实际的输出改写用例文件中的期望值
+    #[arg(long, default_value_t = false, requires = "fixtures")]
+    pub update: bool,
+}
+
+/// 一个用例，文件格式：
+///
+/// ```text
+/// # 注释
+/// args: cl -p logs -f timeout
+/// exit: 1
+/// --- input logs/app.log
+/// 运行前写入根路径下的文件
+/// --- output logs/app_filtered.log
+/// 运行后该文件的期望内容
+/// --- stdout
+/// 期望的标准输出
+/// ```
+///
+/// `args` 在 `lp --base-dir $ROOT --no-progress` 之后，按空白切分，可用引号包含空白；
+/// `exit` 省略时为 0
+#[derive(Debug, Default, PartialEq)]
+struct Case {
+    comments: Vec<String>,
+    args: String,
+    exit: i32,
+    inputs: Vec<(String, String)>,
+    outputs: Vec<(String, String)>,
+    stdout: String,
+}
+
+/// 正在填充的段落
+enum Section {
+    Input,
+    Output,
+    Stdout,
+}
+
+impl Case {
+    fn parse(text: &str) -> Result<Self> {
+        let mut case = Case::default();
+        let mut section = None;
+        for line in text.split_inclusive('\n') {
+            if let Some(header) = line.strip_prefix(SECTION_PREFIX) {
+                let header = header.trim_end();
+                section = Some(match header.split_once(' ') {
+                    Some(("input", path)) => {
+                        case.inputs.push((path.to_string(), String::new()));
+                        Section::Input
+                    }
+                    Some(("output", path)) => {
+                        case.outputs.push((path.to_string(), String::new()));
+                        Section::Output
+                    }
+                    None if header == "stdout" => Section::Stdout,
+                    _ => bail!("❌ invalid section `{header}`"),
+                });
+                continue;
+            }
+
+            match section {
+                Some(Section::Input) => case.inputs.last_mut().unwrap().1.push_str(line),
+                Some(Section::Output) => case.outputs.last_mut().unwrap().1.push_str(line),
+                Some(Section::Stdout) => case.stdout.push_str(line),
+                None => {
+                    let line = line.trim_end();
+                    if line.is_empty() || line.starts_with('#') {
+                        case.comments.push(line.to_string());
+                    } else if let Some(args) = line.strip_prefix("args:") {
+                        case.args = args.trim().to_string();
+                    } else if let Some(exit) = line.strip_prefix("exit:") {
+                        case.exit = exit
+                            .trim()
+                            .parse()
+                            .with_context(|| format!("❌ invalid exit code `{exit}`"))?;
+                    } else {
+                        bail!("❌ invalid line `{line}`, expected `args:` or `exit:`");
+                    }
+                }
+            }
+        }
+        if case.args.is_empty() {
+            bail!("❌ missing `args:`");
+        }
+
+        Ok(case)
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        for comment in &self.comments {
+            text.push_str(comment);
+            text.push('\n');
+        }
+        text.push_str(&format!("args: {}\n", self.args));
+        if self.exit != 0 {
+            text.push_str(&format!("exit: {}\n", self.exit));
+        }
+        let mut section = |header: String, content: &str| {
+            text.push_str(SECTION_PREFIX);
+            text.push_str(&header);
+            text.push('\n');
+            text.push_str(content);
+            if !content.is_empty() && !content.ends_with('\n') {
+                text.push('\n');
+            }
+        };
+        for (path, content) in &self.inputs {
+            section(format!("input {path}"), content);
+        }
+        for (path, content) in &self.outputs {
+            section(format!("output {path}"), content);
+        }
+        section("stdout".to_string(), &self.stdout);
+
+        text
+    }
+}
+
+/// 按空白切分参数，单引号或双引号内的空白不切分
+fn split_args(args: &str) -> Result<Vec<String>> {
+    let mut parts = Vec::new();
+    let mut current = None::<String>;
+    let mut quote = None;
+    for c in args.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.get_or_insert_default().push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                current.get_or_insert_default();
+            }
+            None if c.is_whitespace() => parts.extend(current.take()),
+            None => current.get_or_insert_default().push(c),
+        }
+    }
+    if quote.is_some() {
+        bail!("❌ unterminated quote in `{args}`");
+    }
+    parts.extend(current);
+
+    Ok(parts)
+}
+
+/// 运行一个用例的实际结果
+struct Actual {
+    exit: i32,
+    stdout: String,
+    stderr: String,
+    outputs: Vec<(String, Option<String>)>,
+}
+
+fn run_case(name: &str, case: &Case) -> Result<Actual> {
+    let dir = env::temp_dir().join(format!("lp_selftest_{}_{name}", std::process::id()));
+    let root = dir.join("root");
+    let result = (|| {
+        for (path, content) in &case.inputs {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap_or(&root))?;
+            fs::write(&path, content)?;
+        }
+        fs::create_dir_all(&root)?;
+
+        // 配置与历史记录放在临时目录，不影响本机的配置
+        let output = Command::new(env::current_exe()?)
+            .env("LP_CONFIG", dir.join("config.json"))
+            .env_remove("LP_BASE_DIR")
+            .arg("--base-dir")
+            .arg(&root)
+            .arg("--no-progress")
+            .args(split_args(&case.args)?)
+            .stdin(Stdio::null())
+            .output()?;
+
+        let root = root.display().to_string();
+        let normalize =
+            |text: &[u8]| String::from_utf8_lossy(text).replace(root.as_str(), ROOT_PLACEHOLDER);
+        let outputs = case
+            .outputs
+            .iter()
+            .map(|(path, _)| {
+                let content = fs::read(Path::new(&root).join(path)).ok();
+                (path.clone(), content.map(|c| normalize(&c)))
+            })
+            .collect();
+
+        Ok(Actual {
+            exit: output.status.code().unwrap_or(-1),
+            stdout: normalize(&output.stdout),
+            stderr: normalize(&output.stderr),
+            outputs,
+        })
+    })();
+    let _ = fs::remove_dir_all(&dir);
+
+    result
+}
+
+/// 与期望不一致之处，一致时为空
+fn differences(case: &Case, actual: &Actual) -> Vec<String> {
+    let mut differences = Vec::new();
+    if case.exit != actual.exit {
+        differences.push(format!(
+            "exit code: expected {}, got {}",
+            case.exit, actual.exit
+        ));
+    }
+    if let Some(diff) = first_difference(&case.stdout, &actual.stdout) {
+        differences.push(format!("stdout: {diff}"));
+    }
+    for ((path, expected), (_, content)) in case.outputs.iter().zip(&actual.outputs) {
+        match content {
+            Some(content) => {
+                if let Some(diff) = first_difference(expected, content) {
+                    differences.push(format!("{path}: {diff}"));
+                }
+            }
+            None => differences.push(format!("{path}: not created")),
+        }
+    }
+
+    differences
+}
+
+/// 第一处不同的行
+fn first_difference(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let mut expected_lines = expected.split_inclusive('\n');
+    let mut actual_lines = actual.split_inclusive('\n');
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (e, a) => {
+                return Some(format!(
+                    "line {line}: expected {:?}, got {:?}",
+                    e.unwrap_or("<eof>"),
+                    a.unwrap_or("<eof>")
+                ));
+            }
+        }
+    }
+}
+
+/// 目录下的 `*.case` 用例，按名称排序
+fn read_fixtures(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut cases = fs::read_dir(dir)
+        .with_context(|| format!("❌ read fixtures {} failed", dir.display()))?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "case"))
+        .map(|path| {
+            let name = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            (name, path)
+        })
+        .collect::<Vec<_>>();
+    cases.sort();
+
+    Ok(cases)
+}
+
+pub fn process_selftest(args: SelftestArgs) -> Result<()> {
+    let cases = match &args.fixtures {
+        Some(dir) => read_fixtures(dir)?
+            .into_iter()
+            .map(|(name, path)| Ok((name, Some(path.clone()), fs::read_to_string(path)?)))
+            .collect::<Result<Vec<_>>>()?,
+        None => BUILTIN_CASES
+            .iter()
+            .map(|(name, text)| (name.to_string(), None, text.to_string()))
+            .collect(),
+    };
+    let cases = cases
+        .into_iter()
+        .filter(|(name, _, _)| args.name.as_ref().is_none_or(|n| name.contains(n.as_str())))
+        .collect::<Vec<_>>();
+    if cases.is_empty() {
+        bail!("❌ no test case found");
+    }
+
+    let mut failed = 0;
+    for (name, path, text) in &cases {
+        let mut case = Case::parse(text).with_context(|| format!("❌ invalid case {name}"))?;
+        let actual = run_case(name, &case)?;
+        let differences = differences(&case, &actual);
+        if differences.is_empty() {
+            println!("ok      {name}");
+            continue;
+        }
+
+        if args.update
+            && let Some(path) = path
+        {
+            case.exit = actual.exit;
+            case.stdout = actual.stdout;
+            for ((_, expected), (_, content)) in case.outputs.iter_mut().zip(actual.outputs) {
+                *expected = content.unwrap_or_default();
+            }
+            fs::write(path, case.to_text())?;
+            println!("updated {name}");
+            continue;
+        }
+
+        failed += 1;
+        println!("FAILED  {name}: lp {}", case.args);
+        for difference in differences {
+            println!("  {difference}");
+        }
+        if !actual.stderr.is_empty() {
+            println!("  stderr: {}", actual.stderr.trim_end());
+        }
+    }
+
+    println!("cases: {}, failed: {failed}", cases.len());
+    if failed > 0 {
+        bail!("❌ {failed} of {} test cases failed", cases.len());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_format() {
+        let text = "\
+# keyword lines
+args: cl -p logs -f 'request timeout'
+exit: 1
+--- input logs/app.log
+a
+b
+--- output logs/app_filtered.log
+a
+--- stdout
+path:$ROOT/logs
+";
+        let case = Case::parse(text).unwrap();
+        assert_eq!(case.exit, 1);
+        assert_eq!(
+            case.inputs,
+            vec![("logs/app.log".to_string(), "a\nb\n".to_string())]
+        );
+        assert_eq!(case.outputs[0].1, "a\n");
+        assert_eq!(case.stdout, "path:$ROOT/logs\n");
+        assert_eq!(case.to_text(), text);
+        assert_eq!(
+            split_args(&case.args).unwrap(),
+            ["cl", "-p", "logs", "-f", "request timeout"]
+        );
+
+        assert!(Case::parse("--- stdout\n").is_err());
+        assert!(Case::parse("args: cl\n--- result\n").is_err());
+        assert!(split_args("cl -f 'a").is_err());
+
+        assert_eq!(first_difference("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            first_difference("a\nb\n", "a\nc\n").unwrap(),
+            r#"line 2: expected "b\n", got "c\n""#
+        );
+        assert_eq!(
+            first_difference("a\n", "a\nb\n").unwrap(),
+            r#"line 2: expected "<eof>", got "b\n""#
+        );
+    }
+}
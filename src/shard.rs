@@ -0,0 +1,193 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+
+use crate::{
+    compare::{file_key, load_report},
+    context::AppContext,
+    exit::Failures,
+    matcher::MatchArgs,
+    record::RecordArgs,
+    subcommand::{CheckReport, check_log_file_cpu_mem_info, get_entries},
+};
+
+#[derive(Parser)]
+pub struct ShardArgs {
+    /// 文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 总分片数
+    #[arg(long)]
+    pub shards: u32,
+
+    /// 当前分片编号，从 1 开始
+    #[arg(long)]
+    pub shard_index: u32,
+
+    #[command(flatten)]
+    pub matching: MatchArgs,
+
+    #[command(flatten)]
+    pub records: RecordArgs,
+
+    /// 结果写入文件，默认输出到终端
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct MergeResultsArgs {
+    /// 各分片的 `lp shard` (或 `cl --json`) 输出
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// 合并结果写入文件，默认输出到终端
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// 按相对路径哈希 (FNV-1a) 分配分片，与文件遍历顺序和机器无关
+fn shard_of(rel_path: &str, shards: u32) -> u32 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in rel_path.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    (hash % shards as u64) as u32 + 1
+}
+
+fn write_report(report: &CheckReport, output: Option<PathBuf>) -> Result<()> {
+    let content = serde_json::to_string_pretty(report)?;
+    match output {
+        Some(output) => {
+            fs::write(&output, content)?;
+            println!(
+                "write results, path: {:?}, files: {}",
+                output.display(),
+                report.files.len()
+            );
+        }
+        None => println!("{content}"),
+    }
+
+    Ok(())
+}
+
+pub fn process_shard(ctx: &AppContext, args: ShardArgs) -> Result<()> {
+    if args.shards == 0 || !(1..=args.shards).contains(&args.shard_index) {
+        bail!("❌ --shard-index should be in 1..={}", args.shards);
+    }
+
+    let path = ctx.resolve_path(args.path)?;
+    if !path.is_dir() {
+        bail!("❌ {} is not a directory", path.display());
+    }
+
+    let filters = args.matching.keywords(ctx, "cl")?;
+    let matcher = args.matching.matcher(&filters)?;
+    let boundary = args.records.boundary();
+
+    let files = get_entries(&path)
+        .into_iter()
+        .filter(|e| {
+            let rel = e.path().strip_prefix(&path).unwrap_or(e.path());
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            shard_of(&rel, args.shards) == args.shard_index
+        })
+        .collect::<Vec<_>>();
+
+    let failures = Failures::new(ctx);
+    let summaries = files
+        .par_iter()
+        .filter(|_| !failures.should_stop())
+        .filter_map(|e| {
+            let file_path = e.path();
+            check_log_file_cpu_mem_info(file_path, &matcher, &boundary, None, None)
+                .inspect_err(|e| {
+                    eprintln!("❌ check line failed, path {:?}, reason: {}", file_path, e);
+                    failures.record(file_path, e);
+                })
+                .ok()
+        })
+        .collect();
+
+    let report = CheckReport {
+        root: path,
+        filters,
+        files: summaries,
+    };
+    write_report(&report, args.output)?;
+
+    failures.finish()
+}
+
+pub fn process_merge_results(args: MergeResultsArgs) -> Result<()> {
+    let mut reports = args
+        .files
+        .iter()
+        .map(|path| load_report(path))
+        .collect::<Result<Vec<_>>>()?;
+
+    // 各分片可能在不同机器或挂载点上运行，文件以相对各自根路径的路径对应，合并后放到第一个结果的根路径下
+    let first = reports.remove(0);
+    let root = first.root;
+    let mut files = BTreeMap::new();
+    for mut summary in first.files {
+        let key = file_key(&summary.path, &root);
+        summary.path = root.join(&key);
+        files.insert(key, summary);
+    }
+
+    for (report, path) in reports.into_iter().zip(&args.files[1..]) {
+        if report.filters != first.filters {
+            bail!("❌ {} was produced with different filters", path.display());
+        }
+        if report.root != root {
+            eprintln!(
+                "⚠️ {} has a different root: {}, paths are matched relative to it",
+                path.display(),
+                report.root.display()
+            );
+        }
+        for mut summary in report.files {
+            let key = file_key(&summary.path, &report.root);
+            if files.contains_key(&key) {
+                eprintln!("⚠️ {} appears in several shards", key.display());
+            }
+            summary.path = root.join(&key);
+            files.insert(key, summary);
+        }
+    }
+
+    let report = CheckReport {
+        root,
+        filters: first.filters,
+        files: files.into_values().collect(),
+    };
+    write_report(&report, args.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_of() {
+        let paths = (0..200)
+            .map(|i| format!("node{i}/app.log"))
+            .collect::<Vec<_>>();
+        let mut counts = [0; 4];
+        for path in &paths {
+            let shard = shard_of(path, 4);
+            assert!((1..=4).contains(&shard));
+            assert_eq!(shard, shard_of(path, 4));
+            counts[shard as usize - 1] += 1;
+        }
+        assert!(counts.iter().all(|&n| n > 20));
+        assert_eq!(shard_of("app.log", 1), 1);
+    }
+}
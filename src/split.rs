@@ -0,0 +1,213 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Ok, Result, anyhow, bail};
+use clap::Parser;
+use rust_xlsxwriter::workbook::Workbook;
+
+use crate::{context::AppContext, out_name::OverwriteArgs, time::format_timestamp};
+
+#[derive(Parser)]
+pub struct SplitArgs {
+    /// 文件路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 拆分依据的关键字，一行同时包含多个关键字时归入第一个
+    #[arg(short, long, num_args = 1.., required = true)]
+    pub by: Vec<String>,
+
+    /// 输出目录，默认为源文件所在目录
+    #[arg(short, long)]
+    pub out_dir: Option<PathBuf>,
+
+    /// 输出文件名模板，支持 {stem}、{key}、{ext}、{date}
+    #[arg(
+        short,
+        long,
+        visible_alias = "out-template",
+        default_value = "{stem}_{key}.{ext}"
+    )]
+    pub name: String,
+
+    /// 同时为每个关键字输出 xlsx
+    #[arg(long, default_value_t = false)]
+    pub xlsx: bool,
+
+    #[command(flatten)]
+    pub overwrite: OverwriteArgs,
+}
+
+/// 按模板生成输出文件名，关键字中不能出现在文件名里的字符替换为 `_`，`{date}` 为当天日期 (UTC)
+fn output_name(template: &str, stem: &str, key: &str, ext: &str, date: &str) -> String {
+    let key = key
+        .chars()
+        .map(|c| {
+            if c.is_control() || r#"<>:"/\|?*"#.contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+
+    let name = template
+        .replace("{stem}", stem)
+        .replace("{key}", key.trim())
+        .replace("{ext}", ext)
+        .replace("{date}", date);
+    if ext.is_empty() {
+        name.trim_end_matches('.').to_string()
+    } else {
+        name
+    }
+}
+
+pub fn process_split(ctx: &AppContext, args: SplitArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+
+    let out_dir = match args.out_dir {
+        Some(dir) => ctx.resolve_path(dir)?,
+        None => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    fs::create_dir_all(&out_dir)?;
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let ext = path.extension().unwrap_or_default().to_string_lossy();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+    let date = &format_timestamp(now)[..10];
+    let outputs = args
+        .by
+        .iter()
+        .map(|key| out_dir.join(output_name(&args.name, &stem, key, &ext, date)))
+        .collect::<Vec<_>>();
+    if outputs.contains(&path) {
+        bail!("❌ output name template would overwrite {}", path.display());
+    }
+    let mut seen = HashMap::new();
+    for (key, output) in args.by.iter().zip(&outputs) {
+        if let Some(other) = seen.insert(output, key) {
+            bail!(
+                "❌ `{other}` and `{key}` would both write {}",
+                output.display()
+            );
+        }
+    }
+
+    let outputs = outputs
+        .iter()
+        .map(|output| args.overwrite.claim(output))
+        .collect::<Result<Vec<_>>>()?;
+    let mut writers = outputs
+        .iter()
+        .map(|output| {
+            if let Some(parent) = output.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            Ok(BufWriter::new(File::create(output)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut rows = vec![Vec::new(); args.by.len()];
+    let mut counts = vec![0; args.by.len()];
+
+    for line in BufReader::new(File::open(&path)?).lines() {
+        let line = line?;
+        let Some(i) = args.by.iter().position(|key| line.contains(key.as_str())) else {
+            continue;
+        };
+
+        writeln!(writers[i], "{line}")?;
+        counts[i] += 1;
+        if args.xlsx {
+            rows[i].push(line);
+        }
+    }
+
+    for (i, output) in outputs.iter().enumerate() {
+        writers[i].flush()?;
+        println!(
+            "write split file, key: {}, path: {:?}, lines: {}",
+            args.by[i],
+            output.display(),
+            counts[i]
+        );
+
+        if args.xlsx {
+            let xlsx = args.overwrite.claim(&output.with_extension("xlsx"))?;
+            write_to_xlsx(&rows[i], &xlsx)?;
+            println!("write xlsx, path: {:?}", xlsx.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// 第一列为时间，其余按空白拆分到后续各列
+fn write_to_xlsx<P: AsRef<Path>>(lines: &[String], path: P) -> Result<()> {
+    let mut wb = Workbook::new();
+    let ws = wb.add_worksheet_with_constant_memory();
+
+    for (row, line) in lines.iter().enumerate() {
+        let mut parts = line.split(']');
+        let mut time = parts
+            .next()
+            .ok_or_else(|| anyhow!("line should contain time"))?;
+        if time.starts_with('[') {
+            time = time.strip_prefix('[').unwrap();
+        }
+
+        let other = parts
+            .next()
+            .ok_or_else(|| anyhow!("line should contain other Lines"))?;
+        let other_parts = other.split_whitespace();
+
+        let row = row as u32;
+        ws.write_string(row, 0, time)?;
+        for (col, part) in other_parts.enumerate() {
+            ws.write_string(row, (col + 1) as u16, part)?;
+        }
+    }
+
+    wb.save(path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_name() {
+        assert_eq!(
+            output_name("{stem}_{key}.{ext}", "23", "East", "log", "2026-01-06"),
+            "23_East.log"
+        );
+        assert_eq!(
+            output_name("{key}/{stem}.{ext}", "app", "a/b: c", "log", "2026-01-06"),
+            "a_b_ c/app.log"
+        );
+        assert_eq!(
+            output_name("{stem}_{key}.{ext}", "app", "West", "", "2026-01-06"),
+            "app_West"
+        );
+        assert_eq!(
+            output_name(
+                "{stem}_{key}_{date}.{ext}",
+                "app",
+                "West",
+                "log",
+                "2026-01-06"
+            ),
+            "app_West_2026-01-06.log"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+
+use crate::context::AppContext;
+
+#[derive(Parser)]
+pub struct SplitPidArgs {
+    /// 文件路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 输出目录，默认与源文件相同
+    #[arg(short, long)]
+    pub out_dir: Option<PathBuf>,
+}
+
+pub fn process_split_pid(ctx: &AppContext, args: SplitPidArgs) -> Result<()> {
+    let path = ctx.resolve_path(args.path)?;
+    if !path.is_file() {
+        bail!("❌ {} is not a file", path.display());
+    }
+
+    let out_dir = match args.out_dir {
+        Some(dir) => ctx.resolve_path(dir)?,
+        None => path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+    };
+    fs::create_dir_all(&out_dir)?;
+
+    let content = fs::read_to_string(&path)?;
+    let mut writers: BTreeMap<String, (PathBuf, BufWriter<File>, usize)> = BTreeMap::new();
+    let mut current = "unknown".to_string();
+    for line in content.lines() {
+        if let Some(pid) = parse_pid(line) {
+            current = pid.to_string();
+        }
+
+        if !writers.contains_key(&current) {
+            let out_path = out_dir.join(pid_file_name(&path, &current));
+            let writer = BufWriter::new(File::create(&out_path)?);
+            writers.insert(current.clone(), (out_path, writer, 0));
+        }
+        let (_, writer, lines) = writers.get_mut(&current).unwrap();
+        writeln!(writer, "{line}")?;
+        *lines += 1;
+    }
+
+    for (pid, (out_path, mut writer, lines)) in writers {
+        writer.flush()?;
+        println!("pid: {pid}, lines: {lines}, path: {}", out_path.display());
+    }
+
+    Ok(())
+}
+
+/// 提取行内 `pid: 12992` 中的进程号
+fn parse_pid(line: &str) -> Option<&str> {
+    let rest = line[line.find("pid:")? + 4..].trim_start();
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+
+    (end > 0).then(|| &rest[..end])
+}
+
+fn pid_file_name(path: &Path, pid: &str) -> String {
+    let stem = path.file_stem().unwrap_or_default();
+    let ext = path.extension().unwrap_or_default();
+
+    format!(
+        "{}_pid{}{}{}",
+        stem.display(),
+        pid,
+        if ext.is_empty() { "" } else { "." },
+        ext.display(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pid() {
+        assert_eq!(
+            parse_pid("[2026-01-06 10:29:10.792] [info] [Global]  pid: 12992, total threads: 59"),
+            Some("12992")
+        );
+        assert_eq!(
+            parse_pid(
+                "[2026-01-06 10:22:50.306] [info] [Global]  tid: 17916, start: 0x7ff93b051b70"
+            ),
+            None
+        );
+        assert_eq!(parse_pid("pid: none"), None);
+        assert_eq!(
+            pid_file_name(Path::new("a/23.log"), "12992"),
+            "23_pid12992.log"
+        );
+    }
+}
@@ -0,0 +1,397 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    io::BufRead,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Ok, Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compress::open_log,
+    context::AppContext,
+    exit::Failures,
+    extractor::{Extractor, metric_extractors},
+    history::{RunResult, record_stats_run},
+    output::{OutputFormat, print_records},
+    record::{parse_line, parse_percent},
+    subcommand::get_entries,
+    table::print_table,
+    time::{format_timestamp, parse_timestamp},
+};
+
+#[derive(Parser)]
+pub struct StatsArgs {
+    /// 文件或文件夹路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 文件夹时将所有文件合并统计，默认逐个文件输出
+    #[arg(short, long, default_value_t = false)]
+    pub aggregate: bool,
+
+    /// 以 json 格式输出
+    #[arg(long, default_value_t = false)]
+    pub json: bool,
+
+    /// 同时统计这些数值指标的最小/平均/最大值：内置的 cpu、mem 或配置中的自定义指标
+    #[arg(short, long)]
+    pub metric: Vec<String>,
+}
+
+/// 一个数值指标的采样汇总
+#[derive(Clone, Copy)]
+struct MetricSummary {
+    samples: usize,
+    min: f64,
+    max: f64,
+    sum: f64,
+}
+
+impl MetricSummary {
+    fn new(value: f64) -> Self {
+        MetricSummary {
+            samples: 1,
+            min: value,
+            max: value,
+            sum: value,
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        MetricSummary {
+            samples: self.samples + other.samples,
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+            sum: self.sum + other.sum,
+        }
+    }
+}
+
+/// 一个文件 (或合并后的多个文件) 的统计
+#[derive(Default)]
+struct LogStats {
+    lines: usize,
+    /// 不符合 `[time] [level] [module] message` 结构的行，如堆栈等续行
+    unparsed: usize,
+    levels: BTreeMap<String, usize>,
+    modules: BTreeMap<String, usize>,
+    first: Option<i64>,
+    last: Option<i64>,
+    metrics: BTreeMap<String, MetricSummary>,
+    /// `cpu usage` 的峰值，与 cl 一致，记录到历史中供 `lp trend` 比较
+    cpu_peak: Option<f64>,
+}
+
+impl LogStats {
+    fn add_line(&mut self, line: &str, extractors: &[Extractor]) {
+        self.lines += 1;
+        let Some(record) = parse_line(line) else {
+            self.unparsed += 1;
+            return;
+        };
+
+        *self.levels.entry(record.level.to_lowercase()).or_insert(0) += 1;
+        *self.modules.entry(record.module.to_string()).or_insert(0) += 1;
+        if let Some(time) = parse_timestamp(record.time) {
+            self.first = Some(self.first.map_or(time, |t| t.min(time)));
+            self.last = Some(self.last.map_or(time, |t| t.max(time)));
+        }
+        if let Some(cpu) = parse_percent(record.message, "cpu usage") {
+            self.cpu_peak = Some(self.cpu_peak.map_or(cpu, |peak| peak.max(cpu)));
+        }
+        for extractor in extractors {
+            if let Some(value) = extractor.extract(record.message) {
+                let summary = MetricSummary::new(value);
+                self.metrics
+                    .entry(extractor.name().to_string())
+                    .and_modify(|s| *s = s.merge(summary))
+                    .or_insert(summary);
+            }
+        }
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.lines += other.lines;
+        self.unparsed += other.unparsed;
+        for (level, count) in other.levels {
+            *self.levels.entry(level).or_insert(0) += count;
+        }
+        for (module, count) in other.modules {
+            *self.modules.entry(module).or_insert(0) += count;
+        }
+        self.first = self.first.into_iter().chain(other.first).min();
+        self.last = self.last.into_iter().chain(other.last).max();
+        self.cpu_peak = self
+            .cpu_peak
+            .into_iter()
+            .chain(other.cpu_peak)
+            .reduce(f64::max);
+        for (name, summary) in other.metrics {
+            self.metrics
+                .entry(name)
+                .and_modify(|s| *s = s.merge(summary))
+                .or_insert(summary);
+        }
+        self
+    }
+
+    fn span_ms(&self) -> Option<i64> {
+        Some(self.last? - self.first?)
+    }
+}
+
+/// `stats --json` 输出中的一项
+#[derive(Serialize, Deserialize)]
+pub struct StatsReport {
+    pub path: PathBuf,
+    pub lines: usize,
+    pub unparsed: usize,
+    pub levels: BTreeMap<String, usize>,
+    pub modules: BTreeMap<String, usize>,
+    pub first: Option<String>,
+    pub last: Option<String>,
+    pub span_ms: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metrics: Vec<MetricReport>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MetricReport {
+    pub name: String,
+    pub unit: String,
+    pub samples: usize,
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+}
+
+impl StatsReport {
+    fn new(path: PathBuf, stats: LogStats, extractors: &[Extractor]) -> Self {
+        let metrics = extractors
+            .iter()
+            .filter_map(|extractor| {
+                let summary = stats.metrics.get(extractor.name())?;
+                Some(MetricReport {
+                    name: extractor.name().to_string(),
+                    unit: extractor.unit().to_string(),
+                    samples: summary.samples,
+                    min: summary.min,
+                    avg: summary.sum / summary.samples as f64,
+                    max: summary.max,
+                })
+            })
+            .collect();
+
+        StatsReport {
+            path,
+            lines: stats.lines,
+            unparsed: stats.unparsed,
+            span_ms: stats.span_ms(),
+            first: stats.first.map(format_timestamp),
+            last: stats.last.map(format_timestamp),
+            levels: stats.levels,
+            modules: stats.modules,
+            metrics,
+        }
+    }
+}
+
+fn file_stats(path: &Path, extractors: &[Extractor]) -> Result<LogStats> {
+    let mut stats = LogStats::default();
+    for line in open_log(path)?.lines() {
+        stats.add_line(&line?, extractors);
+    }
+
+    Ok(stats)
+}
+
+/// 时间跨度，如 `1d 02:03:04`
+fn format_span(ms: i64) -> String {
+    let secs = ms / 1000;
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    );
+    if days > 0 {
+        format!("{days}d {time}")
+    } else {
+        time
+    }
+}
+
+/// 按数量从大到小输出计数与占比
+fn print_counts(name: &str, counts: &BTreeMap<String, usize>, total: usize) {
+    let mut counts = counts.iter().collect::<Vec<_>>();
+    counts.sort_by_key(|(_, count)| Reverse(**count));
+    let rows = counts
+        .into_iter()
+        .map(|(key, count)| {
+            vec![
+                key.clone(),
+                count.to_string(),
+                format!("{:.2}%", *count as f64 * 100.0 / total.max(1) as f64),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_table(&[name, "lines", "ratio"], &rows);
+}
+
+fn print_report(report: &StatsReport) {
+    println!(
+        "file: {}, lines: {}, unparsed: {}",
+        report.path.display(),
+        report.lines,
+        report.unparsed
+    );
+    if let (Some(first), Some(last), Some(span)) = (&report.first, &report.last, report.span_ms) {
+        println!("time: {} ~ {}, span: {}", first, last, format_span(span));
+    }
+    let parsed = report.lines - report.unparsed;
+    print_counts("level", &report.levels, parsed);
+    print_counts("module", &report.modules, parsed);
+    if !report.metrics.is_empty() {
+        let rows = report
+            .metrics
+            .iter()
+            .map(|m| {
+                vec![
+                    m.name.clone(),
+                    m.samples.to_string(),
+                    format!("{:.2}{}", m.min, m.unit),
+                    format!("{:.2}{}", m.avg, m.unit),
+                    format!("{:.2}{}", m.max, m.unit),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(&["metric", "samples", "min", "avg", "max"], &rows);
+    }
+}
+
+pub fn process_stats(ctx: &AppContext, args: StatsArgs) -> Result<()> {
+    let extractors = metric_extractors(ctx, &args.metric)?;
+    let path = ctx.resolve_path(args.path)?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+
+    let mut files = if path.is_dir() {
+        get_entries(&path)
+            .into_iter()
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        vec![path.clone()]
+    };
+    files.sort();
+
+    let failures = Failures::new(ctx);
+    let stats = files
+        .into_par_iter()
+        .filter_map(|file| {
+            if failures.should_stop() {
+                return None;
+            }
+            file_stats(&file, &extractors)
+                .inspect_err(|e| {
+                    eprintln!("❌ stats failed, path {:?}, reason: {}", file, e);
+                    failures.record(&file, e);
+                })
+                .ok()
+                .map(|stats| (file, stats))
+        })
+        .collect::<Vec<_>>();
+
+    // 与 cl 一致，按单个文件记录，合并统计时也是
+    let results = stats
+        .iter()
+        .map(|(file, stats)| RunResult {
+            path: file.display().to_string(),
+            matches: 0,
+            errors: stats.levels.get("error").copied().unwrap_or(0) as i64,
+            cpu_peak: stats.cpu_peak,
+        })
+        .collect::<Vec<_>>();
+    if let Err(e) = record_stats_run(ctx, &results) {
+        eprintln!("❌ record history failed, reason: {}", e);
+    }
+
+    let reports = if args.aggregate {
+        let total = stats
+            .into_iter()
+            .map(|(_, stats)| stats)
+            .fold(LogStats::default(), LogStats::merge);
+        vec![StatsReport::new(path, total, &extractors)]
+    } else {
+        stats
+            .into_iter()
+            .map(|(file, stats)| StatsReport::new(file, stats, &extractors))
+            .collect()
+    };
+
+    let format = if args.json {
+        OutputFormat::Json
+    } else {
+        ctx.output_format()
+    };
+    if format != OutputFormat::Text {
+        print_records(format, &reports)?;
+        return failures.finish();
+    }
+
+    for report in &reports {
+        print_report(report);
+        println!();
+    }
+
+    failures.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_stats() {
+        let ctx = AppContext::new("/nonexistent/config.json");
+        let extractors = metric_extractors(&ctx, &["cpu".to_string()]).unwrap();
+        let mut a = LogStats::default();
+        a.add_line(
+            "[2026-01-06 10:29:10.765] [info] [Global]  cpu usage: 5.5%",
+            &extractors,
+        );
+        a.add_line(
+            "[2026-01-06 10:29:12.000] [ERROR] [Infer]  exception callback",
+            &extractors,
+        );
+        a.add_line("    at ModelServer::load", &extractors);
+        let mut b = LogStats::default();
+        b.add_line(
+            "[2026-01-06 09:00:00.000] [error] [Global]  cpu usage: 12.5%",
+            &extractors,
+        );
+
+        let total = a.merge(b);
+        assert_eq!(total.lines, 4);
+        assert_eq!(total.unparsed, 1);
+        assert_eq!(total.levels["error"], 2);
+        assert_eq!(total.modules["Global"], 2);
+        assert_eq!(total.first, parse_timestamp("2026-01-06 09:00:00.000"));
+        assert_eq!(total.span_ms(), Some(5_352_000));
+        assert_eq!(total.cpu_peak, Some(12.5));
+        let cpu = total.metrics["cpu"];
+        assert_eq!(
+            (cpu.samples, cpu.min, cpu.max, cpu.sum),
+            (2, 5.5, 12.5, 18.0)
+        );
+
+        assert_eq!(format_span(5_352_000), "01:29:12");
+        assert_eq!(format_span(90_061_000), "1d 01:01:01");
+    }
+}
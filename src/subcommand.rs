@@ -1,27 +1,50 @@
 use anyhow::{self, Ok, Result, bail};
 use rayon::prelude::*;
 use std::{
-    fs,
+    collections::{BTreeSet, HashSet, VecDeque},
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, BufRead, BufWriter, IsTerminal, LineWriter, Write},
+    iter,
     path::{Path, PathBuf},
-    sync::{Mutex, OnceLock},
+    sync::Arc,
 };
 
-use clap::Parser;
+use clap::{Args, Parser, ValueEnum};
+use globset::{Glob, GlobMatcher};
 use serde::{Deserialize, Serialize};
-use std::sync::LazyLock;
+use std::time::{Duration, Instant, SystemTime};
 use walkdir::{DirEntry, WalkDir};
 
-static DEFAULT_FILTERS: LazyLock<Vec<String>> = LazyLock::new(|| {
-    vec![
-        "tid:".to_string(),
-        "pid:".to_string(),
-        "cpu usage".to_string(),
-    ]
-});
-
-static CONFIG_PATH: LazyLock<PathBuf> = LazyLock::new(|| PathBuf::from("config/config.json"));
-
-static BASE_DIR: OnceLock<Mutex<PathBuf>> = OnceLock::new();
+use crate::{
+    annotate::Annotations,
+    audit::{remove_audited, replace_audited, trash_audited},
+    compress::{LogWriter, OutputCompression, is_gzip, open_log, plain_path},
+    context::AppContext,
+    entries::{EntryArgs, EntryFilter, WalkOptions},
+    estimate::estimate_check,
+    exit::{Failures, NoMatches},
+    follow::follow_file,
+    glob::{check_target, glob_files, glob_root, is_glob},
+    history::{filter_hash, record_check_run},
+    lock::{LockArgs, lock_target},
+    matcher::{MatchArgs, Matcher},
+    ordered::OrderedWriter,
+    out_name::{OutName, OverwriteArgs, mirror_path, output_path, parse_out_name},
+    output::{OutputFormat, print_records},
+    pipeline::Pipeline,
+    record::{
+        Boundary, RecordArgs, TimeWindow, parse_error_codes, parse_line, parse_percent,
+        read_records,
+    },
+    schedule::{Schedule, par_map_scheduled},
+    table::csv_line,
+    temp::InFlight,
+    time::{TimeRange, parse_duration},
+    timeout::with_timeout,
+    undo::{UndoLog, is_stashed},
+    units::{format_size, parse_fraction, parse_size},
+};
 
 #[derive(Parser)]
 pub struct BaseDirArgs {
@@ -29,64 +52,266 @@ pub struct BaseDirArgs {
     pub path: PathBuf,
 }
 
+/// cl / rl 要处理的路径，可以写在位置参数上，也可以用 `-p`
+#[derive(Args)]
+pub struct PathArgs {
+    /// 文件或文件夹路径，也可以是 glob，如 `logs/**/server_*.log`；`-` 为标准输入
+    #[arg(
+        value_name = "PATH",
+        required_unless_present = "path",
+        conflicts_with = "path"
+    )]
+    positional: Option<PathBuf>,
+
+    /// 同位置参数 PATH
+    #[arg(short, long, value_name = "PATH")]
+    path: Option<PathBuf>,
+}
+
+impl PathArgs {
+    pub fn path(&self) -> &Path {
+        self.positional
+            .as_deref()
+            .or(self.path.as_deref())
+            .expect("clap requires PATH or --path")
+    }
+
+    pub fn into_path(self) -> PathBuf {
+        self.positional
+            .or(self.path)
+            .expect("clap requires PATH or --path")
+    }
+}
+
 #[derive(Parser)]
 pub struct CheckLineArgs {
-    /// 文件路径
-    #[arg(short, long)]
-    pub path: PathBuf,
+    #[command(flatten)]
+    pub input: PathArgs,
+
+    #[command(flatten)]
+    pub matching: MatchArgs,
+
+    #[command(flatten)]
+    pub records: RecordArgs,
+
+    #[command(flatten)]
+    pub entries: EntryArgs,
+
+    /// 单个文件的处理超时，如 60s，超时的文件记为失败并继续处理其余文件
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub timeout_per_file: Option<Duration>,
+
+    /// 文件夹中文件的处理顺序，默认先处理大文件
+    #[arg(long, value_enum, default_value = "size")]
+    pub schedule: Schedule,
+
+    /// 以 JSON 输出检查结果，可用于 `lp compare`，等同于 `--format json`
+    #[arg(long, default_value_t = false, conflicts_with = "format")]
+    pub json: bool,
+
+    /// 供脚本解析的稳定输出，等同于 `--format porcelain`
+    #[arg(long, default_value_t = false, conflicts_with_all = ["format", "json"])]
+    pub porcelain: bool,
+
+    /// 输出格式，junit 时每个关键字作为一个用例，有命中行即失败
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: CheckFormat,
+
+    /// 同时输出命中的行及其行号，类似 grep
+    #[arg(long, default_value_t = false)]
+    pub show: bool,
+
+    /// 每个文件命中 N 行后即停止扫描，计数与行数只统计到停止处
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub max_count: Option<u64>,
+
+    /// 只报告每个文件是否包含关键字，命中第一行即停止扫描，等同于 `--max-count 1`
+    #[arg(long, default_value_t = false, conflicts_with = "max_count")]
+    pub first_match: bool,
+
+    /// 每个文件最多输出的命中行数，不影响计数
+    #[arg(long, value_name = "N", requires = "show")]
+    pub max_matches: Option<usize>,
+
+    /// 同时输出命中行之前的行数，隐含 --show
+    #[arg(short = 'B', long, value_name = "N")]
+    pub before: Option<usize>,
+
+    /// 同时输出命中行之后的行数，隐含 --show
+    #[arg(short = 'A', long, value_name = "N")]
+    pub after: Option<usize>,
 
-    /// 需要过滤的关键字
-    #[arg(short, long)]
-    pub filters: Option<Vec<String>>,
+    /// 同时输出命中行前后的行数，等同于同时指定 -A 与 -B
+    #[arg(short = 'C', long, value_name = "N")]
+    pub context: Option<usize>,
+
+    /// 检查完成后持续输出文件新写入的命中行，等同于 `lp follow`，仅支持单个文件
+    #[arg(long, default_value_t = false, conflicts_with_all = ["json", "format", "porcelain"])]
+    pub follow: bool,
+
+    /// 只抽样读取每个大文件的这一比例 (首尾块与随机块，缺省为 1%)，外推命中行数并给出 95% 置信区间；
+    /// 小文件与 `.gz` 仍精确计数，按行匹配
+    #[arg(
+        long,
+        value_name = "FRACTION",
+        num_args = 0..=1,
+        default_missing_value = "0.01",
+        value_parser = parse_fraction,
+        conflicts_with_all = ["json", "porcelain", "format", "show", "max_count", "first_match", "before", "after", "context", "follow", "annotate"]
+    )]
+    pub estimate: Option<f64>,
+
+    /// 在命中行下输出配置 `annotations` 中对应的已知问题说明，隐含 --show
+    #[arg(long, default_value_t = false)]
+    pub annotate: bool,
+}
+
+/// cl 的输出格式
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum CheckFormat {
+    Text,
+    Json,
+    Junit,
+    /// 每个文件一行 `path\tmatches\tlines\tbytes`，不输出其他内容，格式不随版本变化
+    Porcelain,
 }
 
 #[derive(Parser)]
 pub struct RemoveLineArgs {
-    /// 文件路径
-    #[arg(short, long)]
-    pub path: PathBuf,
+    #[command(flatten)]
+    pub input: PathArgs,
+
+    #[command(flatten)]
+    pub matching: MatchArgs,
 
-    /// 需要过滤的关键字
-    #[arg(short, long)]
-    pub filters: Option<Vec<String>>,
+    #[command(flatten)]
+    pub records: RecordArgs,
+
+    #[command(flatten)]
+    pub entries: EntryArgs,
+
+    /// 单个文件的处理超时，如 60s，超时的文件记为失败并继续处理其余文件
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub timeout_per_file: Option<Duration>,
+
+    /// 文件夹中文件的处理顺序，默认先处理大文件
+    #[arg(long, value_enum, default_value = "size")]
+    pub schedule: Schedule,
 
     /// 需要过滤掉还是保留指定的关键字
     #[arg(short, long, default_value_t = false)]
     pub keep: bool,
+
+    /// 保留的行 (记录) 写到标准输出而不生成 xxx_filtered 文件，便于接其他命令；
+    /// 文件夹与 glob 按路径顺序依次输出，统计输出到标准错误
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["in_place", "dry_run", "stats", "compress", "out_name", "out_dir", "provenance", "max_remove_ratio"]
+    )]
+    pub stdout: bool,
+
+    /// 额外输出每个关键字的过滤统计 (xxx_filtered.stats.json)
+    #[arg(long, default_value_t = false)]
+    pub stats: bool,
+
+    /// 直接改写原文件 (先写临时文件再替换)，不生成 xxx_filtered 文件
+    #[arg(long, default_value_t = false)]
+    pub in_place: bool,
+
+    /// 改写前将原文件保留为备份，缺省后缀为 .bak
+    #[arg(
+        long,
+        value_name = "SUFFIX",
+        num_args = 0..=1,
+        default_missing_value = ".bak",
+        requires = "in_place"
+    )]
+    pub backup: Option<String>,
+
+    /// 只报告每个文件将删除的行数，不写任何文件
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    /// 输出文件的压缩方式，auto 时与输入一致 (`.gz` 输入输出 `xxx_filtered.log.gz`)；
+    /// `--in-place` 时总是与原文件一致
+    #[arg(long, value_enum, default_value = "auto", conflicts_with = "in_place")]
+    pub compress: OutputCompression,
+
+    /// 输出文件名模板，支持 {stem}、{ext}、{date}、{preset}、{profile}，如 `{stem}.{date}.clean.{ext}`；
+    /// 默认为 `{stem}_filtered.{ext}`，`.gz` 输入按解压后的文件名计算
+    #[arg(
+        long,
+        visible_alias = "out-template",
+        value_name = "TEMPLATE",
+        value_parser = parse_out_name,
+        conflicts_with = "in_place"
+    )]
+    pub out_name: Option<String>,
+
+    /// 输出目录，覆盖配置中的 `output_dir`；处理文件夹或 glob 时保留其下的目录结构
+    #[arg(long, value_name = "DIR", conflicts_with = "in_place")]
+    pub out_dir: Option<PathBuf>,
+
+    /// 额外输出 xxx_filtered.provenance.csv，按输出顺序列出每条保留的行 (记录) 的
+    /// 源文件、首行行号、字节偏移与行数，便于追溯到原始日志
+    #[arg(long, default_value_t = false)]
+    pub provenance: bool,
+
+    /// 删除的行 (记录) 超过该比例时跳过该文件并记为失败，防止过宽的关键字删掉大部分日志，如 0.9 或 90%；
+    /// 用 `--ignore-ratio` 照常写入
+    #[arg(long, value_name = "RATIO", value_parser = parse_fraction)]
+    pub max_remove_ratio: Option<f64>,
+
+    /// 忽略 `--max-remove-ratio`，照常写入；`--force` 只用于覆盖已存在的输出文件，不跳过该检查
+    #[arg(long, default_value_t = false, requires = "max_remove_ratio")]
+    pub ignore_ratio: bool,
+
+    #[command(flatten)]
+    pub overwrite: OverwriteArgs,
+
+    #[command(flatten)]
+    pub lock: LockArgs,
 }
 
 #[derive(Parser)]
 pub struct RemoveFileArgs {
-    /// 文件路径
+    /// 文件或文件夹路径，也可以是 glob，如 `logs/**/*.log.1`
     pub path: PathBuf,
-}
 
-#[derive(Serialize, Deserialize)]
-struct Config {
-    base_dir: PathBuf,
-}
+    /// 只列出将要删除的文件，不实际删除
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
 
-pub fn get_base_dir_locked() -> Result<&'static Mutex<PathBuf>> {
-    let config = fs::read_to_string(CONFIG_PATH.as_path())?;
-    let config: Config = serde_json::from_str(&config)?;
-    let base_dir = BASE_DIR.get_or_init(|| Mutex::new(config.base_dir));
+    /// 移到系统回收站，可从回收站恢复 (默认)
+    #[arg(long, default_value_t = false, conflicts_with = "permanent")]
+    pub trash: bool,
 
-    Ok(base_dir)
-}
+    /// 永久删除，不经过回收站
+    #[arg(long, default_value_t = false)]
+    pub permanent: bool,
 
-fn config_base_dir<P: AsRef<Path>>(base_dir: P) -> Result<()> {
-    let config = Config {
-        base_dir: base_dir.as_ref().to_path_buf(),
-    };
+    /// 删除文件夹时不再确认，非交互环境删除文件夹时必须指定
+    #[arg(short, long, default_value_t = false)]
+    pub yes: bool,
 
-    let config = serde_json::to_string_pretty(&config)?;
-    println!("config: {config:#?}");
-    fs::write(CONFIG_PATH.as_path(), config)?;
+    /// 只删除修改时间早于该时长之前的文件，如 `30d`；指定任一筛选条件时逐个删除符合的文件，保留目录
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub older_than: Option<Duration>,
 
-    Ok(())
+    /// 只删除文件名匹配该 glob 的文件，如 `*.log`
+    #[arg(long)]
+    pub pattern: Option<String>,
+
+    /// 只删除不小于该大小的文件，如 `100M`
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub min_size: Option<u64>,
+
+    #[command(flatten)]
+    pub lock: LockArgs,
 }
 
-pub fn set_base_dir(args: BaseDirArgs) -> Result<()> {
+pub fn set_base_dir(ctx: &AppContext, args: BaseDirArgs) -> Result<()> {
     if !args.path.exists() {
         bail!("❌ input path not exists");
     }
@@ -95,182 +320,1890 @@ pub fn set_base_dir(args: BaseDirArgs) -> Result<()> {
         bail!("❌ input path is not a directory");
     }
 
-    config_base_dir(&args.path)?;
+    ctx.update_config(|config| config.base_dir = args.path.clone())?;
     println!("base dir set to: {}", args.path.display());
 
     Ok(())
 }
 
-pub fn get_base_dir() -> Result<BaseDirArgs> {
-    let base_dir = get_base_dir_locked()?.lock().unwrap();
-
+pub fn get_base_dir(ctx: &AppContext) -> Result<BaseDirArgs> {
     Ok(BaseDirArgs {
-        path: base_dir.to_path_buf(),
+        path: ctx.base_dir()?.to_path_buf(),
     })
 }
 
-pub fn process_check_line(args: CheckLineArgs) -> Result<()> {
-    let path = if args.path.is_absolute() {
-        args.path
+pub fn process_check_line(ctx: &AppContext, args: CheckLineArgs) -> Result<()> {
+    let stdin = is_stdin(args.input.path());
+    let path = if stdin {
+        args.input.into_path()
     } else {
-        let base_dir = get_base_dir_locked()?.lock().unwrap();
-        base_dir.join(&args.path)
+        ctx.resolve_path(args.input.into_path())?
     };
+    let format = if args.json {
+        CheckFormat::Json
+    } else if args.porcelain {
+        CheckFormat::Porcelain
+    } else {
+        args.format
+    };
+    // `lp --format json|ndjson` 时每个文件输出一条含命中行的记录
+    let records_format = (format == CheckFormat::Text)
+        .then(|| ctx.output_format())
+        .filter(|f| *f != OutputFormat::Text);
+
+    if format == CheckFormat::Text && records_format.is_none() {
+        println!("path:{}", path.display());
+    }
 
-    println!("path:{}", path.display());
+    if stdin && (args.follow || args.estimate.is_some()) {
+        bail!("❌ --follow and --estimate do not support stdin");
+    }
+    if !stdin {
+        check_target(&path)?;
+    }
+    let is_dir = !stdin && (path.is_dir() || is_glob(&path));
+    if args.follow && is_dir {
+        bail!("❌ --follow only supports a single file");
+    }
 
-    if !path.exists() {
-        bail!("❌ {} not exists", path.display());
+    let filters = args.matching.keywords(ctx, "cl")?;
+    let matcher = Arc::new(args.matching.matcher(&filters)?);
+    if let Some(fraction) = args.estimate {
+        if !matches!(args.records.boundary(), Boundary::Line) {
+            bail!("❌ --estimate only supports line mode");
+        }
+        let entries = args.entries.filter()?;
+        return estimate_check(
+            ctx,
+            &path,
+            is_dir,
+            &matcher,
+            fraction,
+            &entries,
+            args.schedule,
+        );
     }
 
-    let filters = args.filters.unwrap_or(DEFAULT_FILTERS.to_vec());
+    let annotations = args.annotate.then(|| ctx.annotations()).transpose()?;
+    if annotations.as_ref().is_some_and(Annotations::is_empty) {
+        bail!("❌ no annotations configured, add `annotations` to config to use --annotate");
+    }
 
-    if path.is_dir() {
-        check_log_dir_cpu_mem_infos(path, &filters);
+    let options = Arc::new(CheckOptions {
+        boundary: args.records.boundary(),
+        timeout: args.timeout_per_file,
+        schedule: args.schedule,
+        show: (args.show
+            || args.annotate
+            || records_format.is_some()
+            || args.before.is_some()
+            || args.after.is_some()
+            || args.context.is_some())
+        .then(|| ShowOptions {
+            max_matches: args.max_matches.unwrap_or(usize::MAX),
+            before: args.before.or(args.context).unwrap_or(0),
+            after: args.after.or(args.context).unwrap_or(0),
+        }),
+        max_count: if args.first_match {
+            Some(1)
+        } else {
+            args.max_count.map(|n| n as usize)
+        },
+        entries: args.entries.filter()?,
+    });
+    let (mut summaries, failed) = if is_dir {
+        check_log_dir_cpu_mem_infos(ctx, &path, &matcher, &options)
+    } else if stdin {
+        let summary = check_records(
+            io::stdin().lock(),
+            &path,
+            &matcher,
+            &options.boundary,
+            options.show,
+            options.max_count,
+        );
+        (vec![summary?], Ok(()))
     } else {
-        check_log_file_cpu_mem_info(path, &filters)?;
+        ctx.progress().started([path.as_path()]);
+        let summary = ctx
+            .progress()
+            .file(&path, || check_with_timeout(&path, &matcher, &options));
+        ctx.progress().finished();
+        (vec![summary?], Ok(()))
+    };
+    if let Some(annotations) = &annotations {
+        for summary in &mut summaries {
+            annotations.annotate(summary);
+        }
+    }
+
+    let filter_hash = filter_hash(&filters, &args.matching, &args.records);
+    // 因 `--max-count` 提前停止的计数不完整，不与完整的运行比较
+    let stopped_early = summaries.iter().any(|summary| summary.stopped_early);
+    if !stdin
+        && !stopped_early
+        && let Err(e) = record_check_run(ctx, &filter_hash, &summaries)
+    {
+        eprintln!("❌ record history failed, reason: {}", e);
+    }
+
+    let report = CheckReport {
+        root: path,
+        filters,
+        files: summaries,
+    };
+    if let Some(records_format) = records_format {
+        // 按路径排序，不受处理顺序影响
+        let mut records = report
+            .files
+            .iter()
+            .map(CheckRecord::new)
+            .collect::<Vec<_>>();
+        records.sort_by(|a, b| a.file.cmp(b.file));
+        print_records(records_format, &records)?;
+        return check_status(&report, failed);
+    }
+    match format {
+        CheckFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        CheckFormat::Junit => print!("{}", junit_report(&report)),
+        CheckFormat::Porcelain => {
+            // 按路径排序，不受处理顺序影响
+            let mut files = report.files.iter().collect::<Vec<_>>();
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            for summary in files {
+                println!("{}", porcelain_line(summary));
+            }
+        }
+        CheckFormat::Text => {
+            for summary in &report.files {
+                if args.first_match {
+                    let found = if summary.matches > 0 { "yes" } else { "no" };
+                    println!(
+                        "file: {}, contains keywords: {found}",
+                        summary.path.display()
+                    );
+                    continue;
+                }
+                let stopped = if summary.stopped_early {
+                    ", stopped at --max-count"
+                } else {
+                    ""
+                };
+                println!(
+                    "file: {}, keyword lines: {}{stopped}",
+                    summary.path.display(),
+                    summary.matches
+                );
+                // 与 grep 一致：命中行用 `:`，上下文行用 `-`，不连续的片段之间输出 `--`
+                let mut next_line = None;
+                for matched in &summary.matched_lines {
+                    if next_line.is_some_and(|line| line != matched.line) {
+                        println!("--");
+                    }
+                    let sep = if matched.context { '-' } else { ':' };
+                    println!(
+                        "{}{sep}{}{sep} {}",
+                        summary.path.display(),
+                        matched.line,
+                        matched.text
+                    );
+                    for note in &matched.annotations {
+                        println!("  ↳ {note}");
+                    }
+                    next_line = Some(matched.line + matched.text.matches('\n').count() + 1);
+                }
+            }
+        }
+    }
+
+    if args.follow {
+        follow_file(&report.root, &matcher, None)?;
+    }
+
+    check_status(&report, failed)
+}
+
+/// 与 grep 一致：有文件失败时为错误，否则没有命中时以退出码 1 结束
+fn check_status(report: &CheckReport, failed: Result<()>) -> Result<()> {
+    failed?;
+    if report.files.iter().all(|summary| summary.matches == 0) {
+        return Err(NoMatches.into());
     }
 
     Ok(())
 }
 
-pub fn process_remove_line(args: RemoveLineArgs) -> Result<()> {
-    let path = if args.path.is_absolute() {
-        args.path
+pub fn process_remove_line(ctx: &AppContext, args: RemoveLineArgs) -> Result<()> {
+    if args.in_place && !args.dry_run {
+        ctx.ensure_writable("rl --in-place")?;
+    }
+    if is_stdin(args.input.path()) {
+        return remove_stdin_lines(ctx, args);
+    }
+    let path = ctx.resolve_path(args.input.into_path())?;
+    check_target(&path)?;
+    let glob = is_glob(&path);
+    if args.stdout {
+        let matcher = remove_matcher(ctx, &args.matching)?;
+        let files = if glob || path.is_dir() {
+            let mut files = filtered_entries(&path, &args.entries.filter()?)
+                .into_iter()
+                .map(DirEntry::into_path)
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        } else {
+            vec![path]
+        };
+        return remove_lines_to_stdout(
+            &files,
+            &matcher,
+            args.keep,
+            &args.records.boundary(),
+            args.matching.time_range,
+        );
+    }
+
+    // 同一目标同时只允许一个 rl/rf 写入，避免定时任务与手动执行的输出互相覆盖；glob 时锁其起点目录
+    let lock_path = if glob { glob_root(&path) } else { path.clone() };
+    let _lock = (!args.dry_run)
+        .then(|| lock_target(&lock_path, args.lock))
+        .transpose()?;
+
+    let matcher = Arc::new(remove_matcher(ctx, &args.matching)?);
+    let out_name = args
+        .out_name
+        .map(|template| OutName::new(ctx, template, &args.matching.preset))
+        .transpose()?;
+    let output_dir = match args.out_dir {
+        Some(dir) => Some(ctx.resolve_path(dir)?),
+        None => ctx.output_dir()?,
+    };
+    // 按模板命名的结果不带 `_filtered`，输出目录也可能在要处理的文件夹内，同样需要跳过
+    let entries = args
+        .entries
+        .filter()?
+        .skip_outputs(out_name.as_ref())?
+        .skip_dir(output_dir.as_deref())
+        .skip_backups(args.backup.as_deref());
+    let options = Arc::new(RemoveOptions {
+        keep: args.keep,
+        time_range: args.matching.time_range,
+        stats: args.stats,
+        boundary: args.records.boundary(),
+        in_place: args.in_place,
+        backup: args.backup,
+        dry_run: args.dry_run,
+        timeout: args.timeout_per_file,
+        schedule: args.schedule,
+        compress: args.compress,
+        out_name,
+        provenance: args.provenance,
+        max_remove_ratio: args.max_remove_ratio.filter(|_| !args.ignore_ratio),
+        overwrite: args.overwrite,
+        output_dir,
+        entries,
+        root: if glob {
+            glob_root(&path)
+        } else if path.is_dir() {
+            path.clone()
+        } else {
+            path.parent().unwrap_or(&path).to_path_buf()
+        },
+        undo: (args.in_place && !args.dry_run).then(|| UndoLog::new("rl")),
+    });
+
+    let result = if glob || path.is_dir() {
+        remove_log_dir_cpu_mem_infos(ctx, &path, &matcher, &options)
     } else {
-        let base_dir = get_base_dir_locked()?.lock().unwrap();
-        base_dir.join(&args.path)
+        ctx.progress().started([path.as_path()]);
+        let result = ctx.progress().file(&path, || {
+            remove_with_timeout(ctx, &path, &matcher, &options)
+        });
+        ctx.progress().finished();
+        result.map(|_| ())
     };
+    if let Some(undo) = &options.undo
+        && let Err(e) = undo.save(ctx)
+    {
+        eprintln!("❌ save undo log failed, reason: {}", e);
+    }
+
+    result
+}
 
-    if !path.exists() {
-        bail!("❌ {} not exists", path.display());
+/// `rl -p -`：从标准输入读取，保留的行逐条写到标准输出，统计输出到标准错误
+fn remove_stdin_lines(ctx: &AppContext, args: RemoveLineArgs) -> Result<()> {
+    if args.in_place
+        || args.dry_run
+        || args.stats
+        || args.provenance
+        || args.out_name.is_some()
+        || args.out_dir.is_some()
+        || args.max_remove_ratio.is_some()
+    {
+        bail!(
+            "❌ --in-place, --dry-run, --stats, --provenance, --out-name, --out-dir and --max-remove-ratio do not support stdin"
+        );
     }
 
-    let filters = args.filters.unwrap_or(DEFAULT_FILTERS.to_vec());
-    let keep = args.keep;
+    let matcher = remove_matcher(ctx, &args.matching)?;
+    let counts = filter_stream(
+        io::stdin().lock(),
+        LineWriter::new(io::stdout().lock()),
+        &matcher,
+        args.keep,
+        &args.records.boundary(),
+        args.matching.time_range,
+    )?;
+    eprintln!("stdin: {}", counts.summary());
 
-    if path.is_dir() {
-        remove_log_dir_cpu_mem_infos(&path, &filters, keep);
-    } else {
-        remove_log_file_cpu_mem_info(&path, &filters, keep)?;
+    Ok(())
+}
+
+/// `rl --stdout`：依次过滤各个文件，保留的行 (记录) 逐条写到标准输出，每个文件的统计输出到标准错误
+fn remove_lines_to_stdout(
+    files: &[PathBuf],
+    matcher: &Matcher,
+    keep: bool,
+    boundary: &Boundary,
+    time_range: TimeRange,
+) -> Result<()> {
+    let mut stdout = LineWriter::new(io::stdout().lock());
+    for file in files {
+        let counts = filter_stream(
+            open_log(file)?,
+            &mut stdout,
+            matcher,
+            keep,
+            boundary,
+            time_range,
+        )?;
+        eprintln!("{}: {}", file.display(), counts.summary());
+    }
+
+    Ok(())
+}
+
+pub fn process_remove_file(ctx: &AppContext, args: RemoveFileArgs) -> Result<()> {
+    if !args.dry_run {
+        ctx.ensure_writable("rf")?;
+    }
+    let filter = RemoveFilter::new(&args)?;
+    let path = ctx.resolve_path(args.path.clone())?;
+    check_target(&path)?;
+    let glob = is_glob(&path);
+
+    if args.dry_run {
+        let files = remove_candidates(&path, glob, filter.as_ref())?;
+        for (size, file) in &files {
+            println!("🗑 {} ({})", file.display(), format_size(*size));
+        }
+        println!(
+            "files to remove: {}, size: {}",
+            files.len(),
+            format_size(files.iter().map(|(size, _)| size).sum())
+        );
+        println!("dry run, nothing removed");
+        return Ok(());
+    }
+
+    // 移到回收站的路径记入操作日志，可用 `lp undo` 恢复；`--permanent` 无法撤销
+    let undo = UndoLog::new("rf");
+    let remove = |path: &Path| {
+        if args.permanent {
+            remove_audited(ctx, "rf", path)
+        } else {
+            trash_audited(ctx, "rf", path)?;
+            undo.trashed(path);
+            Ok(())
+        }
+    };
+    let result = remove_targets(&path, glob, filter.as_ref(), &args, remove);
+    if let Err(e) = undo.save(ctx) {
+        eprintln!("❌ save undo log failed, reason: {}", e);
+    }
+
+    result
+}
+
+/// rf 删除目标：按筛选条件逐个删除文件、删除 glob 匹配的文件，或删除整个文件/文件夹
+fn remove_targets(
+    path: &Path,
+    glob: bool,
+    filter: Option<&RemoveFilter>,
+    args: &RemoveFileArgs,
+    remove: impl Fn(&Path) -> Result<()>,
+) -> Result<()> {
+    if let Some(filter) = filter {
+        let root = if glob {
+            glob_root(path)
+        } else {
+            path.to_path_buf()
+        };
+        let _lock = lock_target(&root, args.lock)?;
+        let files = remove_candidates(path, glob, Some(filter))?;
+        for (_, file) in &files {
+            remove(file)?;
+        }
+        println!(
+            "removed files: {}, size: {}",
+            files.len(),
+            format_size(files.iter().map(|(size, _)| size).sum())
+        );
+        return Ok(());
+    }
+
+    if glob {
+        let _lock = lock_target(&glob_root(path), args.lock)?;
+        for entry in glob_files(path, WalkOptions::default())? {
+            remove(entry.path())?;
+        }
+        return Ok(());
+    }
+
+    if path.is_dir() && !args.yes && !confirm_remove_dir(path, args.permanent)? {
+        println!("cancelled, nothing removed");
+        return Ok(());
+    }
+    let _lock = lock_target(path, args.lock)?;
+    remove(path)?;
+    if !args.permanent {
+        println!("moved to trash, path: {:?}", path.display());
     }
 
     Ok(())
 }
 
-pub fn process_remove_file(args: RemoveFileArgs) -> Result<()> {
-    let path = if args.path.is_absolute() {
-        args.path
+/// rf 按修改时间、文件名与大小筛选要删除的文件，条件同时满足才删除
+struct RemoveFilter {
+    older_than: Option<Duration>,
+    pattern: Option<GlobMatcher>,
+    min_size: Option<u64>,
+    now: SystemTime,
+}
+
+impl RemoveFilter {
+    /// 没有指定任何筛选条件时返回 `None`
+    fn new(args: &RemoveFileArgs) -> Result<Option<Self>> {
+        if args.older_than.is_none() && args.pattern.is_none() && args.min_size.is_none() {
+            return Ok(None);
+        }
+        let pattern = args
+            .pattern
+            .as_deref()
+            .map(|p| {
+                Glob::new(p)
+                    .map(|g| g.compile_matcher())
+                    .map_err(|e| anyhow::anyhow!("❌ invalid --pattern `{p}`: {e}"))
+            })
+            .transpose()?;
+
+        Ok(Some(RemoveFilter {
+            older_than: args.older_than,
+            pattern,
+            min_size: args.min_size,
+            now: SystemTime::now(),
+        }))
+    }
+
+    fn is_match(&self, name: &OsStr, meta: &fs::Metadata) -> bool {
+        if let Some(pattern) = &self.pattern
+            && !pattern.is_match(name)
+        {
+            return false;
+        }
+        if let Some(min_size) = self.min_size
+            && meta.len() < min_size
+        {
+            return false;
+        }
+        if let Some(older_than) = self.older_than {
+            let age = meta
+                .modified()
+                .ok()
+                .and_then(|modified| self.now.duration_since(modified).ok());
+            return age.is_some_and(|age| age >= older_than);
+        }
+
+        true
+    }
+}
+
+/// rf 要删除的文件及其大小：glob 匹配或目录下的所有文件，再按 `filter` 筛选
+fn remove_candidates(
+    path: &Path,
+    glob: bool,
+    filter: Option<&RemoveFilter>,
+) -> Result<Vec<(u64, PathBuf)>> {
+    let entries = if glob {
+        glob_files(path, WalkOptions::default())?
     } else {
-        let base_dir = get_base_dir_locked()?.lock().unwrap();
-        base_dir.join(&args.path)
+        WalkDir::new(path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .collect()
     };
 
-    if !path.exists() {
-        bail!("❌ {} not exists", path.display());
+    Ok(entries
+        .into_iter()
+        .filter(|e| !is_stashed(e.path()))
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            if let Some(filter) = filter
+                && !filter.is_match(e.file_name(), &meta)
+            {
+                return None;
+            }
+            Some((meta.len(), e.into_path()))
+        })
+        .collect())
+}
+
+/// 删除文件夹前在终端确认，非交互环境 (如脚本中) 需指定 `--yes`
+fn confirm_remove_dir(path: &Path, permanent: bool) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        bail!(
+            "❌ {} is a directory, pass --yes to confirm removing it",
+            path.display()
+        );
     }
 
-    if path.is_dir() {
-        fs::remove_dir_all(path)?;
+    let (files, size) = WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .fold((0, 0), |(files, size), e| {
+            (files + 1, size + e.metadata().map_or(0, |m| m.len()))
+        });
+    let action = if permanent {
+        "permanently delete"
     } else {
-        fs::remove_file(&path)?;
+        "move to trash"
+    };
+    print!(
+        "{action} directory {} ({files} files, {})? [y/N] ",
+        path.display(),
+        format_size(size)
+    );
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// cl 的检查选项
+struct CheckOptions {
+    boundary: Boundary,
+    timeout: Option<Duration>,
+    schedule: Schedule,
+    /// 记录命中行的方式，`None` 时不记录
+    show: Option<ShowOptions>,
+    /// 命中这么多行后停止扫描该文件
+    max_count: Option<usize>,
+    /// 文件夹中要处理的文件
+    entries: EntryFilter,
+}
+
+/// `cl --show` 记录命中行的方式
+#[derive(Clone, Copy)]
+pub struct ShowOptions {
+    /// 最多记录的命中行数
+    pub max_matches: usize,
+    /// 命中行之前的上下文行数
+    pub before: usize,
+    /// 命中行之后的上下文行数
+    pub after: usize,
+}
+
+/// 流式收集命中行及其上下文，只缓存最近 `before` 行
+struct MatchCollector {
+    options: ShowOptions,
+    lines: Vec<MatchedLine>,
+    shown: usize,
+    before: VecDeque<MatchedLine>,
+    after_left: usize,
+}
+
+impl MatchCollector {
+    fn new(options: ShowOptions) -> Self {
+        MatchCollector {
+            options,
+            lines: Vec::new(),
+            shown: 0,
+            before: VecDeque::new(),
+            after_left: 0,
+        }
     }
 
-    Ok(())
+    fn push(&mut self, line: usize, text: &str, is_match: bool) {
+        if is_match && self.shown < self.options.max_matches {
+            self.lines.extend(self.before.drain(..));
+            self.lines.push(MatchedLine {
+                line,
+                text: text.to_string(),
+                context: false,
+                annotations: Vec::new(),
+            });
+            self.shown += 1;
+            self.after_left = self.options.after;
+        } else if self.after_left > 0 {
+            self.lines.push(MatchedLine {
+                line,
+                text: text.to_string(),
+                context: true,
+                annotations: Vec::new(),
+            });
+            self.after_left -= 1;
+        } else if self.options.before > 0 && self.shown < self.options.max_matches {
+            if self.before.len() == self.options.before {
+                self.before.pop_front();
+            }
+            self.before.push_back(MatchedLine {
+                line,
+                text: text.to_string(),
+                context: true,
+                annotations: Vec::new(),
+            });
+        }
+    }
 }
 
-fn check_log_dir_cpu_mem_infos<P: AsRef<Path>>(dir: P, filters: &[String]) {
-    let entries = get_entries(dir);
+fn check_with_timeout(
+    path: &Path,
+    matcher: &Arc<Matcher>,
+    options: &Arc<CheckOptions>,
+) -> Result<CheckSummary> {
+    let path = path.to_path_buf();
+    let matcher = Arc::clone(matcher);
+    let timeout = options.timeout;
+    let options = Arc::clone(options);
+    with_timeout(timeout, move || {
+        check_log_file_cpu_mem_info(
+            &path,
+            &matcher,
+            &options.boundary,
+            options.show,
+            options.max_count,
+        )
+    })
+}
 
-    entries.par_iter().for_each(|e| {
+fn check_log_dir_cpu_mem_infos<P: AsRef<Path>>(
+    ctx: &AppContext,
+    dir: P,
+    matcher: &Arc<Matcher>,
+    options: &Arc<CheckOptions>,
+) -> (Vec<CheckSummary>, Result<()>) {
+    let entries = filtered_entries(dir, &options.entries);
+    let failures = Failures::new(ctx);
+    let progress = ctx.progress();
+    progress.started(entries.iter().map(|e| e.path()));
+
+    let summaries = par_map_scheduled("cl", &entries, options.schedule, |e| {
+        if failures.should_stop() {
+            return None;
+        }
         let file_path = e.path();
-        if let Err(e) = check_log_file_cpu_mem_info(file_path, filters) {
-            println!("❌ check line failed, path {:?}, reason: {}", file_path, e);
+        progress
+            .file(file_path, || {
+                check_with_timeout(file_path, matcher, options)
+            })
+            .inspect_err(|e| {
+                eprintln!("❌ check line failed, path {:?}, reason: {}", file_path, e);
+                failures.record(file_path, e);
+            })
+            .ok()
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+    progress.finished();
+
+    (summaries, failures.finish())
+}
+
+/// 单个文件的检查结果
+#[derive(Serialize, Deserialize)]
+pub struct CheckSummary {
+    pub path: PathBuf,
+    pub matches: usize,
+    pub errors: usize,
+    pub cpu_peak: Option<f64>,
+    /// 文件的总行数与字节数 (压缩文件为压缩后的大小)
+    #[serde(default)]
+    pub lines: usize,
+    #[serde(default)]
+    pub bytes: u64,
+    /// 因 `--max-count` / `--first-match` 提前停止，计数与行数只统计到停止处
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stopped_early: bool,
+    #[serde(default)]
+    pub error_codes: BTreeSet<String>,
+    /// 与 `CheckReport::filters` 一一对应的命中数
+    #[serde(default)]
+    pub filter_matches: Vec<usize>,
+    /// `--show` 时记录的命中行
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_lines: Vec<MatchedLine>,
+}
+
+/// 一条命中的行 (或记录)，`line` 为起始行号，从 1 开始；`context` 表示是命中行周围的上下文
+#[derive(Serialize, Deserialize)]
+pub struct MatchedLine {
+    pub line: usize,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub context: bool,
+    /// `--annotate` 时附上的已知问题说明
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<String>,
+}
+
+/// `lp --format json|ndjson cl` 中一个文件的记录
+#[derive(Serialize)]
+struct CheckRecord<'a> {
+    file: &'a Path,
+    match_count: usize,
+    matches: Vec<CheckMatch<'a>>,
+}
+
+#[derive(Serialize)]
+struct CheckMatch<'a> {
+    line_no: usize,
+    text: &'a str,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    annotations: &'a [String],
+}
+
+impl<'a> CheckRecord<'a> {
+    fn new(summary: &'a CheckSummary) -> Self {
+        CheckRecord {
+            file: &summary.path,
+            match_count: summary.matches,
+            matches: summary
+                .matched_lines
+                .iter()
+                .filter(|matched| !matched.context)
+                .map(|matched| CheckMatch {
+                    line_no: matched.line,
+                    text: &matched.text,
+                    annotations: &matched.annotations,
+                })
+                .collect(),
         }
-    });
+    }
 }
 
-fn check_log_file_cpu_mem_info<P: AsRef<Path>>(path: P, filters: &[String]) -> Result<()> {
-    let content = fs::read_to_string(&path)?;
-    let lines = content
-        .lines()
-        .filter(|&s| contains_keyword(s, filters))
-        .collect::<Vec<_>>();
+/// `cl --json` 输出
+#[derive(Serialize, Deserialize)]
+pub struct CheckReport {
+    pub root: PathBuf,
+    pub filters: Vec<String>,
+    pub files: Vec<CheckSummary>,
+}
 
-    println!(
-        "file: {}, keyword lines: {}",
-        path.as_ref().display(),
-        lines.len()
-    );
+/// 检查单个文件，`show` 不为空时同时记录命中行，命中 `max_count` 行后停止扫描
+pub fn check_log_file_cpu_mem_info<P: AsRef<Path>>(
+    path: P,
+    matcher: &Matcher,
+    boundary: &Boundary,
+    show: Option<ShowOptions>,
+    max_count: Option<usize>,
+) -> Result<CheckSummary> {
+    let path = path.as_ref();
+    let summary = check_records(open_log(path)?, path, matcher, boundary, show, max_count)?;
 
-    Ok(())
+    Ok(CheckSummary {
+        bytes: fs::metadata(path)?.len(),
+        ..summary
+    })
 }
 
-fn remove_log_dir_cpu_mem_infos<P: AsRef<Path>>(dir: P, filters: &[String], keep: bool) {
-    let entries = get_entries(dir);
+/// 检查 `reader` 中的行 (记录)，`bytes` 为读到的字节数，文件的检查结果改为文件大小
+fn check_records<R: BufRead>(
+    reader: R,
+    path: &Path,
+    matcher: &Matcher,
+    boundary: &Boundary,
+    show: Option<ShowOptions>,
+    max_count: Option<usize>,
+) -> Result<CheckSummary> {
+    let mut matches = 0;
+    let mut bytes = 0;
+    let mut filter_matches = vec![0; matcher.filters().len()];
+    let mut errors = 0;
+    let mut error_codes = BTreeSet::new();
+    let mut cpu_peak: Option<f64> = None;
+    let mut collector = show.map(MatchCollector::new);
+    let mut line_no = 1;
+    let mut stopped_early = false;
+    for record in read_records(reader, boundary) {
+        if max_count.is_some_and(|max| matches >= max) {
+            stopped_early = true;
+            break;
+        }
+        let record = record?;
+        bytes += record.len() as u64 + 1;
+        let is_match = matcher.is_match(&record);
+        if is_match {
+            matches += 1;
+            for i in matcher.matched_filters(&record) {
+                filter_matches[i] += 1;
+            }
+        }
+        if let Some(collector) = &mut collector {
+            collector.push(line_no, &record, is_match);
+        }
+        line_no += record.matches('\n').count() + 1;
+
+        for line in record.lines().filter_map(parse_line) {
+            if line.level.eq_ignore_ascii_case("error") {
+                errors += 1;
+                error_codes.extend(parse_error_codes(line.message).map(str::to_string));
+            }
+            if let Some(cpu) = parse_percent(line.message, "cpu usage") {
+                cpu_peak = Some(cpu_peak.map_or(cpu, |peak| peak.max(cpu)));
+            }
+        }
+    }
 
-    entries.par_iter().for_each(|e| {
+    Ok(CheckSummary {
+        path: path.to_path_buf(),
+        matches,
+        errors,
+        cpu_peak,
+        lines: line_no - 1,
+        bytes,
+        stopped_early,
+        error_codes,
+        filter_matches,
+        matched_lines: collector.map_or_else(Vec::new, |c| c.lines),
+    })
+}
+
+/// `--porcelain` 的一行：路径中的 `\`、制表符与换行转义，保证每个文件恰好一行四列
+fn porcelain_line(summary: &CheckSummary) -> String {
+    let path = summary
+        .path
+        .display()
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n");
+    format!(
+        "{path}\t{}\t{}\t{}",
+        summary.matches, summary.lines, summary.bytes
+    )
+}
+
+pub fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// JUnit XML 报告：每个文件一个 testsuite，每个关键字一个 testcase
+fn junit_report(report: &CheckReport) -> String {
+    let failures =
+        |summary: &CheckSummary| summary.filter_matches.iter().filter(|&&n| n > 0).count();
+    let total_failures = report.files.iter().map(failures).sum::<usize>();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"lp cl\" tests=\"{}\" failures=\"{}\">\n",
+        report.files.len() * report.filters.len(),
+        total_failures
+    ));
+    for summary in &report.files {
+        let file = escape_xml(&summary.path.display().to_string());
+        xml.push_str(&format!(
+            "  <testsuite name=\"{file}\" tests=\"{}\" failures=\"{}\">\n",
+            report.filters.len(),
+            failures(summary)
+        ));
+        for (i, filter) in report.filters.iter().enumerate() {
+            let filter = escape_xml(filter);
+            let matched = summary.filter_matches.get(i).copied().unwrap_or_default();
+            if matched == 0 {
+                xml.push_str(&format!(
+                    "    <testcase classname=\"{file}\" name=\"{filter}\"/>\n"
+                ));
+            } else {
+                xml.push_str(&format!(
+                    "    <testcase classname=\"{file}\" name=\"{filter}\">\n      <failure type=\"forbidden-pattern\" message=\"{matched} matching lines\"/>\n    </testcase>\n"
+                ));
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+
+    xml
+}
+
+/// 单文件流式过滤时每块的行 (记录) 数
+const CHUNK_RECORDS: usize = 16 * 1024;
+
+/// rl 的输出选项
+struct RemoveOptions {
+    keep: bool,
+    /// 范围外的记录在匹配前直接去掉
+    time_range: TimeRange,
+    stats: bool,
+    boundary: Boundary,
+    in_place: bool,
+    backup: Option<String>,
+    dry_run: bool,
+    timeout: Option<Duration>,
+    schedule: Schedule,
+    compress: OutputCompression,
+    out_name: Option<OutName>,
+    provenance: bool,
+    /// 删除比例的上限，`--ignore-ratio` 时为 `None`
+    max_remove_ratio: Option<f64>,
+    /// 输出文件已存在时的处理方式
+    overwrite: OverwriteArgs,
+    /// 配置的输出目录，结果按相对 `root` 的路径放到该目录下
+    output_dir: Option<PathBuf>,
+    root: PathBuf,
+    /// 文件夹中要处理的文件
+    entries: EntryFilter,
+    /// `--in-place` 时记录被改写的原文件，供 `lp undo` 恢复
+    undo: Option<UndoLog>,
+}
+
+fn remove_with_timeout(
+    ctx: &AppContext,
+    path: &Path,
+    matcher: &Arc<Matcher>,
+    options: &Arc<RemoveOptions>,
+) -> Result<RemoveCounts> {
+    let ctx = ctx.clone();
+    let path = path.to_path_buf();
+    let matcher = Arc::clone(matcher);
+    let timeout = options.timeout;
+    let options = Arc::clone(options);
+    with_timeout(timeout, move || {
+        remove_log_file_cpu_mem_info(&ctx, &path, &matcher, &options)
+    })
+}
+
+fn remove_log_dir_cpu_mem_infos<P: AsRef<Path>>(
+    ctx: &AppContext,
+    dir: P,
+    matcher: &Arc<Matcher>,
+    options: &Arc<RemoveOptions>,
+) -> Result<()> {
+    let entries = filtered_entries(dir, &options.entries);
+    let inputs = entries.iter().map(|e| e.path()).collect::<HashSet<_>>();
+    let failures = Failures::new(ctx);
+    let progress = ctx.progress();
+    progress.started(entries.iter().map(|e| e.path()));
+
+    let counts = par_map_scheduled("rl", &entries, options.schedule, |e| {
+        if failures.should_stop() {
+            return None;
+        }
         let file_path = e.path();
-        if let Err(e) = remove_log_file_cpu_mem_info(file_path, filters, keep) {
-            println!("❌ remove line failed, path {:?}, reason: {}", file_path, e);
+        // 备份也是本次的输入时，两个文件的改写会互相覆盖
+        if let Some(suffix) = &options.backup
+            && inputs.contains(suffixed_path(file_path, "", suffix).as_path())
+        {
+            let e = anyhow::anyhow!("backup {suffix} of this file is also an input of this run");
+            eprintln!("❌ remove line failed, path {:?}, reason: {}", file_path, e);
+            failures.record(file_path, &e);
+            return None;
         }
-    });
+        progress
+            .file(file_path, || {
+                remove_with_timeout(ctx, file_path, matcher, options)
+            })
+            .inspect_err(|e| {
+                eprintln!("❌ remove line failed, path {:?}, reason: {}", file_path, e);
+                failures.record(file_path, e);
+            })
+            .ok()
+    })
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    progress.finished();
+
+    let files = counts.len();
+    let total = counts
+        .into_iter()
+        .fold(RemoveCounts::default(), RemoveCounts::merge);
+    println!("total: files: {files}, {}", total.summary());
+    failures.finish()
 }
 
-fn get_entries<P: AsRef<Path>>(dir: P) -> Vec<DirEntry> {
-    WalkDir::new(dir)
+/// rl 的输出路径：`.gz` 输入按解压后的文件名命名，需要压缩时再追加 `.gz`
+fn remove_output_path(path: &Path, options: &RemoveOptions) -> Result<PathBuf> {
+    let mut new_path = output_path(&plain_path(path), options.out_name.as_ref(), "_filtered")?;
+    if let Some(dir) = &options.output_dir {
+        new_path = mirror_path(&new_path, &options.root, dir);
+    }
+    if options.compress.gzip_for(path) {
+        Ok(suffixed_path(&new_path, "", ".gz"))
+    } else {
+        Ok(new_path)
+    }
+}
+
+/// 文件夹 (或 glob 匹配) 下要处理的文件，跳过处理结果与锁文件
+pub fn get_entries<P: AsRef<Path>>(dir: P) -> Vec<DirEntry> {
+    filtered_entries(dir, &EntryFilter::default())
+}
+
+/// 文件夹 (或 glob 匹配) 下通过 `filter` 的文件
+pub fn filtered_entries<P: AsRef<Path>>(dir: P, filter: &EntryFilter) -> Vec<DirEntry> {
+    let dir = dir.as_ref();
+    let (root, entries) = if is_glob(dir) {
+        (
+            glob_root(dir),
+            glob_files(dir, filter.walk).unwrap_or_default(),
+        )
+    } else {
+        let entries = filter
+            .walk
+            .walk(dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .collect::<Vec<_>>();
+        (dir.to_path_buf(), entries)
+    };
+    entries
         .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
         .filter(|e| {
-            e.file_name()
-                .to_str()
-                .is_some_and(|s| !s.contains("_filtered"))
+            filter.is_match(e.path().strip_prefix(&root).unwrap_or(e.path()))
+                && !filter.is_output(e.path())
         })
         .collect::<Vec<_>>()
 }
 
-fn remove_log_file_cpu_mem_info<P: AsRef<Path>>(path: P, filters: &[String], keep: bool) -> Result<()> {
-    let content = fs::read_to_string(&path)?;
-    let lines = content
-        .lines()
-        .filter(|&s|{
-            if keep {
-                contains_keyword(s, filters)
-            } else {
-                filter_keyword(s, filters)
+/// rl 过滤过程中的计数，用于输出每个文件与文件夹合计的删除比例及 `--stats`
+#[derive(Default)]
+struct RemoveCounts {
+    lines_before: usize,
+    lines_after: usize,
+    /// 解压后的字节数，按每行 (记录) 以 `\n` 结尾计算
+    bytes_before: u64,
+    bytes_after: u64,
+    matched: Vec<usize>,
+}
+
+impl RemoveCounts {
+    fn merge(mut self, other: Self) -> Self {
+        self.lines_before += other.lines_before;
+        self.lines_after += other.lines_after;
+        self.bytes_before += other.bytes_before;
+        self.bytes_after += other.bytes_after;
+        if self.matched.len() < other.matched.len() {
+            self.matched.resize(other.matched.len(), 0);
+        }
+        for (total, n) in self.matched.iter_mut().zip(other.matched) {
+            *total += n;
+        }
+        self
+    }
+
+    /// 删除的行 (记录) 占比，空文件为 0
+    fn removed_ratio(&self) -> f64 {
+        if self.lines_before == 0 {
+            return 0.0;
+        }
+
+        (self.lines_before - self.lines_after) as f64 / self.lines_before as f64
+    }
+
+    /// 如 `lines: 7 -> 3, removed 4 (57.14%), size: 405 B -> 180 B`
+    fn summary(&self) -> String {
+        format!(
+            "lines: {} -> {}, removed {} ({:.2}%), size: {} -> {}",
+            self.lines_before,
+            self.lines_after,
+            self.lines_before - self.lines_after,
+            self.removed_ratio() * 100.0,
+            format_size(self.bytes_before),
+            format_size(self.bytes_after)
+        )
+    }
+
+    /// 删除比例超过 `--max-remove-ratio` 时返回错误，该文件不写入
+    fn check_ratio(&self, max_remove_ratio: Option<f64>) -> Result<()> {
+        if let Some(max) = max_remove_ratio
+            && self.removed_ratio() > max
+        {
+            bail!(
+                "removing {:.2}% of lines exceeds --max-remove-ratio {max}, file skipped, use --ignore-ratio to write anyway (--force only overwrites existing outputs)",
+                self.removed_ratio() * 100.0
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// `-p -` 表示标准输入
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// 在文件名后追加后缀，如 `a.log` -> `a.log.bak`
+fn suffixed_path(path: &Path, prefix: &str, suffix: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default();
+    path.with_file_name(format!("{prefix}{}{suffix}", name.display()))
+}
+
+/// 以默认选项对单个文件执行 rl，结果写到 `_filtered` 文件，供 `lp watch` 使用
+pub fn remove_file_lines(
+    ctx: &AppContext,
+    path: &Path,
+    matcher: &Matcher,
+    keep: bool,
+) -> Result<()> {
+    // 与 rl 一致，结果写到配置的输出目录
+    let output_dir = ctx.output_dir()?;
+    let options = RemoveOptions {
+        keep,
+        time_range: TimeRange::default(),
+        stats: false,
+        boundary: Boundary::Line,
+        in_place: false,
+        backup: None,
+        dry_run: false,
+        timeout: None,
+        schedule: Schedule::Size,
+        compress: OutputCompression::Auto,
+        out_name: None,
+        provenance: false,
+        max_remove_ratio: None,
+        // 文件每次变化都重新生成结果
+        overwrite: OverwriteArgs {
+            force: true,
+            no_clobber: false,
+        },
+        entries: EntryFilter::default().skip_dir(output_dir.as_deref()),
+        output_dir,
+        root: PathBuf::new(),
+        undo: None,
+    };
+    remove_log_file_cpu_mem_info(ctx, path, matcher, &options)?;
+
+    Ok(())
+}
+
+fn remove_log_file_cpu_mem_info<P: AsRef<Path>>(
+    ctx: &AppContext,
+    path: P,
+    matcher: &Matcher,
+    options: &RemoveOptions,
+) -> Result<RemoveCounts> {
+    let start = Instant::now();
+    let path = path.as_ref();
+
+    if options.dry_run {
+        let counts = filter_records(path, io::sink(), None, matcher, options)?;
+        let (target, exists) = if options.in_place {
+            (path.to_path_buf(), false)
+        } else {
+            let target = remove_output_path(path, options)?;
+            match options.overwrite.claim(&target).ok() {
+                Some(new_path) => (new_path, false),
+                None => (target, true),
             }
+        };
+        println!("would write {:?}, {}", target.display(), counts.summary());
+        if let Err(e) = counts.check_ratio(options.max_remove_ratio) {
+            println!("⚠️ {e}");
+        }
+        if exists {
+            println!(
+                "⚠️ output already exists, use --force to overwrite it or --no-clobber to write a numbered copy"
+            );
+        }
+        return Ok(counts);
+    }
+
+    if !options.in_place {
+        let gzip = options.compress.gzip_for(path);
+        let target = remove_output_path(path, options)?;
+        let new_path = options.overwrite.claim(&target)?;
+        if let Some(parent) = new_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = InFlight::register(&new_path);
+        let provenance = options
+            .provenance
+            .then(|| InFlight::register(provenance_path(&new_path)));
+        let mut output = LogWriter::create(&new_path, gzip)?;
+        let provenance_path = provenance.as_ref().map(|p| p.path());
+        let counts = filter_records(path, &mut output, provenance_path, matcher, options)?;
+        output.finish()?;
+        // 未提交的输出与 provenance 在返回时删除
+        counts.check_ratio(options.max_remove_ratio)?;
+        // 已超时的任务已被报告为失败，不再留下输出
+        if options
+            .timeout
+            .is_some_and(|timeout| start.elapsed() > timeout)
+        {
+            bail!("timed out before writing the output");
+        }
+        partial.commit();
+        println!(
+            "write file after remove lines, path: {:?}, {}",
+            path.display(),
+            counts.summary()
+        );
+        if new_path != target {
+            println!(
+                "{:?} already exists, written to {:?}",
+                target.display(),
+                new_path.display()
+            );
+        }
+        if let Some(provenance) = provenance {
+            println!("write provenance, path: {:?}", provenance.path().display());
+            provenance.commit();
+        }
+
+        if options.stats {
+            let elapsed = start.elapsed();
+            write_remove_stats(path, &new_path, &counts, matcher, options.keep, elapsed)?;
+        }
+        return Ok(counts);
+    }
+
+    // 先写到同目录的临时文件，保证 rename 是原子操作
+    let tmp = InFlight::register(suffixed_path(path, ".", ".lp-tmp"));
+    let provenance = options
+        .provenance
+        .then(|| InFlight::register(provenance_path(path)));
+    let mut output = LogWriter::create(tmp.path(), is_gzip(path))?;
+    let provenance_path = provenance.as_ref().map(|p| p.path());
+    let counts = filter_records(path, &mut output, provenance_path, matcher, options)?;
+    output.finish()?;
+    counts.check_ratio(options.max_remove_ratio)?;
+
+    // 已超时的任务不再替换原文件，避免在调用方放弃等待之后才改写
+    if options
+        .timeout
+        .is_some_and(|timeout| start.elapsed() > timeout)
+    {
+        bail!("timed out before replacing the original file");
+    }
+
+    let backup = options
+        .backup
+        .as_deref()
+        .map(|suffix| suffixed_path(path, "", suffix));
+    // 没有 `--backup` 时原文件暂存到 `.lp_undo`，可用 `lp undo` 恢复
+    let saved = match (&backup, &options.undo) {
+        (Some(backup), _) => Some(backup.clone()),
+        (None, Some(undo)) => Some(undo.stash_path(path)?),
+        (None, None) => None,
+    };
+    replace_audited(ctx, "rl", path, tmp.path(), saved.as_deref())?;
+    tmp.commit();
+    if let (Some(undo), Some(saved)) = (&options.undo, &saved) {
+        undo.saved(path, saved);
+    }
+    match &backup {
+        Some(backup) => println!(
+            "rewrite file in place, path: {:?}, backup: {:?}, {}",
+            path.display(),
+            backup.display(),
+            counts.summary()
+        ),
+        None => println!(
+            "rewrite file in place, path: {:?}, {}",
+            path.display(),
+            counts.summary()
+        ),
+    }
+    if let Some(provenance) = provenance {
+        println!("write provenance, path: {:?}", provenance.path().display());
+        provenance.commit();
+    }
+
+    if options.stats {
+        let elapsed = start.elapsed();
+        write_remove_stats(path, path, &counts, matcher, options.keep, elapsed)?;
+    }
+
+    Ok(counts)
+}
+
+/// `--provenance` 的输出路径，`xxx_filtered.log` 旁的 `xxx_filtered.provenance.csv`
+fn provenance_path(path: &Path) -> PathBuf {
+    let stem = plain_path(path);
+    let stem = stem.file_stem().unwrap_or_default();
+    path.with_file_name(format!("{}.provenance.csv", stem.display()))
+}
+
+/// rl 的匹配器：`--since`/`--until` 范围外的记录在匹配前直接去掉，时间范围不再参与关键字匹配
+fn remove_matcher(ctx: &AppContext, matching: &MatchArgs) -> Result<Matcher> {
+    let filters = matching.keywords(ctx, "rl")?;
+
+    Ok(matching
+        .matcher(&filters)?
+        .with_time_range(TimeRange::default()))
+}
+
+/// 只按时间范围筛选 (没有关键字与级别等条件) 时保留范围内的所有记录
+fn keeps_window(matcher: &Matcher, time_range: &TimeRange) -> bool {
+    matcher.is_empty() && !time_range.is_unbounded()
+}
+
+/// 逐条过滤并立即写出，不按块并行，`tail -f` 等持续写入的输入不会积压；下游关闭 (如 `| head`) 时正常结束
+fn filter_stream<R: BufRead, W: Write>(
+    reader: R,
+    output: W,
+    matcher: &Matcher,
+    keep: bool,
+    boundary: &Boundary,
+    time_range: TimeRange,
+) -> Result<RemoveCounts> {
+    let mut window = TimeWindow::new(time_range);
+    let pipeline = Pipeline::new()
+        .boundary(boundary.clone())
+        .filter_with(move |record| window.contains(record))
+        .sink(output);
+    let pipeline = if keeps_window(matcher, &time_range) {
+        pipeline
+    } else {
+        pipeline.filter(matcher, keep)
+    };
+    let stats = pipeline.run(reader)?;
+
+    Ok(RemoveCounts {
+        lines_before: stats.records_in as usize,
+        lines_after: stats.records_out as usize,
+        bytes_before: stats.bytes_in,
+        bytes_after: stats.bytes_out,
+        ..Default::default()
+    })
+}
+
+/// 将 `path` 中保留的行 (记录) 写入 `output`，指定了 `provenance` 时同时写入每条的来源
+fn filter_records<W: Write + Send>(
+    path: &Path,
+    output: W,
+    provenance: Option<&Path>,
+    matcher: &Matcher,
+    options: &RemoveOptions,
+) -> Result<RemoveCounts> {
+    let provenance = provenance
+        .map(|provenance| -> Result<_> {
+            let mut writer = BufWriter::new(File::create(provenance)?);
+            let header = csv_line(["source", "line", "offset", "lines"].into_iter());
+            writer.write_all(header.as_bytes())?;
+            Ok(OrderedWriter::new(writer))
         })
-        .map(|s| format!("{s}\n"))
-        .collect::<String>();
+        .transpose()?;
+    let source = path.display().to_string();
 
-    let path = path.as_ref();
-    let stem = path.file_stem().unwrap_or_default();
-    let ext = path.extension().unwrap_or_default();
-    let new_path = path.with_file_name(format!(
-        "{}_filtered{}{}",
-        stem.display(),
-        if ext.is_empty() { "" } else { "." },
-        ext.display(),
-    ));
+    // 时间范围须按文件顺序判断 (续行跟随上一条)，在分块前标记
+    let mut window = TimeWindow::new(options.time_range);
+    let mut records = read_records(open_log(path)?, &options.boundary)
+        .positioned()
+        .map(|record| {
+            record.map(|(position, record)| (position, window.contains(&record), record))
+        });
+    let keep_all = keeps_window(matcher, &options.time_range);
+    let chunks = iter::from_fn(|| {
+        let chunk = records
+            .by_ref()
+            .take(CHUNK_RECORDS)
+            .collect::<io::Result<Vec<_>>>();
+        if chunk.as_ref().is_ok_and(|chunk| chunk.is_empty()) {
+            None
+        } else {
+            Some(chunk)
+        }
+    });
 
-    fs::write(new_path, lines)?;
-    println!("write file after remove lines, path: {:?}", path.display());
+    // 按块流式读取并行过滤，由 OrderedWriter 保证输出顺序与原文件一致，内存占用与文件大小无关
+    let writer = OrderedWriter::new(BufWriter::new(output));
+    let counts = chunks
+        .enumerate()
+        .par_bridge()
+        .map(|(seq, chunk)| {
+            let chunk = chunk?;
+            let mut counts = RemoveCounts {
+                lines_before: chunk.len(),
+                bytes_before: chunk
+                    .iter()
+                    .map(|(_, _, record)| record.len() as u64 + 1)
+                    .sum(),
+                ..Default::default()
+            };
+            if options.stats {
+                counts.matched = vec![0; matcher.filters().len()];
+            }
 
-    Ok(())
+            let mut lines = String::new();
+            let mut rows = String::new();
+            for (position, in_window, record) in &chunk {
+                if !in_window {
+                    continue;
+                }
+                if options.stats {
+                    for i in matcher.matched_filters(record) {
+                        counts.matched[i] += 1;
+                    }
+                }
+                if keep_all || matcher.keep_line(record, options.keep) {
+                    counts.lines_after += 1;
+                    counts.bytes_after += record.len() as u64 + 1;
+                    lines.push_str(record);
+                    lines.push('\n');
+                    if provenance.is_some() {
+                        let cells = [
+                            position.line.to_string(),
+                            position.offset.to_string(),
+                            (record.matches('\n').count() + 1).to_string(),
+                        ];
+                        let cells =
+                            iter::once(source.as_str()).chain(cells.iter().map(String::as_str));
+                        rows.push_str(&csv_line(cells));
+                    }
+                }
+            }
+            writer.write_chunk(seq, lines.into_bytes())?;
+            if let Some(provenance) = &provenance {
+                provenance.write_chunk(seq, rows.into_bytes())?;
+            }
+
+            Ok(counts)
+        })
+        .try_reduce(RemoveCounts::default, |a, b| Ok(a.merge(b)))?;
+    writer.finish()?;
+    if let Some(provenance) = provenance {
+        provenance.finish()?;
+    }
+
+    Ok(counts)
 }
 
-fn contains_keyword(line: &str, filters: &[String]) -> bool {
-    filters.iter().any(|s| line.contains(s))
+#[derive(Serialize)]
+struct FilterStats<'a> {
+    filter: &'a str,
+    /// 命中该关键字的行 (或记录) 数，`keep` 时为保留数，否则为删除数
+    matched: usize,
 }
 
-fn filter_keyword(line: &str, filters: &[String]) -> bool {
-    filters.iter().all(|s| !line.contains(s))
+#[derive(Serialize)]
+struct RemoveStats<'a> {
+    source: &'a Path,
+    output: &'a Path,
+    keep: bool,
+    lines_before: usize,
+    lines_after: usize,
+    elapsed_ms: u128,
+    filters: Vec<FilterStats<'a>>,
+}
+
+fn write_remove_stats(
+    path: &Path,
+    new_path: &Path,
+    counts: &RemoveCounts,
+    matcher: &Matcher,
+    keep: bool,
+    elapsed: Duration,
+) -> Result<()> {
+    let mut matched = counts.matched.clone();
+    matched.resize(matcher.filters().len(), 0);
+
+    let stats = RemoveStats {
+        source: path,
+        output: new_path,
+        keep,
+        lines_before: counts.lines_before,
+        lines_after: counts.lines_after,
+        elapsed_ms: elapsed.as_millis(),
+        filters: matcher
+            .filters()
+            .iter()
+            .zip(matched)
+            .map(|(filter, matched)| FilterStats { filter, matched })
+            .collect(),
+    };
+
+    let stem = new_path.file_stem().unwrap_or_default();
+    let stats_path = new_path.with_file_name(format!("{}.stats.json", stem.display()));
+    fs::write(&stats_path, serde_json::to_string_pretty(&stats)?)?;
+    println!("write remove stats, path: {:?}", stats_path.display());
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        matcher::{contains_keyword, filter_keyword},
+        time::parse_timestamp,
+    };
+
+    #[test]
+    fn test_path_args() {
+        let check = CheckLineArgs::try_parse_from(["cl", "-"]).unwrap();
+        assert_eq!(check.input.path(), Path::new("-"));
+        let check = CheckLineArgs::try_parse_from(["cl", "-p", "app.log"]).unwrap();
+        assert_eq!(check.input.path(), Path::new("app.log"));
+
+        let remove = RemoveLineArgs::try_parse_from(["rl", "-", "--filters", "pid:"]).unwrap();
+        assert_eq!(remove.input.into_path(), PathBuf::from("-"));
+        let remove = RemoveLineArgs::try_parse_from(["rl", "-f", "pid:", "logs"]).unwrap();
+        assert_eq!(remove.input.into_path(), PathBuf::from("logs"));
+
+        // 缺少路径或两种写法同时出现都报错
+        assert!(RemoveLineArgs::try_parse_from(["rl", "-f", "pid:"]).is_err());
+        assert!(RemoveLineArgs::try_parse_from(["rl", "a.log", "-p", "b.log"]).is_err());
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let mut report = CheckReport {
+            root: PathBuf::from("logs"),
+            filters: vec!["ERRCODE_".to_string(), "<panic>".to_string()],
+            files: vec![CheckSummary {
+                path: PathBuf::from("logs/a.log"),
+                matches: 2,
+                errors: 2,
+                cpu_peak: None,
+                lines: 10,
+                bytes: 512,
+                stopped_early: false,
+                error_codes: BTreeSet::new(),
+                filter_matches: vec![2, 0],
+                matched_lines: Vec::new(),
+            }],
+        };
+
+        let xml = junit_report(&report);
+        assert!(xml.contains("<testsuites name=\"lp cl\" tests=\"2\" failures=\"1\">"));
+        assert!(xml.contains(
+            "<testcase classname=\"logs/a.log\" name=\"ERRCODE_\">\n      <failure type=\"forbidden-pattern\" message=\"2 matching lines\"/>"
+        ));
+        assert!(xml.contains("<testcase classname=\"logs/a.log\" name=\"&lt;panic&gt;\"/>"));
+        let mut summary = report.files.remove(0);
+        assert_eq!(porcelain_line(&summary), "logs/a.log\t2\t10\t512");
+        summary.path = PathBuf::from("logs/a\tb.log");
+        assert_eq!(porcelain_line(&summary), "logs/a\\tb.log\t2\t10\t512");
+
+        summary.matched_lines = vec![
+            MatchedLine {
+                line: 3,
+                text: "before".to_string(),
+                context: true,
+                annotations: Vec::new(),
+            },
+            MatchedLine {
+                line: 4,
+                text: "ERRCODE_1".to_string(),
+                context: false,
+                annotations: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            serde_json::to_string(&CheckRecord::new(&summary)).unwrap(),
+            r#"{"file":"logs/a\tb.log","match_count":2,"matches":[{"line_no":4,"text":"ERRCODE_1"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_match_collector() {
+        let collect = |matches: &[usize], max_matches, before, after| {
+            let mut collector = MatchCollector::new(ShowOptions {
+                max_matches,
+                before,
+                after,
+            });
+            for line in 1..=10 {
+                collector.push(line, &line.to_string(), matches.contains(&line));
+            }
+            collector
+                .lines
+                .iter()
+                .map(|m| (m.line, m.context))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            collect(&[3, 8], usize::MAX, 0, 0),
+            vec![(3, false), (8, false)]
+        );
+        assert_eq!(
+            collect(&[3, 5], usize::MAX, 1, 1),
+            vec![(2, true), (3, false), (4, true), (5, false), (6, true)]
+        );
+        assert_eq!(
+            collect(&[3, 4, 9], 1, 2, 2),
+            vec![(1, true), (2, true), (3, false), (4, true), (5, true)]
+        );
+    }
+
+    #[test]
+    fn test_check_max_count() {
+        let path = std::env::temp_dir().join(format!("lp_max_count_{}.log", std::process::id()));
+        fs::write(&path, "tid: 1\nok\ntid: 2\ntid: 3\nok\n").unwrap();
+        let matcher = Matcher::new(&["tid:".to_string()], false).unwrap();
+        let check = |max_count| {
+            check_log_file_cpu_mem_info(&path, &matcher, &Boundary::Line, None, max_count).unwrap()
+        };
+
+        let full = check(None);
+        assert_eq!(
+            (full.matches, full.lines, full.stopped_early),
+            (3, 5, false)
+        );
+        let first = check(Some(1));
+        assert_eq!(
+            (first.matches, first.lines, first.stopped_early),
+            (1, 1, true)
+        );
+        let two = check(Some(2));
+        assert_eq!((two.matches, two.lines, two.stopped_early), (2, 3, true));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_remove_dir_skips_outputs() {
+        let dir = std::env::temp_dir().join(format!("lp_rl_outputs_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("app.log"), "a pid: 1\nb\n").unwrap();
+        fs::write(dir.join("app_transformed.log"), "a pid: 1\nb\n").unwrap();
+        let ctx = AppContext::new("/nonexistent/config.json");
+
+        let args =
+            RemoveLineArgs::try_parse_from(["rl", "-p", dir.to_str().unwrap(), "-f", "pid:"])
+                .unwrap();
+        process_remove_line(&ctx, args).unwrap();
+        let mut names = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            ["app.log", "app_filtered.log", "app_transformed.log"]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_dir_skips_out_name_outputs() {
+        let dir = std::env::temp_dir().join(format!("lp_rl_out_name_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("app.log"), "a pid: 1\nb\n").unwrap();
+        let ctx = AppContext::new("/nonexistent/config.json");
+
+        // 第二次运行时 `app.clean.log` 不再作为输入，只覆盖 `app.log` 的结果
+        for force in [None, Some("--force")] {
+            let argv = ["rl", "-p", dir.to_str().unwrap(), "-f", "pid:"]
+                .into_iter()
+                .chain(["--out-name", "{stem}.clean.{ext}"])
+                .chain(force);
+            let args = RemoveLineArgs::try_parse_from(argv).unwrap();
+            process_remove_line(&ctx, args).unwrap();
+        }
+        let mut names = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["app.clean.log", "app.log"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_dir_skips_out_dir() {
+        let dir = std::env::temp_dir().join(format!("lp_rl_out_dir_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("app.log"), "a pid: 1\nb\n").unwrap();
+        let ctx = AppContext::new("/nonexistent/config.json");
+
+        // 输出目录在要处理的文件夹内，第二次运行时其中的结果不再作为输入，即使 `--exclude` 没有跳过它们
+        let out_dir = dir.join("out");
+        for force in [None, Some("--force")] {
+            let argv = ["rl", "-p", dir.to_str().unwrap(), "-f", "pid:"]
+                .into_iter()
+                .chain(["--out-dir", out_dir.to_str().unwrap()])
+                .chain(["--exclude", "*.tmp"])
+                .chain(force);
+            let args = RemoveLineArgs::try_parse_from(argv).unwrap();
+            process_remove_line(&ctx, args).unwrap();
+        }
+        assert!(out_dir.join("app_filtered.log").is_file());
+        assert_eq!(fs::read_dir(&out_dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_dir_skips_backups() {
+        let dir = std::env::temp_dir().join(format!("lp_rl_backup_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.log"), "a pid: 1\nb\nc pid: 2\n").unwrap();
+        let ctx = AppContext::new(dir.join("config.json"));
+
+        // 第二次运行时上次的备份不再作为输入，备份为第一次改写后的内容
+        for _ in 0..2 {
+            let argv = ["rl", "-p", dir.to_str().unwrap(), "-f", "pid:"]
+                .into_iter()
+                .chain(["--in-place", "--backup"]);
+            let args = RemoveLineArgs::try_parse_from(argv).unwrap();
+            process_remove_line(&ctx, args).unwrap();
+        }
+        assert_eq!(fs::read_to_string(dir.join("a.log")).unwrap(), "b\n");
+        assert_eq!(fs::read_to_string(dir.join("a.log.bak")).unwrap(), "b\n");
+        assert!(!dir.join("a.log.bak.bak").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_remove_ratio() {
+        let counts = RemoveCounts {
+            lines_before: 10,
+            lines_after: 1,
+            bytes_before: 400,
+            bytes_after: 40,
+            ..Default::default()
+        };
+        assert_eq!(
+            counts.summary(),
+            "lines: 10 -> 1, removed 9 (90.00%), size: 400 B -> 40 B"
+        );
+        assert!(counts.check_ratio(None).is_ok());
+        assert!(counts.check_ratio(Some(0.9)).is_ok());
+        assert!(counts.check_ratio(Some(0.5)).is_err());
+        assert!(RemoveCounts::default().check_ratio(Some(0.1)).is_ok());
+    }
+
+    #[test]
+    fn test_filter_stream() {
+        let matcher = Matcher::new(&["pid:".to_string()], false).unwrap();
+        let input = "a pid: 1\nb\nc pid: 2\n";
+        let mut output = Vec::new();
+        let counts = filter_stream(
+            input.as_bytes(),
+            &mut output,
+            &matcher,
+            false,
+            &Boundary::Line,
+            TimeRange::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output), "b\n");
+        assert_eq!((counts.lines_before, counts.lines_after), (3, 1));
+
+        // 范围外的记录直接去掉，续行跟随上一条；只有时间范围时保留范围内的所有记录
+        let timed = "\
+[2026-01-06 09:00:00.000] [info] [Global]  early
+[2026-01-06 10:30:00.000] [error] [Global]  failed
+    at db::query
+[2026-01-06 10:31:00.000] [info] [Global]  pid: 3
+[2026-01-06 12:00:00.000] [info] [Global]  late
+";
+        let time_range = TimeRange {
+            since: parse_timestamp("2026-01-06 10:00"),
+            until: parse_timestamp("2026-01-06 11:00"),
+        };
+        let filter = |matcher: &Matcher| {
+            let mut output = Vec::new();
+            filter_stream(
+                timed.as_bytes(),
+                &mut output,
+                matcher,
+                false,
+                &Boundary::Line,
+                time_range,
+            )
+            .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        assert_eq!(
+            filter(&matcher),
+            "[2026-01-06 10:30:00.000] [error] [Global]  failed\n    at db::query\n"
+        );
+        assert_eq!(
+            filter(&Matcher::new(&[], false).unwrap()),
+            "\
+[2026-01-06 10:30:00.000] [error] [Global]  failed
+    at db::query
+[2026-01-06 10:31:00.000] [info] [Global]  pid: 3
+"
+        );
+
+        let summary = check_records(
+            input.as_bytes(),
+            Path::new("-"),
+            &matcher,
+            &Boundary::Line,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!((summary.matches, summary.lines), (2, 3));
+        assert_eq!(summary.bytes, input.len() as u64);
+    }
+
+    #[test]
+    fn test_remove_candidates() {
+        let dir = std::env::temp_dir().join(format!("lp_rf_filter_{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let old = SystemTime::now() - Duration::from_secs(40 * 86_400);
+        for (name, size, modified) in [
+            ("old.log", 200, Some(old)),
+            ("sub/old_small.log", 10, Some(old)),
+            ("old.txt", 200, Some(old)),
+            ("new.log", 200, None),
+        ] {
+            let file = File::create(dir.join(name)).unwrap();
+            file.set_len(size).unwrap();
+            if let Some(modified) = modified {
+                file.set_modified(modified).unwrap();
+            }
+        }
+
+        let args = RemoveFileArgs::parse_from([
+            "rf",
+            "x",
+            "--older-than",
+            "30d",
+            "--pattern",
+            "*.log",
+            "--min-size",
+            "100",
+        ]);
+        let filter = RemoveFilter::new(&args).unwrap().unwrap();
+        let files = remove_candidates(&dir, false, Some(&filter)).unwrap();
+        assert_eq!(files, [(200, dir.join("old.log"))]);
+
+        let args = RemoveFileArgs::parse_from(["rf", "x", "--older-than", "30d"]);
+        let filter = RemoveFilter::new(&args).unwrap().unwrap();
+        assert_eq!(
+            remove_candidates(&dir, false, Some(&filter)).unwrap().len(),
+            3
+        );
+        assert!(
+            RemoveFilter::new(&RemoveFileArgs::parse_from(["rf", "x"]))
+                .unwrap()
+                .is_none()
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
 
     #[test]
     fn test_filter_keyword() {
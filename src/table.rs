@@ -0,0 +1,222 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+use rust_xlsxwriter::{Chart, ChartType, workbook::Workbook};
+
+use crate::temp::InFlight;
+
+/// 按列宽对齐打印表格
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths = headers
+        .iter()
+        .map(|h| h.chars().count())
+        .collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: &mut dyn Iterator<Item = &str>| {
+        cells
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(&mut headers.iter().copied()));
+    for row in rows {
+        println!("{}", format_row(&mut row.iter().map(String::as_str)));
+    }
+}
+
+/// xlsx 单个工作表的最大行数 (含表头)
+const XLSX_MAX_ROWS: u32 = 1_048_576;
+
+enum TableFile {
+    /// constant memory 模式的工作簿，已写完的行会落盘而不是常驻内存
+    Xlsx {
+        wb: Box<Workbook>,
+        sheet: usize,
+    },
+    Csv(BufWriter<File>),
+}
+
+/// 按扩展名逐行写出 xlsx 或 csv 表格，内存占用与总行数无关；
+/// xlsx 超出单表行数上限时续写到新的工作表
+pub struct TableWriter {
+    output: InFlight,
+    headers: Vec<String>,
+    file: TableFile,
+    row: u32,
+    chart: Option<LineChart>,
+}
+
+/// 写完后追加的折线图：以第一列为横轴，`columns` 中的每一列为一条折线
+struct LineChart {
+    title: String,
+    columns: Vec<u16>,
+}
+
+impl TableWriter {
+    pub fn create<P: AsRef<Path>>(path: P, headers: &[&str]) -> Result<Self> {
+        let path = path.as_ref();
+        let is_xlsx = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("xlsx"));
+
+        let file = if is_xlsx {
+            TableFile::Xlsx {
+                wb: Box::new(Workbook::new()),
+                sheet: 0,
+            }
+        } else {
+            TableFile::Csv(BufWriter::new(File::create(path)?))
+        };
+        let mut writer = TableWriter {
+            output: InFlight::register(path),
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            file,
+            row: 0,
+            chart: None,
+        };
+        writer.write_headers()?;
+
+        Ok(writer)
+    }
+
+    fn write_headers(&mut self) -> Result<()> {
+        match &mut self.file {
+            TableFile::Xlsx { wb, .. } => {
+                let ws = wb.add_worksheet_with_constant_memory();
+                for (col, header) in self.headers.iter().enumerate() {
+                    ws.write_string(0, col as u16, header)?;
+                }
+            }
+            TableFile::Csv(file) => {
+                file.write_all(csv_line(self.headers.iter().map(String::as_str)).as_bytes())?
+            }
+        }
+        self.row = 1;
+
+        Ok(())
+    }
+
+    /// 写入一行，xlsx 中能解析为数字的单元格按数字写入
+    pub fn write_row<S: AsRef<str>>(&mut self, cells: &[S]) -> Result<()> {
+        if let TableFile::Xlsx { sheet, .. } = &mut self.file
+            && self.row == XLSX_MAX_ROWS
+        {
+            *sheet += 1;
+            self.write_headers()?;
+        }
+
+        match &mut self.file {
+            TableFile::Xlsx { wb, sheet } => {
+                let ws = wb.worksheet_from_index(*sheet)?;
+                for (col, cell) in cells.iter().enumerate() {
+                    let (row, col, cell) = (self.row, col as u16, cell.as_ref());
+                    match cell.parse::<f64>() {
+                        Ok(n) => ws.write_number(row, col, n)?,
+                        Err(_) => ws.write_string(row, col, cell)?,
+                    };
+                }
+            }
+            TableFile::Csv(file) => {
+                file.write_all(csv_line(cells.iter().map(AsRef::as_ref)).as_bytes())?
+            }
+        }
+        self.row += 1;
+
+        Ok(())
+    }
+
+    /// 写完后在单独的工作表中追加折线图，只对 xlsx 生效；
+    /// 数据超出单表行数上限时只绘制第一个工作表中的数据
+    pub fn with_line_chart(mut self, title: &str, columns: &[u16]) -> Self {
+        self.chart = Some(LineChart {
+            title: title.to_string(),
+            columns: columns.to_vec(),
+        });
+        self
+    }
+
+    pub fn finish(self) -> Result<()> {
+        match self.file {
+            TableFile::Xlsx { mut wb, sheet } => {
+                let last_row = if sheet > 0 {
+                    XLSX_MAX_ROWS - 1
+                } else {
+                    self.row - 1
+                };
+                if let Some(line_chart) = &self.chart
+                    && last_row > 0
+                {
+                    let data = wb.worksheet_from_index(0)?.name();
+                    let mut chart = Chart::new(ChartType::Line);
+                    chart.title().set_name(&line_chart.title);
+                    chart.x_axis().set_name(&self.headers[0]);
+                    for &col in &line_chart.columns {
+                        chart
+                            .add_series()
+                            .set_name(&self.headers[col as usize])
+                            .set_categories((data.as_str(), 1, 0, last_row, 0))
+                            .set_values((data.as_str(), 1, col, last_row, col));
+                    }
+                    wb.add_chartsheet()
+                        .set_name("chart")?
+                        .insert_chart(0, 0, &chart)?;
+                }
+                wb.save(self.output.path())?
+            }
+            TableFile::Csv(mut file) => file.flush()?,
+        }
+        self.output.commit();
+
+        Ok(())
+    }
+}
+
+/// 按扩展名将表格写为 xlsx 或 csv
+pub fn write_table<P: AsRef<Path>>(path: P, headers: &[&str], rows: &[Vec<String>]) -> Result<()> {
+    let mut writer = TableWriter::create(path, headers)?;
+    for row in rows {
+        writer.write_row(row)?;
+    }
+
+    writer.finish()
+}
+
+/// 一行 csv，含逗号、引号或换行的单元格加引号
+pub fn csv_line<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    let mut line = cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push('\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_line() {
+        assert_eq!(csv_line(["a", "b c", "d,e"].into_iter()), "a,b c,\"d,e\"\n");
+        assert_eq!(csv_line(["say \"hi\""].into_iter()), "\"say \"\"hi\"\"\"\n");
+    }
+}
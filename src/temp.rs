@@ -0,0 +1,108 @@
+use std::{
+    fs, mem,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// 正在写入、尚未完成的输出文件
+static IN_FLIGHT: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// 正在写入的输出文件 (临时文件或部分写入的结果)，
+/// 调用 [`InFlight::commit`] 之前出错返回、panic 或被 Ctrl-C 中断时删除
+pub struct InFlight {
+    path: PathBuf,
+    committed: bool,
+}
+
+impl InFlight {
+    pub fn register<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        IN_FLIGHT.lock().unwrap().push(path.clone());
+
+        InFlight {
+            path,
+            committed: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 文件已经完整写入 (或已改名为正式文件)，不再需要清理
+    pub fn commit(mut self) {
+        self.committed = true;
+        unregister(&self.path);
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if !self.committed && unregister(&self.path) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// 从登记表中移除，已被中断处理清理过时返回 false
+fn unregister(path: &Path) -> bool {
+    let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+    match in_flight.iter().position(|p| p == path) {
+        Some(i) => {
+            in_flight.swap_remove(i);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 删除所有未完成的输出文件，返回实际删除的路径
+pub fn rollback() -> Vec<PathBuf> {
+    rollback_from(&IN_FLIGHT)
+}
+
+/// 清空登记表 `registry` 并删除其中的文件
+fn rollback_from(registry: &Mutex<Vec<PathBuf>>) -> Vec<PathBuf> {
+    let in_flight = mem::take(&mut *registry.lock().unwrap_or_else(|e| e.into_inner()));
+    in_flight
+        .into_iter()
+        .filter(|path| fs::remove_file(path).is_ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_flight() {
+        let dir = std::env::temp_dir().join(format!("lp_temp_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let dropped = dir.join("dropped.lp-tmp");
+        let committed = dir.join("committed.log");
+        {
+            let guard = InFlight::register(&dropped);
+            fs::write(guard.path(), "partial").unwrap();
+            let guard = InFlight::register(&committed);
+            fs::write(guard.path(), "done").unwrap();
+            guard.commit();
+        }
+        assert!(!dropped.exists());
+        assert!(committed.exists());
+
+        // 全局的 rollback 会删掉并行运行的其他测试的文件，这里只回滚本测试的登记表
+        let interrupted = InFlight::register(dir.join("interrupted.lp-tmp"));
+        fs::write(interrupted.path(), "partial").unwrap();
+        let registry = Mutex::new(vec![interrupted.path().to_path_buf()]);
+        assert_eq!(
+            rollback_from(&registry),
+            vec![interrupted.path().to_path_buf()]
+        );
+        assert!(registry.lock().unwrap().is_empty());
+        assert!(!interrupted.path().exists());
+        drop(interrupted);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use clap::Args;
+
+/// 按行首时间戳限定的时间范围，包含 `since`，不含 `until`
+#[derive(Args, Clone, Copy, Default)]
+pub struct TimeRange {
+    /// 只处理该时间及之后的行，如 "2026-01-06 10:00"
+    #[arg(long, value_name = "TIME", value_parser = parse_time_arg)]
+    pub since: Option<i64>,
+
+    /// 只处理该时间之前的行，如 "2026-01-06 11:00"
+    #[arg(long, value_name = "TIME", value_parser = parse_time_arg)]
+    pub until: Option<i64>,
+}
+
+impl TimeRange {
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn contains(&self, ms: i64) -> bool {
+        self.since.is_none_or(|since| ms >= since) && self.until.is_none_or(|until| ms < until)
+    }
+}
+
+/// 命令行中的时间参数，格式同 [`parse_timestamp`]
+pub fn parse_time_arg(s: &str) -> Result<i64, String> {
+    parse_timestamp(s).ok_or_else(|| format!("invalid time: {s}, expected YYYY-MM-DD HH:MM[:SS]"))
+}
+
+/// 解析 `YYYY-MM-DD[ HH:MM[:SS[.mmm]]]` 为毫秒时间戳，不区分时区
+pub fn parse_timestamp(s: &str) -> Option<i64> {
+    let s = s.trim();
+    let (date, time) = s.split_once([' ', 'T']).unwrap_or((s, ""));
+
+    let mut parts = date.splitn(3, '-');
+    let year = parts.next()?.parse::<i64>().ok()?;
+    let month = parts.next()?.parse::<i64>().ok()?;
+    let day = parts.next()?.parse::<i64>().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let (hms, frac) = time.split_once('.').unwrap_or((time, ""));
+    let (hour, minute, second) = if hms.is_empty() {
+        (0, 0, 0)
+    } else {
+        let mut parts = hms.splitn(3, ':');
+        let hour = parts.next()?.trim().parse::<i64>().ok()?;
+        let minute = parts.next().map_or(Some(0), |s| s.trim().parse().ok())?;
+        let second = parts.next().map_or(Some(0), |s| s.trim().parse().ok())?;
+        (hour, minute, second)
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let millis = match frac.len() {
+        0 => 0,
+        1..=3 => frac.parse::<i64>().ok()? * 10_i64.pow(3 - frac.len() as u32),
+        _ => frac[..3].parse::<i64>().ok()?,
+    };
+
+    let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second;
+    Some(secs * 1000 + millis)
+}
+
+/// 毫秒时间戳格式化为 `YYYY-MM-DD HH:MM:SS.mmm`
+pub fn format_timestamp(ms: i64) -> String {
+    let days = ms.div_euclid(86_400_000);
+    let rest = ms.rem_euclid(86_400_000);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{:03}",
+        rest / 3_600_000,
+        rest / 60_000 % 60,
+        rest / 1000 % 60,
+        rest % 1000
+    )
+}
+
+/// 解析 `500ms`、`5s`、`10m`、`2h`、`30d` 形式的时长，不带单位时按秒处理
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value = value
+        .parse::<f64>()
+        .map_err(|_| format!("invalid duration: {s}"))?;
+
+    let secs = match unit.trim() {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        "h" => value * 3600.0,
+        "d" => value * 86_400.0,
+        "w" => value * 7.0 * 86_400.0,
+        unit => {
+            return Err(format!(
+                "unknown duration unit `{unit}`, expected ms/s/m/h/d/w"
+            ));
+        }
+    };
+
+    Ok(Duration::from_secs_f64(secs))
+}
+
+/// 毫秒时长格式化为 `1h 5m`、`2m 30s`、`45s` 或 `500ms`，只保留最大的两个单位
+pub fn format_duration_ms(ms: i64) -> String {
+    if ms < 1000 {
+        return format!("{ms}ms");
+    }
+    let secs = ms / 1000;
+    let parts = [
+        (secs / 86_400, "d"),
+        (secs / 3600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ];
+    let first = parts.iter().position(|(n, _)| *n > 0).unwrap_or(3);
+
+    parts[first..]
+        .iter()
+        .take(2)
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, unit)| format!("{n}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 解析 `+08:00`、`-0530`、`Z` 形式的 UTC 偏移，返回毫秒
+pub fn parse_utc_offset(s: &str) -> Result<i64, String> {
+    let s = s.trim();
+    if s.eq_ignore_ascii_case("z") {
+        return Ok(0);
+    }
+
+    let (sign, rest) = match s.as_bytes().first() {
+        Some(b'+') => (1, &s[1..]),
+        Some(b'-') => (-1, &s[1..]),
+        _ => return Err(format!("invalid utc offset: {s}, expected e.g. +08:00")),
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((h, m)) => (h, m),
+        None if rest.len() == 4 => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours = hours
+        .parse::<i64>()
+        .map_err(|_| format!("invalid utc offset: {s}"))?;
+    let minutes = minutes
+        .parse::<i64>()
+        .map_err(|_| format!("invalid utc offset: {s}"))?;
+    if hours > 14 || minutes > 59 {
+        return Err(format!("invalid utc offset: {s}"));
+    }
+
+    Ok(sign * (hours * 3600 + minutes * 60) * 1000)
+}
+
+/// Howard Hinnant 的 days_from_civil 算法，返回距 1970-01-01 的天数
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(
+            parse_timestamp("2026-01-06 10:29:10.765").unwrap()
+                - parse_timestamp("2026-01-06 10:29:10").unwrap(),
+            765
+        );
+        assert_eq!(
+            parse_timestamp("2026-01-06 00:00:00"),
+            Some(1_767_657_600_000)
+        );
+        assert_eq!(parse_timestamp("1970-01-01 00:00:01"), Some(1000));
+        assert_eq!(
+            parse_timestamp("2026-01-06 10:29"),
+            parse_timestamp("2026-01-06 10:29:00.000")
+        );
+        assert_eq!(
+            parse_timestamp("2026-01-06"),
+            parse_timestamp("2026-01-06 00:00:00")
+        );
+        assert_eq!(
+            parse_timestamp("2024-03-01 00:00:00.000").unwrap()
+                - parse_timestamp("2024-02-29 23:59:59.9").unwrap(),
+            100
+        );
+        assert_eq!(
+            format_timestamp(parse_timestamp("2024-02-29 23:59:59.9").unwrap()),
+            "2024-02-29 23:59:59.900"
+        );
+        assert!(parse_timestamp("exception callback").is_none());
+        assert_eq!(format_duration_ms(500), "500ms");
+        assert_eq!(format_duration_ms(150_000), "2m 30s");
+        assert_eq!(format_duration_ms(3_720_000), "1h 2m");
+        assert_eq!(format_duration_ms(7_200_500), "2h");
+        assert!(parse_timestamp("2026-13-06 10:29").is_none());
+    }
+
+    #[test]
+    fn test_time_range() {
+        let range = TimeRange {
+            since: parse_timestamp("2026-01-06 10:00"),
+            until: parse_timestamp("2026-01-06 11:00"),
+        };
+        assert!(range.contains(parse_timestamp("2026-01-06 10:00").unwrap()));
+        assert!(range.contains(parse_timestamp("2026-01-06 10:59:59.999").unwrap()));
+        assert!(!range.contains(parse_timestamp("2026-01-06 11:00").unwrap()));
+        assert!(!range.contains(parse_timestamp("2026-01-06 09:59:59.999").unwrap()));
+        assert!(TimeRange::default().is_unbounded());
+        assert!(TimeRange::default().contains(0));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(parse_duration("30d"), Ok(Duration::from_secs(30 * 86_400)));
+        assert_eq!(parse_duration("60"), Ok(Duration::from_secs(60)));
+        assert!(parse_duration("5 parsecs").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(parse_utc_offset("+08:00"), Ok(8 * 3_600_000));
+        assert_eq!(parse_utc_offset("-0530"), Ok(-(5 * 3600 + 30 * 60) * 1000));
+        assert_eq!(parse_utc_offset("+8"), Ok(8 * 3_600_000));
+        assert_eq!(parse_utc_offset("Z"), Ok(0));
+        assert!(parse_utc_offset("08:00").is_err());
+        assert!(parse_utc_offset("+25:00").is_err());
+    }
+}
@@ -0,0 +1,48 @@
+use std::{sync::mpsc, thread, time::Duration};
+
+use anyhow::{Result, bail};
+
+/// 在独立线程中执行 `f`，超过 `timeout` 仍未返回时放弃等待并返回错误。
+/// 超时的线程无法被强制结束 (可能阻塞在 IO 上)，调用方需保证其稍后完成时不会造成破坏：
+/// 完成前检查是否已超时，输出文件用 [`InFlight`](crate::temp::InFlight) 登记，进程退出前由 main 清理
+pub fn with_timeout<T, F>(timeout: Option<Duration>, f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let Some(timeout) = timeout else {
+        return f();
+    };
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => bail!("timed out after {:?}", timeout),
+        Err(mpsc::RecvTimeoutError::Disconnected) => bail!("worker thread panicked"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_timeout() {
+        assert_eq!(with_timeout(None, || Ok(1)).unwrap(), 1);
+        assert_eq!(
+            with_timeout(Some(Duration::from_secs(5)), || Ok(2)).unwrap(),
+            2
+        );
+
+        let err = with_timeout(Some(Duration::from_millis(10)), || {
+            thread::sleep(Duration::from_secs(1));
+            Ok(())
+        })
+        .unwrap_err();
+        assert!(err.to_string().starts_with("timed out"));
+    }
+}
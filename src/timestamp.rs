@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use globset::{GlobBuilder, GlobMatcher};
+use serde::{Deserialize, Serialize};
+
+use crate::{context::AppContext, record::line_timestamp, time::parse_timestamp};
+
+/// 行首时间戳的格式，都按本地时间处理，不区分时区
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampFormat {
+    /// `[2026-01-06 10:29:10.765] ...`
+    #[default]
+    Bracket,
+    /// `2026-01-06T10:29:10.765Z ...` 或 `2026-01-06 10:29:10,765 ...`
+    Iso,
+    /// nginx/apache 的 `... [06/Jan/2026:10:29:10 +0800] ...`
+    Clf,
+    /// `1767695350 ...` 秒或 `1767695350765 ...` 毫秒
+    Epoch,
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+impl TimestampFormat {
+    /// 解析一行的时间戳为毫秒时间戳，没有时返回 `None`
+    pub fn parse(self, line: &str) -> Option<i64> {
+        match self {
+            TimestampFormat::Bracket => line_timestamp(line),
+            TimestampFormat::Iso => {
+                let line = line.trim_start();
+                let date = line.get(..10)?;
+                let rest = line.get(10..)?;
+                let separator = rest.chars().next().filter(|c| *c == 'T' || *c == ' ')?;
+                let time = rest[separator.len_utf8()..]
+                    .split(|c: char| !(c.is_ascii_digit() || c == ':' || c == '.' || c == ','))
+                    .next()?;
+                parse_timestamp(&format!("{date} {}", time.replace(',', ".")))
+            }
+            TimestampFormat::Clf => {
+                let start = line.find('[')? + 1;
+                let end = start + line[start..].find(']')?;
+                let (date, time) = line[start..end].split_once(':')?;
+                let time = time.split_whitespace().next()?;
+
+                let mut parts = date.splitn(3, '/');
+                let day = parts.next()?;
+                let month = parts.next()?;
+                let year = parts.next()?;
+                let month = MONTHS.iter().position(|m| *m == month)? + 1;
+                parse_timestamp(&format!("{year}-{month:02}-{day} {time}"))
+            }
+            TimestampFormat::Epoch => {
+                let token = line.split_whitespace().next()?;
+                let (secs, frac) = token.split_once('.').unwrap_or((token, ""));
+                if !secs.bytes().all(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                match secs.len() {
+                    13 => secs.parse().ok(),
+                    10 => {
+                        let millis = format!("{frac:0<3}");
+                        Some(
+                            secs.parse::<i64>().ok()? * 1000
+                                + millis.get(..3)?.parse::<i64>().ok()?,
+                        )
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// 配置中按路径指定的时间戳格式，如 `{ "pattern": "nginx/*.log", "format": "clf" }`；
+/// `pattern` 匹配文件名或相对根路径的路径
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TimestampRule {
+    pub pattern: String,
+    pub format: TimestampFormat,
+}
+
+/// 为每个文件选择时间戳格式，按配置顺序取第一个匹配的规则，都不匹配时为 bracket
+pub struct Timestamps {
+    root: Option<PathBuf>,
+    rules: Vec<(GlobMatcher, TimestampFormat)>,
+}
+
+impl Timestamps {
+    pub fn new(root: Option<PathBuf>, rules: &[TimestampRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                let glob = GlobBuilder::new(&rule.pattern)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|e| anyhow!("❌ invalid timestamp pattern `{}`: {e}", rule.pattern))?;
+                Ok((glob.compile_matcher(), rule.format))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Timestamps { root, rules })
+    }
+
+    /// 配置中的规则，`pattern` 相对当前根路径；配置文件不存在时都按 bracket 解析
+    pub fn load(ctx: &AppContext) -> Result<Self> {
+        let rules = ctx.config_or_default()?.timestamps;
+        if rules.is_empty() {
+            return Self::new(None, &[]);
+        }
+
+        Self::new(ctx.base_dir().ok().map(Path::to_path_buf), &rules)
+    }
+
+    pub fn format_for(&self, path: &Path) -> TimestampFormat {
+        let rel = self
+            .root
+            .as_ref()
+            .and_then(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+        let name = path.file_name().map(Path::new);
+
+        self.rules
+            .iter()
+            .find(|(matcher, _)| matcher.is_match(rel) || name.is_some_and(|n| matcher.is_match(n)))
+            .map_or_else(TimestampFormat::default, |(_, format)| *format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_format() {
+        let expected = parse_timestamp("2026-01-06 10:29:10.765");
+        let seconds = parse_timestamp("2026-01-06 10:29:10");
+        assert_eq!(
+            TimestampFormat::Bracket.parse("[2026-01-06 10:29:10.765] [info] [Global]  a"),
+            expected
+        );
+        assert_eq!(
+            TimestampFormat::Iso.parse("2026-01-06T10:29:10.765Z INFO a"),
+            expected
+        );
+        assert_eq!(
+            TimestampFormat::Iso.parse("2026-01-06 10:29:10,765 INFO a"),
+            expected
+        );
+        assert_eq!(
+            TimestampFormat::Iso.parse("2026-01-06T10:29:10-05:00 a"),
+            seconds
+        );
+        assert_eq!(
+            TimestampFormat::Clf
+                .parse(r#"127.0.0.1 - - [06/Jan/2026:10:29:10 +0800] "GET / HTTP/1.1" 200 612"#),
+            seconds
+        );
+        assert_eq!(TimestampFormat::Epoch.parse("1767695350.765 a"), expected);
+        assert_eq!(TimestampFormat::Epoch.parse("1767695350765 a"), expected);
+        assert_eq!(TimestampFormat::Epoch.parse("1767695350 a"), seconds);
+        assert!(TimestampFormat::Iso.parse("    at stack frame").is_none());
+        assert!(TimestampFormat::Clf.parse("[info] a").is_none());
+        assert!(TimestampFormat::Epoch.parse("42 a").is_none());
+
+        let rules = [
+            TimestampRule {
+                pattern: "nginx/*.log".to_string(),
+                format: TimestampFormat::Clf,
+            },
+            TimestampRule {
+                pattern: "*.jsonl".to_string(),
+                format: TimestampFormat::Epoch,
+            },
+        ];
+        let timestamps = Timestamps::new(Some(PathBuf::from("/var/log")), &rules).unwrap();
+        let format = |path: &str| timestamps.format_for(Path::new(path));
+        assert_eq!(format("/var/log/nginx/access.log"), TimestampFormat::Clf);
+        assert_eq!(
+            format("/var/log/nginx/old/access.log"),
+            TimestampFormat::Bracket
+        );
+        assert_eq!(format("/var/log/app/events.jsonl"), TimestampFormat::Epoch);
+        assert_eq!(format("/var/log/app/server.log"), TimestampFormat::Bracket);
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use rayon::prelude::*;
+
+use crate::{
+    context::AppContext,
+    entries::EntryFilter,
+    exit::Failures,
+    out_name::{OutName, output_path, parse_out_name},
+    pipeline::Pipeline,
+    record::{parse_line, replace_level},
+    subcommand::filtered_entries,
+    temp::InFlight,
+};
+
+#[derive(Parser)]
+pub struct TransformArgs {
+    /// 文件路径
+    #[arg(short, long)]
+    pub path: PathBuf,
+
+    /// 级别重映射规则 `原级别:消息关键字=>新级别`，原级别可用 `*`，
+    /// 如 `error:ERRCODE_MSOPTIMEOUT=>warn`，按顺序取第一条匹配的规则
+    #[arg(short, long = "remap", value_parser = parse_remap_rule)]
+    pub remap: Vec<RemapRule>,
+
+    /// 输出文件名模板，支持 {stem}、{ext}、{date}、{preset}、{profile}，如 `{stem}.{date}.clean.{ext}`；
+    /// 默认为 `{stem}_transformed.{ext}`
+    #[arg(long, value_name = "TEMPLATE", value_parser = parse_out_name)]
+    pub out_name: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct RemapRule {
+    from: String,
+    pattern: String,
+    to: String,
+}
+
+fn parse_remap_rule(s: &str) -> Result<RemapRule, String> {
+    let (rule, to) = s
+        .split_once("=>")
+        .ok_or_else(|| format!("invalid remap rule `{s}`, expected `level:pattern=>level`"))?;
+    let (from, pattern) = rule
+        .split_once(':')
+        .ok_or_else(|| format!("invalid remap rule `{s}`, expected `level:pattern=>level`"))?;
+
+    if from.trim().is_empty() || to.trim().is_empty() {
+        return Err(format!(
+            "invalid remap rule `{s}`, level should not be empty"
+        ));
+    }
+
+    Ok(RemapRule {
+        from: from.trim().to_string(),
+        pattern: pattern.to_string(),
+        to: to.trim().to_string(),
+    })
+}
+
+pub fn process_transform(ctx: &AppContext, args: TransformArgs) -> Result<()> {
+    if args.remap.is_empty() {
+        bail!("❌ no transform given, e.g. --remap 'error:ERRCODE_MSOPTIMEOUT=>warn'");
+    }
+
+    let path = ctx.resolve_path(args.path)?;
+    if !path.exists() {
+        bail!("❌ {} not exists", path.display());
+    }
+    let out_name = args
+        .out_name
+        .map(|template| OutName::new(ctx, template, &[]))
+        .transpose()?;
+
+    if path.is_dir() {
+        let failures = Failures::new(ctx);
+        let entries = EntryFilter::default().skip_outputs(out_name.as_ref())?;
+        filtered_entries(&path, &entries).par_iter().for_each(|e| {
+            if failures.should_stop() {
+                return;
+            }
+            let file_path = e.path();
+            if let Err(e) = transform_file(file_path, &args.remap, out_name.as_ref()) {
+                eprintln!("❌ transform failed, path {:?}, reason: {}", file_path, e);
+                failures.record(file_path, &e);
+            }
+        });
+        failures.finish()?;
+    } else {
+        transform_file(&path, &args.remap, out_name.as_ref())?;
+    }
+
+    Ok(())
+}
+
+fn transform_file(path: &Path, rules: &[RemapRule], out_name: Option<&OutName>) -> Result<()> {
+    let new_path = output_path(path, out_name, "_transformed")?;
+    let partial = InFlight::register(&new_path);
+    let mut remapped = 0;
+    Pipeline::new()
+        .transform(|line| match remap_line(&line, rules) {
+            Some(line) => {
+                remapped += 1;
+                line
+            }
+            None => line,
+        })
+        .sink(BufWriter::new(File::create(&new_path)?))
+        .run(BufReader::new(File::open(path)?))?;
+    partial.commit();
+    println!(
+        "write file after transform, path: {:?}, remapped lines: {}",
+        new_path.display(),
+        remapped
+    );
+
+    Ok(())
+}
+
+fn remap_line(line: &str, rules: &[RemapRule]) -> Option<String> {
+    let record = parse_line(line)?;
+    let rule = rules.iter().find(|rule| {
+        (rule.from == "*" || rule.from.eq_ignore_ascii_case(record.level))
+            && record.message.contains(rule.pattern.as_str())
+    })?;
+
+    replace_level(line, &rule.to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remap_line() {
+        let rules = [
+            parse_remap_rule("error:ERRCODE_MSOPTIMEOUT=>warn").unwrap(),
+            parse_remap_rule("info:GET:/api/model=>error").unwrap(),
+        ];
+
+        assert_eq!(
+            remap_line(
+                "[2026-01-06 10:29:10.765] [error] [Global]  exception callback: ERRCODE_MSOPTIMEOUT",
+                &rules
+            )
+            .as_deref(),
+            Some("[2026-01-06 10:29:10.765] [warn] [Global]  exception callback: ERRCODE_MSOPTIMEOUT")
+        );
+        assert_eq!(
+            remap_line(
+                "[2026-01-06 11:37:24.511] [info] [ModelServer]  GET:/api/model/path from 172.24.25.2",
+                &rules
+            )
+            .as_deref(),
+            Some("[2026-01-06 11:37:24.511] [error] [ModelServer]  GET:/api/model/path from 172.24.25.2")
+        );
+        assert!(
+            remap_line(
+                "[2026-01-06 10:29:10.765] [info] [Global]  exception callback: ERRCODE_MSOPTIMEOUT",
+                &rules
+            )
+            .is_none()
+        );
+        assert!(parse_remap_rule("error=>warn").is_err());
+    }
+
+    #[test]
+    fn test_transform_dir_skips_outputs() {
+        let dir = std::env::temp_dir().join(format!("lp_transform_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("app.log"),
+            "[2026-01-06 10:29:10.765] [error] [Global]  a\n",
+        )
+        .unwrap();
+        let ctx = AppContext::new("/nonexistent/config.json");
+        let args = || TransformArgs {
+            path: dir.clone(),
+            remap: vec![parse_remap_rule("error:a=>warn").unwrap()],
+            out_name: None,
+        };
+
+        // 第二次运行时目录中已有上次的结果，不再对其处理
+        process_transform(&ctx, args()).unwrap();
+        process_transform(&ctx, args()).unwrap();
+        let mut names = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["app.log", "app_transformed.log"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
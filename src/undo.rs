@@ -0,0 +1,359 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Component, Path, PathBuf},
+    process,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+use crate::{context::AppContext, table::print_table, time::format_timestamp};
+
+const UNDO_FILE: &str = "undo.jsonl";
+
+/// 被改写文件的原文件暂存在其所在目录下的该目录中，遍历文件夹时跳过
+pub const UNDO_DIR: &str = ".lp_undo";
+
+#[derive(Parser)]
+pub struct UndoArgs {
+    /// 撤销最近的 N 次操作，从最近的开始
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub last: u64,
+
+    /// 只列出可以撤销的操作，不执行
+    #[arg(long, default_value_t = false, conflicts_with = "purge")]
+    pub list: bool,
+
+    /// 清空操作日志并删除暂存的原文件，之后无法再撤销
+    #[arg(long, default_value_t = false)]
+    pub purge: bool,
+}
+
+/// 一次可撤销的操作，如一次 `rl --in-place` 或 `rf`
+#[derive(Serialize, Deserialize)]
+struct Operation {
+    id: String,
+    /// 秒级时间戳
+    time: i64,
+    command: String,
+    items: Vec<UndoItem>,
+}
+
+/// 一个被改写或删除的路径：`saved` 为原文件暂存或备份的位置，为空时表示已移到系统回收站
+#[derive(Serialize, Deserialize)]
+struct UndoItem {
+    path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    saved: Option<PathBuf>,
+}
+
+/// 路径是否位于暂存目录中
+pub fn is_stashed(path: &Path) -> bool {
+    path.components()
+        .any(|c| matches!(c, Component::Normal(name) if name == UNDO_DIR))
+}
+
+/// 一次命令执行中记录可撤销的改动，文件夹模式下并行处理的文件记为同一次操作
+pub struct UndoLog {
+    id: String,
+    time: i64,
+    command: String,
+    items: Mutex<Vec<UndoItem>>,
+}
+
+impl UndoLog {
+    pub fn new(command: &str) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        UndoLog {
+            id: format!("{}-{}", now.as_millis(), process::id()),
+            time: now.as_secs() as i64,
+            command: command.to_string(),
+            items: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// `path` 被改写前原文件的暂存位置：同目录下的 `.lp_undo/<操作 id>/<文件名>`，与原文件在同一文件系统
+    pub fn stash_path(&self, path: &Path) -> Result<PathBuf> {
+        let dir = path
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(UNDO_DIR)
+            .join(&self.id);
+        fs::create_dir_all(&dir)?;
+
+        Ok(dir.join(path.file_name().unwrap_or_default()))
+    }
+
+    /// 原文件已移到 `saved` (暂存或 `--backup` 的备份)
+    pub fn saved(&self, path: &Path, saved: &Path) {
+        self.items.lock().unwrap().push(UndoItem {
+            path: path.to_path_buf(),
+            saved: Some(saved.to_path_buf()),
+        });
+    }
+
+    /// 已移到系统回收站
+    pub fn trashed(&self, path: &Path) {
+        self.items.lock().unwrap().push(UndoItem {
+            path: path.to_path_buf(),
+            saved: None,
+        });
+    }
+
+    /// 有改动时追加到操作日志，命令中途失败时也应调用，已完成的改动仍可撤销
+    pub fn save(&self, ctx: &AppContext) -> Result<()> {
+        let items = std::mem::take(&mut *self.items.lock().unwrap());
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let operation = Operation {
+            id: self.id.clone(),
+            time: self.time,
+            command: self.command.clone(),
+            items,
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(ctx.config_file(UNDO_FILE)?)?;
+        file.lock()?;
+        writeln!(file, "{}", serde_json::to_string(&operation)?)?;
+
+        Ok(())
+    }
+}
+
+fn read_operations(file: &File) -> Result<Vec<Operation>> {
+    BufReader::new(file)
+        .lines()
+        .filter(|line| line.as_ref().is_ok_and(|line| !line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+fn write_operations(path: &Path, operations: &[Operation]) -> Result<()> {
+    let mut content = String::new();
+    for operation in operations {
+        content.push_str(&serde_json::to_string(operation)?);
+        content.push('\n');
+    }
+    fs::write(path, content)?;
+
+    Ok(())
+}
+
+/// 暂存文件所在的 `.lp_undo/<操作 id>` 目录，`--backup` 的备份不在暂存目录中
+fn stash_dir(saved: &Path) -> Option<&Path> {
+    saved
+        .parent()
+        .filter(|dir| dir.parent().and_then(Path::file_name) == Some(UNDO_DIR.as_ref()))
+}
+
+/// 删除已经为空的暂存目录，`.lp_undo` 为空时一并删除
+fn remove_empty_stash(saved: &Path) {
+    if let Some(dir) = stash_dir(saved)
+        && fs::remove_dir(dir).is_ok()
+    {
+        let _ = fs::remove_dir(dir.parent().unwrap());
+    }
+}
+
+/// 把原文件恢复到原处，覆盖改写后的文件：暂存的移回，`--backup` 的备份是用户要保留的文件，复制回去
+fn restore_saved(path: &Path, saved: &Path) -> Result<()> {
+    if !saved.exists() {
+        bail!("❌ saved copy {} no longer exists", saved.display());
+    }
+    if stash_dir(saved).is_none() {
+        fs::copy(saved, path)?;
+        return Ok(());
+    }
+    fs::rename(saved, path)?;
+    remove_empty_stash(saved);
+
+    Ok(())
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+fn restore_trashed(path: &Path) -> Result<()> {
+    // 同一路径多次移到回收站时恢复最近的一次
+    let item = trash::os_limited::list()?
+        .into_iter()
+        .filter(|item| item.original_path() == path)
+        .max_by_key(|item| item.time_deleted);
+    let Some(item) = item else {
+        bail!("❌ {} is no longer in the trash", path.display());
+    };
+    if path.exists() {
+        bail!("❌ {} already exists, not restored", path.display());
+    }
+    trash::os_limited::restore_all([item])?;
+
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+fn restore_trashed(path: &Path) -> Result<()> {
+    bail!(
+        "❌ restoring from the trash is not supported on this platform, restore {} from the system trash",
+        path.display()
+    );
+}
+
+/// 逆序恢复一次操作中的各个路径，返回恢复失败而保留在日志中的项
+fn undo_items(items: Vec<UndoItem>) -> Vec<UndoItem> {
+    let mut remaining = Vec::new();
+    for item in items.into_iter().rev() {
+        let result = match &item.saved {
+            Some(saved) => restore_saved(&item.path, saved),
+            None => restore_trashed(&item.path),
+        };
+        match result {
+            Ok(()) => println!("restored, path: {:?}", item.path.display()),
+            Err(e) => {
+                eprintln!(
+                    "❌ restore failed, path: {:?}, reason: {}",
+                    item.path.display(),
+                    e
+                );
+                remaining.push(item);
+            }
+        }
+    }
+    remaining.reverse();
+
+    remaining
+}
+
+pub fn process_undo(ctx: &AppContext, args: UndoArgs) -> Result<()> {
+    let path = ctx.config_file(UNDO_FILE)?;
+    if !path.exists() {
+        println!("nothing to undo");
+        return Ok(());
+    }
+    if !args.list {
+        ctx.ensure_writable("undo")?;
+    }
+
+    let file = OpenOptions::new().read(true).write(true).open(&path)?;
+    file.lock()?;
+    let mut operations = read_operations(&file)?;
+
+    if args.list {
+        let rows = operations
+            .iter()
+            .rev()
+            .map(|op| {
+                let trashed = op.items.iter().filter(|item| item.saved.is_none()).count();
+                vec![
+                    op.id.clone(),
+                    format_timestamp(op.time * 1000)[..19].to_string(),
+                    op.command.clone(),
+                    op.items.len().to_string(),
+                    trashed.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        print_table(&["id", "time (UTC)", "command", "paths", "trashed"], &rows);
+        return Ok(());
+    }
+
+    if args.purge {
+        for item in operations.iter().flat_map(|op| &op.items) {
+            if let Some(dir) = item.saved.as_deref().and_then(stash_dir) {
+                let _ = fs::remove_dir_all(dir);
+                let _ = fs::remove_dir(dir.parent().unwrap());
+            }
+        }
+        println!("purged {} operations", operations.len());
+        return write_operations(&path, &[]);
+    }
+
+    if operations.is_empty() {
+        println!("nothing to undo");
+        return Ok(());
+    }
+    let keep = operations.len().saturating_sub(args.last as usize);
+    let mut failed = Vec::new();
+    for mut operation in operations.split_off(keep).into_iter().rev() {
+        println!("undo {} ({})", operation.command, operation.id);
+        operation.items = undo_items(operation.items);
+        if !operation.items.is_empty() {
+            failed.push(operation);
+        }
+    }
+    let failed_paths = failed.iter().map(|op| op.items.len()).sum::<usize>();
+    operations.extend(failed.into_iter().rev());
+    write_operations(&path, &operations)?;
+
+    if failed_paths > 0 {
+        bail!("❌ {failed_paths} paths not restored, they are kept in the undo log");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_undo_saved() {
+        let dir = env::temp_dir().join(format!("lp_undo_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let ctx = AppContext::new(dir.join("config.json"));
+        let path = dir.join("a.log");
+        fs::write(&path, "old\n").unwrap();
+
+        let undo = UndoLog::new("rl");
+        let stash = undo.stash_path(&path).unwrap();
+        assert!(is_stashed(&stash));
+        assert!(!is_stashed(&path));
+        fs::rename(&path, &stash).unwrap();
+        fs::write(&path, "new\n").unwrap();
+        undo.saved(&path, &stash);
+        undo.save(&ctx).unwrap();
+        // 没有新的改动时不再写入
+        undo.save(&ctx).unwrap();
+
+        let file = File::open(ctx.config_file(UNDO_FILE).unwrap()).unwrap();
+        let mut operations = read_operations(&file).unwrap();
+        assert_eq!(operations.len(), 1);
+        assert!(undo_items(operations.pop().unwrap().items).is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old\n");
+        assert!(!dir.join(UNDO_DIR).exists());
+
+        // `--backup` 的备份恢复后仍然保留
+        let backup = dir.join("a.log.bak");
+        fs::copy(&path, &backup).unwrap();
+        fs::write(&path, "new\n").unwrap();
+        restore_saved(&path, &backup).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "old\n");
+        assert!(backup.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+/// 解析 `512`、`100K`、`100M`、`10GB` 形式的大小，按 1024 进制
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value = value
+        .parse::<f64>()
+        .map_err(|_| format!("invalid size: {s}"))?;
+
+    let power = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" => 1,
+        "M" | "MB" => 2,
+        "G" | "GB" => 3,
+        "T" | "TB" => 4,
+        unit => return Err(format!("unknown size unit `{unit}`, expected B/K/M/G/T")),
+    };
+
+    Ok((value * 1024_f64.powi(power)) as u64)
+}
+
+/// 解析 `0.01`、`5%` 形式的比例，范围 (0, 1]
+pub fn parse_fraction(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let value = match s.strip_suffix('%') {
+        Some(percent) => percent.trim().parse::<f64>().map(|v| v / 100.0),
+        None => s.parse::<f64>(),
+    }
+    .map_err(|_| format!("invalid fraction: {s}"))?;
+    if !(value > 0.0 && value <= 1.0) {
+        return Err(format!("fraction should be in (0, 1], got {s}"));
+    }
+
+    Ok(value)
+}
+
+pub fn format_size(size: u64) -> String {
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{size} B")
+    } else {
+        format!("{value:.2} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("100K"), Ok(100 * 1024));
+        assert_eq!(parse_size("1.5M"), Ok(1536 * 1024));
+        assert_eq!(parse_size("10GB"), Ok(10 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size(" 2 kb "), Ok(2048));
+        assert_eq!(parse_size("1T"), Ok(1 << 40));
+        assert!(parse_size("10 parsecs").is_err());
+        assert!(parse_size("").is_err());
+        assert!(parse_size("-1K").is_err());
+
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.00 KB");
+        assert_eq!(format_size(1536 * 1024), "1.50 MB");
+        // 超过 TB 时不再进位
+        assert_eq!(format_size(1 << 50), "1024.00 TB");
+
+        assert_eq!(parse_fraction("0.01"), Ok(0.01));
+        assert_eq!(parse_fraction("5%"), Ok(0.05));
+        assert_eq!(parse_fraction(" 100% "), Ok(1.0));
+        assert_eq!(parse_fraction("1"), Ok(1.0));
+        assert!(parse_fraction("0").is_err());
+        assert!(parse_fraction("0%").is_err());
+        assert!(parse_fraction("1.5").is_err());
+        assert!(parse_fraction("-5%").is_err());
+        assert!(parse_fraction("half").is_err());
+    }
+}
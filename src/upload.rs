@@ -0,0 +1,243 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audit::sha256_file,
+    config::lock_config,
+    context::AppContext,
+    temp::InFlight,
+    units::{format_size, parse_size},
+};
+
+/// 未完成的上传，键为 `endpoint#sha256`，值为服务端的上传 id，用于续传
+const UPLOADS_FILE: &str = "uploads.json";
+
+#[derive(Parser)]
+pub struct UploadArgs {
+    /// 要上传的文件，如 bundle 生成的 zip
+    pub artifact: PathBuf,
+
+    /// 制品服务地址，如 https://artifacts.internal
+    #[arg(long)]
+    pub endpoint: String,
+
+    /// 每次请求上传的大小
+    #[arg(long, default_value = "8M", value_parser = parse_size)]
+    pub chunk_size: u64,
+
+    /// 生成该文件使用的关键字，记录在元数据中
+    #[arg(short, long)]
+    pub filter: Vec<String>,
+
+    /// 生成该文件的源路径，记录在元数据中
+    #[arg(long)]
+    pub source: Vec<PathBuf>,
+
+    /// 忽略未完成的上传，重新开始
+    #[arg(long, default_value_t = false)]
+    pub restart: bool,
+}
+
+/// 开始上传时发送的元数据
+#[derive(Serialize)]
+struct UploadMetadata<'a> {
+    name: String,
+    size: u64,
+    sha256: &'a str,
+    chunk_size: u64,
+    filters: &'a [String],
+    sources: &'a [PathBuf],
+    tool_version: &'static str,
+}
+
+/// 服务端返回的上传状态
+#[derive(Deserialize)]
+struct UploadSession {
+    id: String,
+    /// 已接收的字节数
+    #[serde(default)]
+    received: u64,
+}
+
+/// 制品服务的分块上传协议：
+/// - `POST {endpoint}/uploads` 发送元数据，返回 `{"id": "...", "received": 0}`
+/// - `GET {endpoint}/uploads/{id}` 返回同样的结构，续传时从 `received` 处继续
+/// - `PUT {endpoint}/uploads/{id}` 上传一块，`Content-Range: bytes start-end/total`
+/// - `POST {endpoint}/uploads/{id}/complete` 结束上传，由服务端校验大小与 sha256
+struct ArtifactStore<'a> {
+    endpoint: &'a str,
+}
+
+impl ArtifactStore<'_> {
+    fn create(&self, metadata: &UploadMetadata) -> Result<UploadSession> {
+        let mut response = ureq::post(format!("{}/uploads", self.endpoint))
+            .header("Content-Type", "application/json")
+            .send(serde_json::to_string(metadata)?)?;
+        Ok(serde_json::from_str(
+            &response.body_mut().read_to_string()?,
+        )?)
+    }
+
+    fn status(&self, id: &str) -> Result<UploadSession> {
+        let mut response = ureq::get(format!("{}/uploads/{id}", self.endpoint)).call()?;
+        Ok(serde_json::from_str(
+            &response.body_mut().read_to_string()?,
+        )?)
+    }
+
+    fn put_chunk(&self, id: &str, start: u64, chunk: &[u8], total: u64) -> Result<()> {
+        ureq::put(format!("{}/uploads/{id}", self.endpoint))
+            .header("Content-Type", "application/octet-stream")
+            .header(
+                "Content-Range",
+                content_range(start, chunk.len() as u64, total),
+            )
+            .send(chunk)?;
+        Ok(())
+    }
+
+    fn complete(&self, id: &str) -> Result<()> {
+        ureq::post(format!("{}/uploads/{id}/complete", self.endpoint)).send("")?;
+        Ok(())
+    }
+}
+
+/// `bytes start-end/total`，`end` 包含在内
+fn content_range(start: u64, len: u64, total: u64) -> String {
+    format!("bytes {start}-{}/{total}", start + len - 1)
+}
+
+fn read_uploads(path: &Path) -> Result<BTreeMap<String, String>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// 修改未完成上传的记录
+fn update_uploads(ctx: &AppContext, f: impl FnOnce(&mut BTreeMap<String, String>)) -> Result<()> {
+    // 与更新配置相同：加锁后读改写，先写临时文件再替换，并发上传不会丢记录，中断也不会留下半个文件
+    let path = ctx.config_file(UPLOADS_FILE)?;
+    let _lock = lock_config(&path, true)?;
+    let mut uploads = read_uploads(&path)?;
+    f(&mut uploads);
+
+    let tmp = InFlight::register(path.with_extension("json.tmp"));
+    fs::write(tmp.path(), serde_json::to_string_pretty(&uploads)?)?;
+    fs::rename(tmp.path(), &path)?;
+    tmp.commit();
+
+    Ok(())
+}
+
+pub fn process_upload(ctx: &AppContext, args: UploadArgs) -> Result<()> {
+    if args.chunk_size == 0 {
+        bail!("❌ --chunk-size should be greater than 0");
+    }
+    let artifact = ctx.resolve_path(args.artifact)?;
+    if !artifact.is_file() {
+        bail!("❌ {} is not a file", artifact.display());
+    }
+
+    let size = artifact.metadata()?.len();
+    let sha256 = sha256_file(&artifact)?;
+    let store = ArtifactStore {
+        endpoint: args.endpoint.trim_end_matches('/'),
+    };
+    let key = format!("{}#{sha256}", store.endpoint);
+
+    // 同一文件上传到同一地址时，从服务端已接收的位置继续
+    let previous = read_uploads(&ctx.config_file(UPLOADS_FILE)?)?.remove(&key);
+    let resumed = previous
+        .filter(|_| !args.restart)
+        .and_then(|id| match store.status(&id) {
+            Ok(session) => Some(session),
+            Err(e) => {
+                println!("⚠️ cannot resume upload {id}, start over, reason: {e}");
+                None
+            }
+        });
+    let session = match resumed {
+        Some(session) => {
+            println!(
+                "resume upload {}, {} of {} already received",
+                session.id,
+                format_size(session.received),
+                format_size(size)
+            );
+            session
+        }
+        None => {
+            let session = store
+                .create(&UploadMetadata {
+                    name: artifact
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned(),
+                    size,
+                    sha256: &sha256,
+                    chunk_size: args.chunk_size,
+                    filters: &args.filter,
+                    sources: &args.source,
+                    tool_version: env!("CARGO_PKG_VERSION"),
+                })
+                .context("❌ failed to start upload")?;
+            update_uploads(ctx, |uploads| {
+                uploads.insert(key.clone(), session.id.clone());
+            })?;
+            session
+        }
+    };
+
+    let mut file = File::open(&artifact)?;
+    let mut offset = session.received.min(size);
+    file.seek(SeekFrom::Start(offset))?;
+    let mut chunk = Vec::with_capacity(args.chunk_size.min(size) as usize);
+    while offset < size {
+        chunk.clear();
+        (&mut file).take(args.chunk_size).read_to_end(&mut chunk)?;
+        if chunk.is_empty() {
+            bail!("❌ {} was truncated during upload", artifact.display());
+        }
+        store
+            .put_chunk(&session.id, offset, &chunk, size)
+            .with_context(|| format!("❌ upload failed at {offset} bytes, run again to resume"))?;
+        offset += chunk.len() as u64;
+        println!("uploaded {} / {}", format_size(offset), format_size(size));
+    }
+
+    store
+        .complete(&session.id)
+        .context("❌ failed to complete upload, run again to retry")?;
+    update_uploads(ctx, |uploads| {
+        uploads.remove(&key);
+    })?;
+    println!(
+        "upload finished, path: {:?}, id: {}, sha256: {sha256}",
+        artifact.display(),
+        session.id
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_range() {
+        assert_eq!(content_range(0, 8, 20), "bytes 0-7/20");
+        assert_eq!(content_range(16, 4, 20), "bytes 16-19/20");
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Result, bail};
+use clap::{Parser, ValueEnum};
+use notify::{RecursiveMode, Watcher};
+
+use crate::{
+    compress::plain_path,
+    context::AppContext,
+    matcher::{MatchArgs, Matcher},
+    record::Boundary,
+    subcommand::{check_log_file_cpu_mem_info, get_entries, remove_file_lines},
+    time::parse_duration,
+};
+
+/// 没有收到文件事件时重新扫描目录的间隔，兼容不支持 inotify 的文件系统 (如网络共享)
+const RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 对新增/修改的日志执行的操作
+#[derive(Clone, Copy, ValueEnum)]
+pub enum WatchAction {
+    /// 同 cl，输出命中行数
+    Check,
+    /// 同 rl，生成 `_filtered` 文件
+    Remove,
+}
+
+#[derive(Parser)]
+pub struct WatchArgs {
+    /// 监听的文件夹，默认为 base dir
+    pub path: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub matching: MatchArgs,
+
+    /// 对新增/修改的 .log 文件执行的操作
+    #[arg(long, value_enum, default_value = "remove")]
+    pub action: WatchAction,
+
+    /// remove 时保留而不是过滤掉命中的行
+    #[arg(short, long, default_value_t = false)]
+    pub keep: bool,
+
+    /// 文件最后一次修改后至少等待多久再处理，避免处理还在写入的文件
+    #[arg(long, value_name = "DURATION", default_value = "2s", value_parser = parse_duration)]
+    pub settle: Duration,
+
+    /// 启动时也处理目录中已有的文件，默认只处理启动后新增或修改的文件
+    #[arg(long, default_value_t = false)]
+    pub initial: bool,
+}
+
+/// 需要处理的日志文件：`.log` 与 `.log.gz`，不含 `_filtered` 输出
+fn is_watched_log(path: &Path) -> bool {
+    plain_path(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("log"))
+}
+
+/// 记录每个文件处理时的修改时间，只处理修改时间变化且已经稳定的文件
+#[derive(Default)]
+struct DropFolder {
+    seen: HashMap<PathBuf, SystemTime>,
+}
+
+impl DropFolder {
+    fn changed(
+        &mut self,
+        files: Vec<(PathBuf, SystemTime)>,
+        now: SystemTime,
+        settle: Duration,
+    ) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, modified) in files {
+            if self.seen.get(&path) != Some(&modified)
+                && now.duration_since(modified).unwrap_or_default() >= settle
+            {
+                self.seen.insert(path.clone(), modified);
+                changed.push(path);
+            }
+        }
+
+        changed
+    }
+}
+
+fn scan(dir: &Path) -> Vec<(PathBuf, SystemTime)> {
+    get_entries(dir)
+        .into_iter()
+        .filter(|e| is_watched_log(e.path()))
+        .filter_map(|e| {
+            let modified = e.metadata().ok()?.modified().ok()?;
+            Some((e.into_path(), modified))
+        })
+        .collect()
+}
+
+fn handle(ctx: &AppContext, path: &Path, matcher: &Matcher, args: &WatchArgs) -> Result<()> {
+    match args.action {
+        WatchAction::Check => {
+            let summary = check_log_file_cpu_mem_info(path, matcher, &Boundary::Line, None, None)?;
+            println!(
+                "file: {}, keyword lines: {}",
+                summary.path.display(),
+                summary.matches
+            );
+        }
+        WatchAction::Remove => remove_file_lines(ctx, path, matcher, args.keep)?,
+    }
+
+    Ok(())
+}
+
+pub fn process_watch(ctx: &AppContext, args: WatchArgs) -> Result<()> {
+    let dir = match &args.path {
+        Some(path) => ctx.resolve_path(path.clone())?,
+        None => ctx.base_dir()?.to_path_buf(),
+    };
+    if !dir.is_dir() {
+        bail!("❌ {} is not a directory", dir.display());
+    }
+
+    let command = match args.action {
+        WatchAction::Check => "cl",
+        WatchAction::Remove => "rl",
+    };
+    let filters = args.matching.keywords(ctx, command)?;
+    let matcher = args.matching.matcher(&filters)?;
+
+    let mut folder = DropFolder::default();
+    if !args.initial {
+        for (path, modified) in scan(&dir) {
+            folder.seen.insert(path, modified);
+        }
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(&dir, RecursiveMode::Recursive)?;
+    println!("watching {}", dir.display());
+
+    // 事件只用于唤醒，统一按修改时间判断哪些文件需要处理；
+    // 还没有稳定的文件在下一次唤醒时再检查
+    let mut wait = RESCAN_INTERVAL;
+    loop {
+        let files = scan(&dir);
+        for path in folder.changed(files.clone(), SystemTime::now(), args.settle) {
+            if let Err(e) = handle(ctx, &path, &matcher, &args) {
+                eprintln!("❌ watch failed, path {:?}, reason: {}", path, e);
+            }
+        }
+
+        if files
+            .iter()
+            .any(|(path, modified)| folder.seen.get(path) != Some(modified))
+        {
+            wait = args.settle.min(RESCAN_INTERVAL);
+        }
+
+        match rx.recv_timeout(wait) {
+            Ok(Err(e)) => eprintln!("❌ watch failed, path {:?}, reason: {}", dir, e),
+            Ok(Ok(_)) | Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => bail!("❌ watcher stopped"),
+        }
+        wait = RESCAN_INTERVAL;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_folder() {
+        assert!(is_watched_log(Path::new("/drop/device1.log")));
+        assert!(is_watched_log(Path::new("/drop/device1.log.gz")));
+        assert!(!is_watched_log(Path::new("/drop/device1.txt")));
+
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        let settle = Duration::from_secs(2);
+        let path = PathBuf::from("/drop/device1.log");
+        let mut folder = DropFolder::default();
+
+        // 刚写入的文件等稳定后再处理，处理过的文件修改后再次处理
+        assert!(
+            folder
+                .changed(vec![(path.clone(), t0)], t0, settle)
+                .is_empty()
+        );
+        let later = t0 + settle;
+        assert_eq!(
+            folder.changed(vec![(path.clone(), t0)], later, settle),
+            vec![path.clone()]
+        );
+        assert!(
+            folder
+                .changed(vec![(path.clone(), t0)], later, settle)
+                .is_empty()
+        );
+        let modified = t0 + Duration::from_secs(10);
+        assert_eq!(
+            folder.changed(vec![(path.clone(), modified)], modified + settle, settle),
+            vec![path]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::Result;
+use clap::Parser;
+
+use crate::context::AppContext;
+
+#[derive(Parser)]
+pub struct WithArgs {
+    /// 要执行的子命令及其参数，写在 `--` 之后，如 `lp with --base-dir /tmp/case123 -- cl -p logs`
+    #[arg(last = true, required = true)]
+    pub command: Vec<String>,
+}
+
+/// `lp with` 期间使用的配置副本：从当前配置复制，子命令对配置、历史记录等的修改只写到副本，
+/// 结束后删除，同一台机器上并行的排查互不影响
+pub struct ConfigOverlay {
+    dir: PathBuf,
+}
+
+/// 同一进程中创建的副本编号，副本目录互不重叠
+static OVERLAY_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+impl ConfigOverlay {
+    pub fn new(ctx: &AppContext) -> Result<Self> {
+        let dir = env::temp_dir().join(format!(
+            "lp_with_{}_{}",
+            process::id(),
+            OVERLAY_SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        let overlay = ConfigOverlay { dir };
+        if ctx.config_path().exists() {
+            fs::copy(ctx.config_path(), overlay.config_path())?;
+        }
+
+        Ok(overlay)
+    }
+
+    fn config_path(&self) -> PathBuf {
+        self.dir.join("config.json")
+    }
+
+    /// 使用副本配置的上下文，根路径与 `ctx` 一致，不存在时创建；
+    /// 根路径同时写入副本配置，直接读取配置的命令也不会落到全局的根路径
+    pub fn context(&self, ctx: &AppContext) -> Result<AppContext> {
+        let base_dir = ctx.base_dir()?.to_path_buf();
+        fs::create_dir_all(&base_dir)?;
+        let nested = ctx.clone().with_config_path(self.config_path());
+        nested.update_config(|config| config.base_dir = base_dir)?;
+
+        Ok(nested)
+    }
+}
+
+impl Drop for ConfigOverlay {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clean::{CleanArgs, process_clean};
+
+    #[test]
+    fn test_config_overlay() {
+        let dir = env::temp_dir().join(format!("lp_with_test_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.json");
+        fs::write(&config_path, r#"{ "threads": 2 }"#).unwrap();
+        let ctx = AppContext::new(&config_path).with_base_dir(dir.join("case123"));
+
+        let overlay = ConfigOverlay::new(&ctx).unwrap();
+        let overlay_dir = overlay.dir.clone();
+        let nested = overlay.context(&ctx).unwrap();
+        assert!(dir.join("case123").is_dir());
+        assert_eq!(nested.base_dir().unwrap(), dir.join("case123"));
+        assert_eq!(nested.threads().unwrap(), Some(2));
+        assert_eq!(nested.load_config().unwrap().base_dir, dir.join("case123"));
+
+        nested
+            .update_config(|config| config.threads = Some(8))
+            .unwrap();
+        assert_eq!(nested.threads().unwrap(), Some(8));
+        assert_eq!(ctx.threads().unwrap(), Some(2));
+
+        drop(overlay);
+        assert!(!overlay_dir.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_with_clean() {
+        let dir = env::temp_dir().join(format!("lp_with_clean_test_{}", process::id()));
+        let global = dir.join("global");
+        let case = dir.join("case");
+        fs::create_dir_all(&global).unwrap();
+        fs::create_dir_all(&case).unwrap();
+        fs::write(global.join("a.log"), "a\n").unwrap();
+        fs::write(case.join("a.log"), "a\n").unwrap();
+        let config_path = dir.join("config.json");
+        fs::write(
+            &config_path,
+            format!(
+                r#"{{ "base_dir": {:?}, "retention": [{{ "pattern": "*.log", "keep_last": 0 }}] }}"#,
+                global
+            ),
+        )
+        .unwrap();
+
+        // 相当于 `lp with --base-dir <case> -- clean --apply-policy`
+        let ctx = AppContext::new(&config_path).with_base_dir(&case);
+        let overlay = ConfigOverlay::new(&ctx).unwrap();
+        let args = CleanArgs {
+            apply_policy: true,
+            dry_run: false,
+        };
+        process_clean(&overlay.context(&ctx).unwrap(), args).unwrap();
+        assert!(!case.join("a.log").exists());
+        assert!(global.join("a.log").exists());
+
+        drop(overlay);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
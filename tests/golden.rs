@@ -0,0 +1,22 @@
+use std::{env, fs, process::Command};
+
+/// 逐个运行 `tests/fixtures/` 下的用例，与 `lp selftest` 使用同一套用例
+#[test]
+fn golden_fixtures() {
+    // selftest 本身也会记录运行历史，配置放在临时目录，不在仓库中生成 config/
+    let dir = env::temp_dir().join(format!("lp_golden_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_log_process_cli"))
+        .env("LP_CONFIG", dir.join("config.json"))
+        .args(["selftest", "--fixtures"])
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"))
+        .output()
+        .unwrap();
+    let _ = fs::remove_dir_all(&dir);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}